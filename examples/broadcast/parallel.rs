@@ -0,0 +1,37 @@
+mod process;
+
+use flurry::{
+    workload::{Broadcast, Driver},
+    Explorer,
+};
+use process::BroadcastProcess;
+
+fn make_system(proc_cnt: usize) -> (flurry::System, Driver<Broadcast>) {
+    let mut sys = flurry::System::default();
+    for proc in 0..proc_cnt {
+        sys.add_process(BroadcastProcess::new(proc));
+    }
+    (sys, Driver::new(Broadcast::new(proc_cnt, 1)))
+}
+
+fn main() {
+    let threads = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("number of threads expected"));
+    let mut explorer = Explorer::new(|| make_system(3));
+    if let Some(threads) = threads {
+        explorer = explorer.with_threads(threads);
+    }
+    let report = explorer.explore();
+    if let Some(violation) = report.violation {
+        println!("Failed: {}", violation.error);
+        println!("Path: {:?}", violation.path);
+        return;
+    }
+    println!("Processed {} states", report.states);
+    println!("Elapsed time: {:?}", report.elapsed);
+    println!(
+        "Processed/s: {}",
+        (report.states as f64) / report.elapsed.as_secs_f64()
+    );
+}
@@ -1,33 +1,75 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
+use flurry::{
+    json::Value,
+    workload::{node_id, parse_node_id},
+};
+
+/// Node of the Maelstrom broadcast workload,
+/// which forwards every new message to its neighbours.
 pub struct BroadcastProcess {
-    pub others: Vec<flurry::ProcessId>,
-    pub delivered: HashSet<String>,
+    pub me: flurry::ProcessId,
+    pub neighbours: Vec<flurry::ProcessId>,
+    pub seen: BTreeSet<u64>,
 }
 
-impl flurry::Process for BroadcastProcess {
-    fn on_message(&mut self, from: flurry::ProcessId, msg: String) {
-        if self.delivered.contains(&msg) {
+impl BroadcastProcess {
+    pub fn new(me: flurry::ProcessId) -> Self {
+        Self {
+            me,
+            neighbours: Vec::new(),
+            seen: BTreeSet::new(),
+        }
+    }
+
+    fn gossip(&mut self, msg: u64, from: Option<flurry::ProcessId>) {
+        if !self.seen.insert(msg) {
             return;
         }
-        for to in self.others.iter() {
-            if *to != from {
+        for to in self.neighbours.iter() {
+            if Some(*to) != from {
                 let to = *to;
-                let msg = msg.clone();
-                flurry::spawn(async move { flurry::send(to, msg).await });
+                flurry::spawn(async move { flurry::send(to, msg.to_string()).await });
             }
         }
-        self.delivered.insert(msg.clone());
-        flurry::send_local(msg.clone());
+    }
+}
+
+impl flurry::Process for BroadcastProcess {
+    fn on_message(&mut self, from: flurry::ProcessId, msg: String) {
+        self.gossip(msg.parse().unwrap(), Some(from));
     }
 
     fn on_local_message(&mut self, msg: &str) {
-        for to in self.others.iter() {
-            let to = *to;
-            let msg = msg.to_string();
-            flurry::spawn(async move { flurry::send(to, msg).await });
+        let body = Value::parse(msg).unwrap();
+        let mut reply = Value::object([("in_reply_to", body.get("msg_id").unwrap().clone())]);
+        match body.get("type").and_then(Value::as_str).unwrap() {
+            "topology" => {
+                let topology = body.get("topology").unwrap();
+                self.neighbours = topology
+                    .get(&node_id(self.me))
+                    .and_then(Value::as_array)
+                    .unwrap_or(&[])
+                    .iter()
+                    .filter_map(|node| parse_node_id(node.as_str()?))
+                    .collect();
+                reply.set("type", "topology_ok".into());
+            }
+            "broadcast" => {
+                self.gossip(body.get("message").and_then(Value::as_u64).unwrap(), None);
+                reply.set("type", "broadcast_ok".into());
+            }
+            "read" => {
+                let messages = self
+                    .seen
+                    .iter()
+                    .map(|m| Value::from(*m))
+                    .collect::<Vec<_>>();
+                reply.set("type", "read_ok".into());
+                reply.set("messages", messages.into());
+            }
+            other => panic!("unexpected request {other}"),
         }
-        self.delivered.insert(msg.to_string());
-        flurry::send_local(msg.to_string());
+        flurry::send_local(reply.to_string());
     }
 }
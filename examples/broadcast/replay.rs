@@ -1,35 +1,52 @@
-mod check;
 mod process;
 mod state;
 
 use std::{collections::VecDeque, time::Instant};
 
+use flurry::workload::{Broadcast, Driver};
 use process::BroadcastProcess;
 use state::State;
 
-fn make_system(proc_cnt: usize) -> flurry::System {
+struct Run {
+    sys: flurry::System,
+    driver: Driver<Broadcast>,
+}
+
+impl Run {
+    /// Sends requests of the workload until there are pending events.
+    /// Returns `true` if the workload is finished.
+    fn advance(&mut self) -> Result<bool, String> {
+        loop {
+            if self.driver.step(&mut self.sys)? {
+                return Ok(true);
+            }
+            if self.sys.get_pending_events_count() > 0 {
+                return Ok(false);
+            }
+        }
+    }
+}
+
+fn make_system(proc_cnt: usize) -> Run {
     let mut sys = flurry::System::default();
-    let add = (0..proc_cnt).collect::<Vec<flurry::ProcessId>>();
     for proc in 0..proc_cnt {
-        let mut except = add.clone();
-        except.remove(proc);
-        sys.add_process(BroadcastProcess {
-            others: except,
-            delivered: std::collections::HashSet::default(),
-        });
+        sys.add_process(BroadcastProcess::new(proc));
+    }
+    Run {
+        sys,
+        driver: Driver::new(Broadcast::new(proc_cnt, 1)),
     }
-    sys.send_local_message(0, "message");
-    sys
 }
 
-fn make_system_with_state(proc_cnt: usize, state: &State) -> flurry::System {
-    let mut sys = make_system(proc_cnt);
+fn make_system_with_state(proc_cnt: usize, state: &State) -> Run {
+    let mut run = make_system(proc_cnt);
 
     for event in state.to_apply.iter() {
-        sys.apply_pending_event(*event);
+        run.advance().unwrap();
+        run.sys.apply_pending_event(*event);
     }
 
-    sys
+    run
 }
 
 fn search(proc_cnt: usize) -> (bool, usize) {
@@ -46,17 +63,18 @@ fn search(proc_cnt: usize) -> (bool, usize) {
 
         processed_states += 1;
 
-        let mut sys = make_system_with_state(proc_cnt, &state);
-        if sys.get_pending_events_count() == 0 {
-            let check_result = check::check(&mut sys, proc_cnt);
-            if !check_result {
+        let mut run = make_system_with_state(proc_cnt, &state);
+        match run.advance() {
+            Ok(true) => continue,
+            Ok(false) => {}
+            Err(err) => {
+                println!("{err}");
                 failed = true;
                 break;
             }
-            continue;
         }
 
-        let pending_events_cnt = sys.get_pending_events_count();
+        let pending_events_cnt = run.sys.get_pending_events_count();
         for i in 0..pending_events_cnt {
             let mut new_state = state.clone();
             new_state.to_apply.push(i);
@@ -0,0 +1,156 @@
+//! Runs the SWIM-like membership protocol under message loss and crashes,
+//! and checks that views of members converge.
+//! Reports convergence time in protocol rounds: for members to join through the seed,
+//! and for crashes to be detected and known to all correct members.
+//!
+//! Usage: `gossip-check [runs] [members]`.
+//! Schedule of the first failed run is written to `gossip-violation.jsonl`
+//! in the temporary directory.
+
+mod convergence;
+mod message;
+mod swim;
+
+use std::{cell::RefCell, collections::BTreeSet, fs::File, process::exit, rc::Rc};
+
+use convergence::check_convergence;
+use flurry::{EventKind, Latency};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use swim::{Config, Node, SwimProcess};
+
+const SEEDS: [flurry::ProcessId; 1] = [0];
+const CRASHES: usize = 2;
+/// Probability to drop a pending message on every step while faults are injected.
+const DROP: f64 = 0.05;
+/// Views must converge within this many rounds after joins and after crashes.
+const MAX_ROUNDS: f64 = 50.0;
+/// Rounds with message loss after the crashes are detected,
+/// followed by rounds without faults, after which views must be converged again.
+const LOSSY_ROUNDS: f64 = 20.0;
+const QUIET_ROUNDS: f64 = 20.0;
+
+struct Run {
+    system: flurry::System,
+    nodes: Vec<Rc<RefCell<Node>>>,
+    crashed: BTreeSet<flurry::ProcessId>,
+    config: Config,
+    rng: StdRng,
+}
+
+impl Run {
+    fn new(seed: u64, members: usize, config: Config) -> Self {
+        let mut system = flurry::System::with_seed(seed);
+        system.set_latency(Latency::Uniform(0.1, 0.5));
+        let mut nodes = Vec::new();
+        for me in 0..members {
+            let process = SwimProcess::new(me, &SEEDS, config.clone());
+            nodes.push(process.node());
+            system.add_process(process);
+        }
+        for proc in 0..members {
+            system.send_local_message(proc, "start");
+        }
+        Self {
+            system,
+            nodes,
+            crashed: BTreeSet::new(),
+            config,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn rounds_since(&self, start: f64) -> f64 {
+        (self.system.now() - start) / self.config.period
+    }
+
+    fn step(&mut self, lossy: bool) {
+        if lossy && self.rng.gen::<f64>() < DROP {
+            let pending = self.system.get_pending_events();
+            let messages: Vec<_> = (0..pending.len())
+                .filter(|event| matches!(pending[*event], EventKind::MessageDelivered(..)))
+                .collect();
+            if let Some(event) = messages.choose(&mut self.rng) {
+                self.system.drop_pending_event(*event);
+            }
+        }
+        assert!(self.system.step(), "rounds never stop");
+    }
+
+    /// Runs until views converge, returning the number of rounds it took.
+    fn converge(&mut self, lossy: bool) -> Result<f64, String> {
+        let start = self.system.now();
+        loop {
+            let rounds = self.rounds_since(start);
+            match check_convergence(&self.nodes, &self.crashed) {
+                Ok(()) => return Ok(rounds),
+                Err(error) if rounds > MAX_ROUNDS => {
+                    return Err(format!("not converged in {MAX_ROUNDS} rounds: {error}"))
+                }
+                Err(_) => self.step(lossy),
+            }
+        }
+    }
+
+    /// Returns rounds of convergence after joins and after crashes.
+    fn run(&mut self) -> Result<(f64, f64), String> {
+        let joined = self.converge(false)?;
+
+        let mut members: Vec<_> = (0..self.nodes.len()).collect();
+        members.shuffle(&mut self.rng);
+        for proc in members.into_iter().take(CRASHES) {
+            self.system.crash_process(proc);
+            self.crashed.insert(proc);
+        }
+        let detected = self.converge(true)?;
+
+        for (rounds, lossy) in [(LOSSY_ROUNDS, true), (QUIET_ROUNDS, false)] {
+            let start = self.system.now();
+            while self.rounds_since(start) < rounds {
+                self.step(lossy);
+            }
+        }
+        check_convergence(&self.nodes, &self.crashed)?;
+        Ok((joined, detected))
+    }
+}
+
+/// Mean and maximum of the samples.
+fn stats(samples: &[f64]) -> (f64, f64) {
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    (mean, samples.iter().copied().fold(0.0, f64::max))
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let runs = args
+        .next()
+        .map_or(100, |arg| arg.parse().expect("number of runs expected"));
+    let members = args
+        .next()
+        .map_or(10, |arg| arg.parse().expect("number of members expected"));
+
+    let mut joins = Vec::new();
+    let mut detections = Vec::new();
+    for seed in 0..runs {
+        let mut run = Run::new(seed, members, Config::default());
+        match run.run() {
+            Ok((joined, detected)) => {
+                joins.push(joined);
+                detections.push(detected);
+            }
+            Err(error) => {
+                println!("Failed with seed {seed}: {error}");
+                let path = std::env::temp_dir().join("gossip-violation.jsonl");
+                let file = File::create(&path).unwrap();
+                run.system.get_schedule().write(file).unwrap();
+                println!("Schedule written to {}", path.display());
+                exit(1);
+            }
+        }
+    }
+    let (join_mean, join_max) = stats(&joins);
+    let (detect_mean, detect_max) = stats(&detections);
+    println!("Passed {runs} runs with {members} members");
+    println!("Joined in {join_mean:.1} rounds on average, {join_max:.1} at most");
+    println!("Crashes detected in {detect_mean:.1} rounds on average, {detect_max:.1} at most");
+}
@@ -0,0 +1,32 @@
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+
+use crate::{message::Status, swim::Node};
+
+/// Checks that views of correct members are converged: every correct member
+/// knows every other correct member as alive and every crashed member it knows of as dead.
+pub fn check_convergence(
+    nodes: &[Rc<RefCell<Node>>],
+    crashed: &BTreeSet<flurry::ProcessId>,
+) -> Result<(), String> {
+    for node in nodes.iter() {
+        let node = node.borrow();
+        if crashed.contains(&node.me) {
+            continue;
+        }
+        for member in 0..nodes.len() {
+            let status = node.members.get(&member).map(|update| update.status);
+            let expected = match crashed.contains(&member) {
+                true if status.is_none() => continue,
+                true => Status::Dead,
+                false => Status::Alive,
+            };
+            if status != Some(expected) {
+                return Err(format!(
+                    "member {} sees {member} as {status:?}, expected {expected:?}",
+                    node.me
+                ));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use flurry::json::Value;
+
+/// Status of the member, ordered by precedence for equal incarnations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    Alive,
+    Suspect,
+    Dead,
+}
+
+impl Status {
+    fn name(&self) -> &'static str {
+        match self {
+            Status::Alive => "alive",
+            Status::Suspect => "suspect",
+            Status::Dead => "dead",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Status> {
+        match name {
+            "alive" => Some(Status::Alive),
+            "suspect" => Some(Status::Suspect),
+            "dead" => Some(Status::Dead),
+            _ => None,
+        }
+    }
+}
+
+/// Claim about the member, which is disseminated by gossip.
+/// Claim with greater incarnation, or with the same incarnation and greater status, wins.
+/// Only the member itself increments its incarnation, to refute suspicions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Update {
+    pub member: flurry::ProcessId,
+    pub status: Status,
+    pub incarnation: u64,
+}
+
+impl Update {
+    pub fn overrides(&self, other: &Update) -> bool {
+        (self.incarnation, self.status) > (other.incarnation, other.status)
+    }
+
+    fn to_json(self) -> Value {
+        Value::object([
+            ("member", self.member.into()),
+            ("status", self.status.name().into()),
+            ("incarnation", self.incarnation.into()),
+        ])
+    }
+
+    fn from_json(value: &Value) -> Option<Update> {
+        Some(Update {
+            member: value.get("member")?.as_u64()? as flurry::ProcessId,
+            status: Status::from_name(value.get("status")?.as_str()?)?,
+            incarnation: value.get("incarnation")?.as_u64()?,
+        })
+    }
+}
+
+/// Messages of the membership protocol, encoded as JSON.
+/// Every message carries updates, either recent ones or the whole view.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Ping {
+        seq: u64,
+        updates: Vec<Update>,
+    },
+    Ack {
+        seq: u64,
+        updates: Vec<Update>,
+    },
+    /// Asks to ping the target and forward its ack, when the direct ping was not acked.
+    PingReq {
+        seq: u64,
+        target: flurry::ProcessId,
+        updates: Vec<Update>,
+    },
+    /// Anti-entropy exchange of whole views, answered with the view of the receiver
+    /// unless it is a reply.
+    Sync {
+        reply: bool,
+        updates: Vec<Update>,
+    },
+}
+
+impl Message {
+    pub fn updates(&self) -> &[Update] {
+        match self {
+            Message::Ping { updates, .. }
+            | Message::Ack { updates, .. }
+            | Message::PingReq { updates, .. }
+            | Message::Sync { updates, .. } => updates,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let updates: Vec<Value> = self
+            .updates()
+            .iter()
+            .map(|update| update.to_json())
+            .collect();
+        let mut value = match self {
+            Message::Ping { seq, .. } => {
+                Value::object([("type", "ping".into()), ("seq", (*seq).into())])
+            }
+            Message::Ack { seq, .. } => {
+                Value::object([("type", "ack".into()), ("seq", (*seq).into())])
+            }
+            Message::PingReq { seq, target, .. } => Value::object([
+                ("type", "ping_req".into()),
+                ("seq", (*seq).into()),
+                ("target", (*target).into()),
+            ]),
+            Message::Sync { reply, .. } => {
+                Value::object([("type", "sync".into()), ("reply", (*reply).into())])
+            }
+        };
+        value.set("updates", updates.into());
+        value.to_string()
+    }
+
+    pub fn decode(msg: &str) -> Option<Message> {
+        let value = Value::parse(msg).ok()?;
+        let number = |key: &str| value.get(key).and_then(Value::as_u64);
+        let updates = value
+            .get("updates")?
+            .as_array()?
+            .iter()
+            .map(Update::from_json)
+            .collect::<Option<_>>()?;
+        let message = match value.get("type")?.as_str()? {
+            "ping" => Message::Ping {
+                seq: number("seq")?,
+                updates,
+            },
+            "ack" => Message::Ack {
+                seq: number("seq")?,
+                updates,
+            },
+            "ping_req" => Message::PingReq {
+                seq: number("seq")?,
+                target: number("target")? as flurry::ProcessId,
+                updates,
+            },
+            "sync" => Message::Sync {
+                reply: match value.get("reply")? {
+                    Value::Bool(reply) => *reply,
+                    _ => return None,
+                },
+                updates,
+            },
+            _ => return None,
+        };
+        Some(message)
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    rc::{Rc, Weak},
+};
+
+use crate::message::{Message, Status, Update};
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Duration of the protocol round, in which one member is probed.
+    /// Must exceed the round trip of the indirect probe after the ping timeout.
+    pub period: f64,
+    /// Time to wait for the ack of the direct ping, before asking others to ping.
+    pub ping_timeout: f64,
+    /// Number of members asked to ping the target indirectly.
+    pub indirect: usize,
+    /// Suspected member is declared dead after this many rounds without refutation.
+    pub suspicion_rounds: u64,
+    /// Maximal number of updates piggybacked on every message.
+    pub piggyback: usize,
+    /// Every update is piggybacked `retransmit * log2(members)` times.
+    pub retransmit: u64,
+    /// Whole views are exchanged with a random member every this many rounds.
+    pub sync_every: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            period: 5.0,
+            ping_timeout: 2.0,
+            indirect: 2,
+            suspicion_rounds: 3,
+            piggyback: 6,
+            retransmit: 3,
+            sync_every: 5,
+        }
+    }
+}
+
+/// State of the member, which is shared with its rounds and inspected by the checker.
+pub struct Node {
+    pub me: flurry::ProcessId,
+    config: Config,
+    this: Weak<RefCell<Node>>,
+
+    pub incarnation: u64,
+    /// Latest claims about known members, including the node itself.
+    pub members: BTreeMap<flurry::ProcessId, Update>,
+    pub round: u64,
+
+    /// Updates to piggyback, newest first, with numbers of remaining transmissions.
+    recent: Vec<(Update, u64)>,
+    suspected_since: HashMap<flurry::ProcessId, u64>,
+    seq: u64,
+    /// Sequence numbers of pings acked in the current round.
+    acked: HashSet<u64>,
+    /// Acks to forward for indirect probes: own sequence number to the origin,
+    /// its sequence number and the round of the request.
+    forwards: HashMap<u64, (flurry::ProcessId, u64, u64)>,
+}
+
+fn send(to: flurry::ProcessId, msg: Message) {
+    // Link cut by a topology only delays detection, since SWIM treats silence as suspicion.
+    let _ = flurry::try_send_unacked(to, msg.encode());
+}
+
+/// Chooses up to `k` random processes from the candidates.
+fn choose(mut candidates: Vec<flurry::ProcessId>, k: usize) -> Vec<flurry::ProcessId> {
+    let k = k.min(candidates.len());
+    for i in 0..k {
+        let j = flurry::random_range(i as u64..candidates.len() as u64) as usize;
+        candidates.swap(i, j);
+    }
+    candidates.truncate(k);
+    candidates
+}
+
+impl Node {
+    /// Members which are not known to be dead, except the node itself.
+    pub fn peers(&self) -> Vec<flurry::ProcessId> {
+        self.members
+            .values()
+            .filter(|update| update.member != self.me && update.status != Status::Dead)
+            .map(|update| update.member)
+            .collect()
+    }
+
+    fn set(&mut self, update: Update) {
+        self.members.insert(update.member, update);
+        if update.status == Status::Suspect {
+            self.suspected_since.insert(update.member, self.round);
+        } else {
+            self.suspected_since.remove(&update.member);
+        }
+        let members = self.members.len() as u64;
+        let transmissions = self.config.retransmit * (u64::BITS - members.leading_zeros()) as u64;
+        self.recent
+            .retain(|(recent, _)| recent.member != update.member);
+        self.recent.insert(0, (update, transmissions));
+    }
+
+    /// Merges claim received from another member.
+    /// Suspicion of the node itself is refuted by the greater incarnation.
+    fn apply(&mut self, update: Update) {
+        if update.member == self.me {
+            if update.status != Status::Alive && update.incarnation >= self.incarnation {
+                self.incarnation = update.incarnation + 1;
+                self.set(Update {
+                    member: self.me,
+                    status: Status::Alive,
+                    incarnation: self.incarnation,
+                });
+            }
+            return;
+        }
+        let known = self.members.get(&update.member);
+        if known.is_none_or(|known| update.overrides(known)) {
+            self.set(update);
+        }
+    }
+
+    /// Returns updates to piggyback on the next message.
+    fn gossip(&mut self) -> Vec<Update> {
+        let count = self.config.piggyback.min(self.recent.len());
+        let updates = self.recent[..count]
+            .iter_mut()
+            .map(|(update, remaining)| {
+                *remaining -= 1;
+                *update
+            })
+            .collect();
+        self.recent.retain(|(_, remaining)| *remaining > 0);
+        updates
+    }
+
+    fn next_seq(&mut self) -> u64 {
+        self.seq += 1;
+        self.seq
+    }
+
+    /// Starts the round: declares dead members which were suspected for too long,
+    /// exchanges views with a random member every few rounds
+    /// and pings a random member, returning it with the sequence number of the ping.
+    fn start_round(&mut self) -> Option<(flurry::ProcessId, u64)> {
+        self.round += 1;
+        self.acked.clear();
+        let round = self.round;
+        self.forwards.retain(|_, (_, _, at)| *at + 1 >= round);
+
+        let expired: Vec<_> = self
+            .suspected_since
+            .iter()
+            .filter(|(_, since)| round - **since >= self.config.suspicion_rounds)
+            .map(|(member, _)| *member)
+            .collect();
+        for member in expired {
+            let incarnation = self.members[&member].incarnation;
+            self.set(Update {
+                member,
+                status: Status::Dead,
+                incarnation,
+            });
+        }
+
+        if round.is_multiple_of(self.config.sync_every) {
+            if let Some(to) = choose(self.peers(), 1).pop() {
+                let updates = self.members.values().copied().collect();
+                send(
+                    to,
+                    Message::Sync {
+                        reply: false,
+                        updates,
+                    },
+                );
+            }
+        }
+
+        let target = choose(self.peers(), 1).pop()?;
+        let seq = self.next_seq();
+        let updates = self.gossip();
+        send(target, Message::Ping { seq, updates });
+        Some((target, seq))
+    }
+
+    /// Asks random members to ping the target if the direct ping was not acked.
+    fn probe_indirectly(&mut self, target: flurry::ProcessId, seq: u64) {
+        if self.acked.contains(&seq) {
+            return;
+        }
+        let helpers = self.peers().into_iter().filter(|peer| *peer != target);
+        for helper in choose(helpers.collect(), self.config.indirect) {
+            let updates = self.gossip();
+            send(
+                helper,
+                Message::PingReq {
+                    seq,
+                    target,
+                    updates,
+                },
+            );
+        }
+    }
+
+    /// Suspects the target if neither direct nor indirect ping was acked in the round.
+    fn finish_probe(&mut self, target: flurry::ProcessId, seq: u64) {
+        if self.acked.contains(&seq) {
+            return;
+        }
+        let known = self.members[&target];
+        if known.status == Status::Alive {
+            self.set(Update {
+                status: Status::Suspect,
+                ..known
+            });
+        }
+    }
+
+    fn on_message(&mut self, from: flurry::ProcessId, msg: Message) {
+        for update in msg.updates() {
+            self.apply(*update);
+        }
+        match msg {
+            Message::Ping { seq, .. } => {
+                let updates = self.gossip();
+                send(from, Message::Ack { seq, updates });
+            }
+            Message::Ack { seq, .. } => match self.forwards.remove(&seq) {
+                Some((origin, seq, _)) => {
+                    let updates = self.gossip();
+                    send(origin, Message::Ack { seq, updates });
+                }
+                None => {
+                    self.acked.insert(seq);
+                }
+            },
+            Message::PingReq { seq, target, .. } => {
+                let own = self.next_seq();
+                self.forwards.insert(own, (from, seq, self.round));
+                let updates = self.gossip();
+                send(target, Message::Ping { seq: own, updates });
+            }
+            Message::Sync { reply: false, .. } => {
+                let updates = self.members.values().copied().collect();
+                send(
+                    from,
+                    Message::Sync {
+                        reply: true,
+                        updates,
+                    },
+                );
+            }
+            Message::Sync { reply: true, .. } => {}
+        }
+    }
+
+    fn start(&mut self) {
+        let node = self.this.upgrade().unwrap();
+        let config = self.config.clone();
+        flurry::spawn(async move {
+            loop {
+                let probe = node.borrow_mut().start_round();
+                flurry::sleep(config.ping_timeout).await;
+                if let Some((target, seq)) = probe {
+                    node.borrow_mut().probe_indirectly(target, seq);
+                }
+                flurry::sleep(config.period - config.ping_timeout).await;
+                if let Some((target, seq)) = probe {
+                    node.borrow_mut().finish_probe(target, seq);
+                }
+            }
+        });
+    }
+}
+
+/// Member of the SWIM-like membership protocol, which starts on the local message `start`
+/// knowing only itself and the seeds.
+///
+/// Every round the member pings a random peer, and if the ack does not arrive in time,
+/// asks a few other peers to ping it. Peer which was not reached is suspected,
+/// and declared dead if it does not refute the suspicion in a few rounds.
+/// Updates of membership are piggybacked on pings and acks,
+/// and whole views are periodically exchanged with random peers (anti-entropy),
+/// so new members and missed updates are eventually known to everyone.
+///
+/// Rounds never stop, so the run must be stopped by time.
+pub struct SwimProcess {
+    node: Rc<RefCell<Node>>,
+}
+
+impl SwimProcess {
+    pub fn new(me: flurry::ProcessId, seeds: &[flurry::ProcessId], config: Config) -> Self {
+        let alive = |member| Update {
+            member,
+            status: Status::Alive,
+            incarnation: 0,
+        };
+        let node = Rc::new_cyclic(|this| {
+            let mut node = Node {
+                me,
+                config,
+                this: this.clone(),
+                incarnation: 0,
+                members: BTreeMap::new(),
+                round: 0,
+                recent: Vec::new(),
+                suspected_since: HashMap::new(),
+                seq: 0,
+                acked: HashSet::new(),
+                forwards: HashMap::new(),
+            };
+            node.set(alive(me));
+            for seed in seeds.iter().filter(|seed| **seed != me) {
+                node.members.insert(*seed, alive(*seed));
+            }
+            RefCell::new(node)
+        });
+        Self { node }
+    }
+
+    /// Returns state of the member, e.g. to check convergence.
+    pub fn node(&self) -> Rc<RefCell<Node>> {
+        self.node.clone()
+    }
+}
+
+impl flurry::Process for SwimProcess {
+    fn on_message(&mut self, from: flurry::ProcessId, msg: String) {
+        let msg = Message::decode(&msg).expect("invalid message");
+        self.node.borrow_mut().on_message(from, msg);
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        match msg {
+            "start" => self.node.borrow_mut().start(),
+            _ => panic!("unexpected local message '{msg}'"),
+        }
+    }
+}
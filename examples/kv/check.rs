@@ -0,0 +1,174 @@
+//! Runs the key-value store replicated with Raft under random crashes, message loss
+//! and partitions of servers, and checks that histories of clients are linearizable.
+//! Store with stale reads must be caught by the checker.
+//!
+//! Usage: `kv-check [runs]`.
+//! Schedule of the first failed run is written to `kv-violation.jsonl`
+//! in the temporary directory.
+
+mod kv;
+mod linearizability;
+#[path = "../raft/message.rs"]
+mod message;
+#[path = "../raft/raft.rs"]
+mod raft;
+
+use std::{fs::File, process::exit};
+
+use flurry::{EventKind, Latency, Topology};
+use kv::{KvClient, KvServer};
+use linearizability::History;
+use raft::Config;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+const SERVERS: usize = 3;
+const CLIENTS: usize = 3;
+const OPS: u64 = 20;
+const KEYS: u64 = 2;
+/// Faults are injected until this time, then clients must finish.
+const FAULTS_UNTIL: f64 = 200.0;
+const RUN_UNTIL: f64 = 2000.0;
+
+struct Run {
+    system: flurry::System,
+    history: History,
+    rng: StdRng,
+}
+
+impl Run {
+    fn new(seed: u64, stale_reads: bool) -> Self {
+        let mut system = flurry::System::with_seed(seed);
+        system.set_latency(Latency::Uniform(0.2, 1.0));
+        for me in 0..SERVERS {
+            system.add_process(KvServer::new(me, SERVERS, Config::default(), stale_reads));
+        }
+        for _ in 0..CLIENTS {
+            system.add_process(KvClient::new(SERVERS, OPS, KEYS));
+        }
+        for proc in 0..SERVERS + CLIENTS {
+            system.send_local_message(proc, "start");
+        }
+        Self {
+            system,
+            history: History::default(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn correct_servers(&self) -> Vec<flurry::ProcessId> {
+        (0..SERVERS)
+            .filter(|proc| !self.system.is_crashed(*proc))
+            .collect()
+    }
+
+    /// Makes one fault with some probability.
+    fn inject(&mut self) {
+        let roll: f64 = self.rng.gen();
+        let pending = self.system.get_pending_events();
+        if roll < 0.05 {
+            let messages: Vec<_> = (0..pending.len())
+                .filter(|event| matches!(pending[*event], EventKind::MessageDelivered(..)))
+                .collect();
+            if let Some(event) = messages.choose(&mut self.rng) {
+                self.system.drop_pending_event(*event);
+            }
+        } else if roll < 0.052 {
+            if self.correct_servers().len() == SERVERS {
+                let proc = *self.correct_servers().choose(&mut self.rng).unwrap();
+                self.system.crash_process(proc);
+            }
+        } else if roll < 0.06 {
+            let mut group: Vec<_> = (0..SERVERS).collect();
+            group.shuffle(&mut self.rng);
+            let side = self.rng.gen_range(1..SERVERS);
+            self.system.set_topology(partition(&group[..side]));
+        } else if roll < 0.07 {
+            self.system
+                .set_topology(Topology::full_mesh(SERVERS + CLIENTS));
+        }
+    }
+
+    /// Records reports of clients, counting clients which are done.
+    fn record(&mut self, done: &mut usize) {
+        for client in SERVERS..SERVERS + CLIENTS {
+            for msg in self.system.read_local(client) {
+                if msg == "done" {
+                    *done += 1;
+                }
+                self.history.record(client, &msg);
+            }
+        }
+    }
+
+    fn run(&mut self) -> Result<(), String> {
+        let mut done = 0;
+        while done < CLIENTS {
+            if self.system.now() < FAULTS_UNTIL {
+                self.inject();
+            } else {
+                self.system
+                    .set_topology(Topology::full_mesh(SERVERS + CLIENTS));
+            }
+            if self.system.now() > RUN_UNTIL || !self.system.step() {
+                return Err(format!("{} of {CLIENTS} clients finished", done));
+            }
+            self.record(&mut done);
+        }
+        self.history.check()
+    }
+}
+
+/// Cuts links between the group and other servers, clients can reach all servers.
+fn partition(group: &[flurry::ProcessId]) -> Topology {
+    let processes = SERVERS + CLIENTS;
+    Topology::custom(
+        (0..processes)
+            .map(|proc| {
+                (0..processes)
+                    .filter(|other| {
+                        *other != proc
+                            && (proc >= SERVERS
+                                || *other >= SERVERS
+                                || group.contains(other) == group.contains(&proc))
+                    })
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+/// Runs with seeds until the first failure, returning it with the run.
+fn check(runs: u64, stale_reads: bool) -> Option<(u64, String, Run)> {
+    let mut ops = 0;
+    for seed in 0..runs {
+        let mut run = Run::new(seed, stale_reads);
+        if let Err(error) = run.run() {
+            return Some((seed, error, run));
+        }
+        ops += run.history.len();
+    }
+    println!("Passed {runs} runs, {ops} operations checked");
+    None
+}
+
+fn main() {
+    let runs = std::env::args()
+        .nth(1)
+        .map_or(100, |arg| arg.parse().expect("number of runs expected"));
+
+    if let Some((seed, error, run)) = check(runs, false) {
+        println!("Failed with seed {seed}: {error}");
+        let path = std::env::temp_dir().join("kv-violation.jsonl");
+        let file = File::create(&path).unwrap();
+        run.system.get_schedule().write(file).unwrap();
+        println!("Schedule written to {}", path.display());
+        exit(1);
+    }
+    match check(runs, true) {
+        Some((seed, error, _)) => println!("Stale reads caught with seed {seed}: {error}"),
+        None => {
+            println!("Stale reads were not caught");
+            exit(1);
+        }
+    }
+}
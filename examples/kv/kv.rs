@@ -0,0 +1,356 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use flurry::{json::Value, Process};
+use futures::future::{select, Either};
+
+use crate::raft::{Config, Node, RaftProcess, Role};
+
+/// Operation on the register of the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Read(u64),
+    Write(u64, u64),
+    /// Compare-and-set from the first value to the second.
+    Cas(u64, u64, u64),
+}
+
+impl Op {
+    fn to_request(self, req: u64) -> Value {
+        let mut request = match self {
+            Op::Read(key) => Value::object([("type", "read".into()), ("key", key.into())]),
+            Op::Write(key, value) => Value::object([
+                ("type", "write".into()),
+                ("key", key.into()),
+                ("value", value.into()),
+            ]),
+            Op::Cas(key, from, to) => Value::object([
+                ("type", "cas".into()),
+                ("key", key.into()),
+                ("from", from.into()),
+                ("to", to.into()),
+            ]),
+        };
+        request.set("req", req.into());
+        request
+    }
+
+    fn from_request(request: &Value) -> Option<Op> {
+        let number = |key: &str| request.get(key)?.as_u64();
+        match request.get("type")?.as_str()? {
+            "read" => Some(Op::Read(number("key")?)),
+            "write" => Some(Op::Write(number("key")?, number("value")?)),
+            "cas" => Some(Op::Cas(number("key")?, number("from")?, number("to")?)),
+            _ => None,
+        }
+    }
+
+    /// Returns operation as reported by the client, e.g. `cas 1 2 3`.
+    pub fn describe(&self) -> String {
+        match self {
+            Op::Read(key) => format!("read {key}"),
+            Op::Write(key, value) => format!("write {key} {value}"),
+            Op::Cas(key, from, to) => format!("cas {key} {from} {to}"),
+        }
+    }
+}
+
+fn error(req: u64, code: &str) -> Value {
+    Value::object([
+        ("type", "error".into()),
+        ("req", req.into()),
+        ("code", code.into()),
+    ])
+}
+
+/// State machine of the store, replicated by Raft.
+#[derive(Default)]
+struct Store {
+    data: HashMap<u64, u64>,
+    /// Last applied request of every client with its reply,
+    /// so requests retried by clients are applied once.
+    sessions: HashMap<flurry::ProcessId, (u64, Value)>,
+}
+
+impl Store {
+    fn execute(&mut self, op: Op, req: u64) -> Value {
+        let (kind, value) = match op {
+            Op::Read(key) => (
+                "read_ok",
+                self.data
+                    .get(&key)
+                    .map_or(Value::Null, |value| (*value).into()),
+            ),
+            Op::Write(key, value) => {
+                self.data.insert(key, value);
+                ("write_ok", Value::Null)
+            }
+            Op::Cas(key, from, to) => {
+                if self.data.get(&key) != Some(&from) {
+                    return error(req, "precondition");
+                }
+                self.data.insert(key, to);
+                ("cas_ok", Value::Null)
+            }
+        };
+        let mut reply = Value::object([("type", kind.into()), ("req", req.into())]);
+        if matches!(op, Op::Read(_)) {
+            reply.set("value", value);
+        }
+        reply
+    }
+
+    /// Applies command of the log, returning the client and the reply,
+    /// `None` if the command is an old request of the client.
+    fn apply(&mut self, cmd: &str) -> Option<(flurry::ProcessId, u64, Value)> {
+        let request = Value::parse(cmd).expect("invalid command");
+        let client = request.get("client")?.as_u64()? as flurry::ProcessId;
+        let req = request.get("req")?.as_u64()?;
+        match self.sessions.get(&client) {
+            Some((last, reply)) if *last == req => return Some((client, req, reply.clone())),
+            Some((last, _)) if *last > req => return None,
+            _ => {}
+        }
+        let reply = self.execute(Op::from_request(&request)?, req);
+        self.sessions.insert(client, (req, reply.clone()));
+        Some((client, req, reply))
+    }
+}
+
+/// Server of the store: requests of clients are appended to the Raft log
+/// and answered by the server which received them, when they are applied.
+///
+/// Server with `stale_reads` answers reads from its own state without the log,
+/// even if it is not the leader, which is not linearizable.
+pub struct KvServer {
+    raft: RaftProcess,
+    node: Rc<RefCell<Node>>,
+    servers: usize,
+    store: Store,
+    applied: usize,
+    waiting: HashSet<(flurry::ProcessId, u64)>,
+    stale_reads: bool,
+}
+
+impl KvServer {
+    pub fn new(me: flurry::ProcessId, servers: usize, config: Config, stale_reads: bool) -> Self {
+        let raft = RaftProcess::new(me, servers, config);
+        Self {
+            node: raft.node(),
+            raft,
+            servers,
+            store: Store::default(),
+            applied: 0,
+            waiting: HashSet::new(),
+            stale_reads,
+        }
+    }
+
+    fn on_request(&mut self, client: flurry::ProcessId, request: Value) {
+        let Some(req) = request.get("req").and_then(Value::as_u64) else {
+            return;
+        };
+        if let (true, Some(op @ Op::Read(_))) = (self.stale_reads, Op::from_request(&request)) {
+            let reply = self.store.execute(op, req);
+            flurry::send_unacked(client, reply.to_string());
+            return;
+        }
+        if self.node.borrow().role != Role::Leader {
+            flurry::send_unacked(client, error(req, "not_leader").to_string());
+            return;
+        }
+        let mut cmd = request;
+        cmd.set("client", client.into());
+        self.waiting.insert((client, req));
+        self.raft.on_local_message(&format!("submit {cmd}"));
+    }
+
+    /// Applies commands applied by Raft since the last call, answering waiting clients.
+    fn catch_up(&mut self) {
+        let node = self.node.borrow();
+        for cmd in node.applied[self.applied..].iter() {
+            let Some((client, req, reply)) = self.store.apply(cmd) else {
+                continue;
+            };
+            if self.waiting.remove(&(client, req)) {
+                flurry::send_unacked(client, reply.to_string());
+            }
+        }
+        self.applied = node.applied.len();
+    }
+}
+
+impl Process for KvServer {
+    fn on_message(&mut self, from: flurry::ProcessId, msg: String) {
+        if from < self.servers {
+            self.raft.on_message(from, msg);
+        } else if let Ok(request) = Value::parse(&msg) {
+            self.on_request(from, request);
+        }
+        self.catch_up();
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        self.raft.on_local_message(msg);
+    }
+}
+
+struct ClientState {
+    req: u64,
+    reply: Option<Value>,
+    waker: Option<Waker>,
+}
+
+/// Future which is resolved with the reply to the current request.
+struct Reply(Rc<RefCell<ClientState>>);
+
+impl Future for Reply {
+    type Output = Value;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Value> {
+        let mut state = self.0.borrow_mut();
+        match state.reply.take() {
+            Some(reply) => Poll::Ready(reply),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Client which makes `ops` random operations on the local message `start`,
+/// one at a time, and reports them for the linearizability checker:
+/// `invoke {req} {op}` before sending the request, then
+/// `ok {req}` with the read value, `fail {req}` if the operation surely had no effect,
+/// or `info {req}` if it is unknown, because no reply arrived.
+/// Reports `done` after the last operation.
+///
+/// Request is sent to the next server after the timeout or if the server is not the leader,
+/// with the same id, so the store applies it once.
+pub struct KvClient {
+    servers: usize,
+    ops: u64,
+    keys: u64,
+    timeout: f64,
+    attempts: usize,
+    state: Rc<RefCell<ClientState>>,
+}
+
+impl KvClient {
+    pub fn new(servers: usize, ops: u64, keys: u64) -> Self {
+        Self {
+            servers,
+            ops,
+            keys,
+            timeout: 10.0,
+            attempts: 10,
+            state: Rc::new(RefCell::new(ClientState {
+                req: 0,
+                reply: None,
+                waker: None,
+            })),
+        }
+    }
+
+    fn random_op(&self) -> Op {
+        let key = flurry::random_range(0..self.keys);
+        let value = || flurry::random_range(0..5);
+        match flurry::random_range(0..3) {
+            0 => Op::Read(key),
+            1 => Op::Write(key, value()),
+            _ => Op::Cas(key, value(), value()),
+        }
+    }
+}
+
+async fn request(
+    state: Rc<RefCell<ClientState>>,
+    op: Op,
+    req: u64,
+    servers: usize,
+    leader: &mut flurry::ProcessId,
+    timeout: f64,
+    attempts: usize,
+) -> String {
+    {
+        let mut state = state.borrow_mut();
+        state.req = req;
+        state.reply = None;
+    }
+    let request = op.to_request(req).to_string();
+    for _ in 0..attempts {
+        // Client cut off from the server by a partition waits for the timeout and moves on.
+        let _ = flurry::try_send_unacked(*leader, request.clone());
+        let reply = match select(Reply(state.clone()), flurry::sleep(timeout)).await {
+            Either::Left((reply, _)) => reply,
+            Either::Right(_) => {
+                *leader = (*leader + 1) % servers;
+                continue;
+            }
+        };
+        match reply.get("code").and_then(Value::as_str) {
+            Some("not_leader") => {
+                *leader = (*leader + 1) % servers;
+                flurry::sleep(1.0).await;
+            }
+            Some(_) => return format!("fail {req}"),
+            None => {
+                return match reply.get("value") {
+                    Some(value) => format!("ok {req} {value}"),
+                    None => format!("ok {req}"),
+                }
+            }
+        }
+    }
+    format!("info {req}")
+}
+
+impl Process for KvClient {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        let Ok(reply) = Value::parse(&msg) else {
+            return;
+        };
+        let waker = {
+            let mut state = self.state.borrow_mut();
+            if reply.get("req").and_then(Value::as_u64) != Some(state.req) {
+                return;
+            }
+            state.reply = Some(reply);
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    fn on_local_message(&mut self, _msg: &str) {
+        let ops: Vec<Op> = (0..self.ops).map(|_| self.random_op()).collect();
+        let state = self.state.clone();
+        let (servers, timeout, attempts) = (self.servers, self.timeout, self.attempts);
+        flurry::spawn(async move {
+            let mut leader = 0;
+            for (req, op) in (0..).zip(ops) {
+                flurry::send_local(format!("invoke {req} {}", op.describe()));
+                let outcome = request(
+                    state.clone(),
+                    op,
+                    req,
+                    servers,
+                    &mut leader,
+                    timeout,
+                    attempts,
+                )
+                .await;
+                flurry::send_local(outcome);
+            }
+            flurry::send_local("done".to_string());
+        });
+    }
+}
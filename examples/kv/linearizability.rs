@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::kv::Op;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Operation took effect, with the value for reads.
+    Ok(Option<u64>),
+    /// Operation surely did not take effect.
+    Fail,
+    /// Operation could take effect or not, at any time after the invocation.
+    Info,
+}
+
+#[derive(Debug, Clone)]
+struct Operation {
+    client: flurry::ProcessId,
+    op: Op,
+    /// Positions of the invocation and the completion in the history.
+    invoke: usize,
+    complete: usize,
+    outcome: Outcome,
+}
+
+/// History of operations reported by clients, which is checked for linearizability.
+#[derive(Default)]
+pub struct History {
+    ops: Vec<Operation>,
+    open: HashMap<flurry::ProcessId, usize>,
+    events: usize,
+}
+
+fn parse_op(words: &[&str]) -> Option<Op> {
+    let number = |i: usize| words.get(i)?.parse().ok();
+    match *words.first()? {
+        "read" => Some(Op::Read(number(1)?)),
+        "write" => Some(Op::Write(number(1)?, number(2)?)),
+        "cas" => Some(Op::Cas(number(1)?, number(2)?, number(3)?)),
+        _ => None,
+    }
+}
+
+impl History {
+    /// Records message reported by the client, see [`crate::kv::KvClient`].
+    pub fn record(&mut self, client: flurry::ProcessId, msg: &str) {
+        self.events += 1;
+        let words: Vec<_> = msg.split(' ').collect();
+        let outcome = match words[..] {
+            ["invoke", _, ref op @ ..] => {
+                let op = parse_op(op).unwrap_or_else(|| panic!("invalid operation: {msg}"));
+                self.open.insert(client, self.ops.len());
+                self.ops.push(Operation {
+                    client,
+                    op,
+                    invoke: self.events,
+                    complete: usize::MAX,
+                    outcome: Outcome::Info,
+                });
+                return;
+            }
+            ["ok", _] => Outcome::Ok(None),
+            ["ok", _, "null"] => Outcome::Ok(None),
+            ["ok", _, value] => Outcome::Ok(Some(value.parse().unwrap())),
+            ["fail", _] => Outcome::Fail,
+            _ => return,
+        };
+        let index = self
+            .open
+            .remove(&client)
+            .expect("completion without invocation");
+        let op = &mut self.ops[index];
+        op.outcome = outcome;
+        op.complete = self.events;
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Checks that operations on every key can be ordered, so that every operation
+    /// takes effect between its invocation and completion, and results are
+    /// the same as if operations were made one by one on the register.
+    ///
+    /// Keys are checked separately, since the history is linearizable
+    /// if and only if its operations on every key are.
+    pub fn check(&self) -> Result<(), String> {
+        let mut keys: BTreeMap<u64, Vec<Operation>> = BTreeMap::new();
+        for op in self.ops.iter() {
+            let key = match op.op {
+                Op::Read(key) | Op::Write(key, _) | Op::Cas(key, _, _) => key,
+            };
+            // Reads without the result do not change anything.
+            if !(matches!(op.op, Op::Read(_)) && op.outcome == Outcome::Info) {
+                keys.entry(key).or_default().push(op.clone());
+            }
+        }
+        for (key, ops) in keys {
+            let mut search = Search {
+                ops: &ops,
+                visited: HashSet::new(),
+            };
+            if !search.linearize(&mut vec![false; ops.len()], None) {
+                let ops: Vec<_> = ops
+                    .iter()
+                    .map(|op| format!("{}: {} -> {:?}", op.client, op.op.describe(), op.outcome))
+                    .collect();
+                return Err(format!(
+                    "operations on key {key} are not linearizable: {}",
+                    ops.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Search of the linearization of operations on one key,
+/// by Wing and Gong with memoization of visited states.
+struct Search<'a> {
+    ops: &'a [Operation],
+    visited: HashSet<(Vec<bool>, Option<u64>)>,
+}
+
+impl Search<'_> {
+    /// Returns result of the operation applied to the register, `None` if it is impossible.
+    fn apply(op: &Operation, value: Option<u64>) -> Option<Option<u64>> {
+        match (op.op, op.outcome) {
+            (Op::Read(_), Outcome::Ok(read)) => (read == value).then_some(value),
+            (Op::Write(_, written), _) => Some(Some(written)),
+            (Op::Cas(_, from, to), Outcome::Ok(_) | Outcome::Info) => {
+                (value == Some(from)).then_some(Some(to))
+            }
+            (Op::Cas(_, from, _), Outcome::Fail) => (value != Some(from)).then_some(value),
+            (Op::Read(_), _) => Some(value),
+        }
+    }
+
+    fn linearize(&mut self, done: &mut Vec<bool>, value: Option<u64>) -> bool {
+        // Operation can go next only if it was invoked
+        // before every other remaining operation with the result completed.
+        let deadline = (0..self.ops.len())
+            .filter(|i| !done[*i] && self.ops[*i].outcome != Outcome::Info)
+            .map(|i| self.ops[i].complete)
+            .min();
+        let Some(deadline) = deadline else {
+            return true;
+        };
+        if !self.visited.insert((done.clone(), value)) {
+            return false;
+        }
+        for i in 0..self.ops.len() {
+            let op = &self.ops[i];
+            if done[i] || op.invoke > deadline {
+                continue;
+            }
+            let Some(next) = Self::apply(op, value) else {
+                continue;
+            };
+            done[i] = true;
+            if self.linearize(done, next) {
+                return true;
+            }
+            done[i] = false;
+        }
+        false
+    }
+}
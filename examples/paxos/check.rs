@@ -0,0 +1,63 @@
+use std::collections::{BTreeMap, HashSet};
+
+use flurry::{Event, EventKind};
+
+/// Values proposed with `propose {value}` and `submit {value}`.
+fn proposed(trace: &[Event]) -> HashSet<&str> {
+    trace
+        .iter()
+        .filter_map(|event| match &event.kind {
+            EventKind::UserLocalMessage(_, msg) => msg
+                .strip_prefix("propose ")
+                .or_else(|| msg.strip_prefix("submit ")),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Decisions reported with `decide {slot} {value}`.
+fn decided(trace: &[Event]) -> Result<Vec<(flurry::ProcessId, u64, &str)>, String> {
+    trace
+        .iter()
+        .filter_map(|event| match &event.kind {
+            EventKind::ProcLocalMessage(proc, msg) => Some((*proc, msg.strip_prefix("decide ")?)),
+            _ => None,
+        })
+        .map(|(proc, decision)| {
+            let (slot, value) = decision
+                .split_once(' ')
+                .and_then(|(slot, value)| Some((slot.parse().ok()?, value)))
+                .ok_or(format!(
+                    "process {proc} reported invalid decision '{decision}'"
+                ))?;
+            Ok((proc, slot, value))
+        })
+        .collect()
+}
+
+/// Checks that processes decide the same value in every slot.
+pub fn check_agreement(trace: &[Event]) -> Result<(), String> {
+    let mut values: BTreeMap<u64, (flurry::ProcessId, &str)> = BTreeMap::new();
+    for (proc, slot, value) in decided(trace)? {
+        let (first, decided) = *values.entry(slot).or_insert((proc, value));
+        if decided != value {
+            return Err(format!(
+                "process {first} decided '{decided}' in slot {slot}, but process {proc} decided '{value}'"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every decided value was proposed.
+pub fn check_validity(trace: &[Event]) -> Result<(), String> {
+    let proposed = proposed(trace);
+    for (proc, slot, value) in decided(trace)? {
+        if !proposed.contains(value) {
+            return Err(format!(
+                "process {proc} decided '{value}' in slot {slot}, which was not proposed"
+            ));
+        }
+    }
+    Ok(())
+}
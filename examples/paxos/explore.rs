@@ -0,0 +1,169 @@
+//! Explores all orders of events of small Paxos runs, checking agreement and validity.
+//! Buggy variants must be caught by the checkers.
+//!
+//! Proposers retry preempted proposals with higher ballots once,
+//! and faulty runs drop a message and crash an acceptor.
+//! Faulty single-decree runs end early and are explored completely,
+//! all other runs only up to the depth, which keeps the whole exploration short.
+//!
+//! Usage: `paxos-explore [depth] [threads]`, depth is 10 by default.
+
+mod check;
+mod message;
+mod multi;
+mod roles;
+mod single;
+
+use std::{process::exit, time::Instant};
+
+use flurry::{EventKind, Explorer, Model, Process};
+use multi::MultiPaxos;
+use single::SingleDecree;
+
+const PROCESSES: usize = 3;
+/// In faulty runs the first message from `0` to the acceptor is dropped,
+/// and the acceptor crashes right after its first reply.
+const DROPPED: (flurry::ProcessId, flurry::ProcessId) = (0, CRASHED);
+const CRASHED: flurry::ProcessId = 2;
+
+/// Run where processes `0` and `1` propose different values.
+struct PaxosModel {
+    system: flurry::System,
+    faulty: bool,
+    dropped: bool,
+}
+
+impl PaxosModel {
+    fn new<P, F>(process: F, request: &str, faulty: bool) -> Self
+    where
+        P: Process + 'static,
+        F: Fn(flurry::ProcessId) -> P,
+    {
+        let mut system = flurry::System::default();
+        for proc in 0..PROCESSES {
+            system.add_process(process(proc));
+        }
+        system.send_local_message(0, &format!("{request} a"));
+        system.send_local_message(1, &format!("{request} b"));
+        Self {
+            system,
+            faulty,
+            dropped: false,
+        }
+    }
+
+    /// Injects faults as soon as they are possible,
+    /// so the explorer checks them in all orders of other events.
+    fn inject(&mut self) {
+        if !self.dropped {
+            let pending = self.system.get_pending_events();
+            let (from, to) = DROPPED;
+            let dropped = pending.iter().position(
+                |event| matches!(event, EventKind::MessageDelivered(src, dst, ..) if (*src, *dst) == (from, to)),
+            );
+            if let Some(event) = dropped {
+                self.system.drop_pending_event(event);
+                self.dropped = true;
+            }
+        }
+        if !self.system.is_crashed(CRASHED) {
+            let replied = self.system.get_trace().iter().any(
+                |event| matches!(event.kind, EventKind::MessageSent(from, ..) if from == CRASHED),
+            );
+            if replied {
+                self.system.crash_process(CRASHED);
+            }
+        }
+    }
+}
+
+impl Model for PaxosModel {
+    fn system(&mut self) -> &mut flurry::System {
+        &mut self.system
+    }
+
+    fn visit(&mut self) -> Result<bool, String> {
+        if self.faulty {
+            self.inject();
+        }
+        let trace = self.system.get_trace();
+        check::check_agreement(&trace)?;
+        check::check_validity(&trace)?;
+        Ok(false)
+    }
+}
+
+/// Explores runs of the factory, returning `true` if a violation is found.
+fn explore<F>(name: &str, factory: F, depth: Option<usize>, threads: Option<usize>) -> bool
+where
+    F: Fn() -> PaxosModel + Sync,
+{
+    let start = Instant::now();
+    let mut explorer = Explorer::new(factory);
+    if let Some(depth) = depth {
+        explorer = explorer.with_max_depth(depth);
+    }
+    if let Some(threads) = threads {
+        explorer = explorer.with_threads(threads);
+    }
+    let report = explorer.explore();
+    print!("{name}: {} states in {:?}", report.states, start.elapsed());
+    match report.violation {
+        Some(violation) => {
+            println!(", violation: {}", violation.error);
+            true
+        }
+        None => {
+            println!(", no violations");
+            false
+        }
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let depth = args
+        .next()
+        .map_or(10, |arg| arg.parse().expect("depth expected"));
+    let threads = args
+        .next()
+        .map(|arg| arg.parse().expect("number of threads expected"));
+
+    type Factory = Box<dyn Fn() -> PaxosModel + Sync>;
+    let single = |rounds, buggy, faulty| -> Factory {
+        Box::new(move || {
+            let process = |me| SingleDecree::new(me, PROCESSES, rounds, buggy);
+            PaxosModel::new(process, "propose", faulty)
+        })
+    };
+    let multi = |rounds, buggy, faulty| -> Factory {
+        Box::new(move || {
+            let process = |me| MultiPaxos::new(me, PROCESSES, rounds, buggy);
+            PaxosModel::new(process, "submit", faulty)
+        })
+    };
+
+    let (full, bounded) = (None, Some(depth));
+    let correct = [
+        ("single-decree", single(1, false, false), bounded),
+        ("single-decree, retries", single(2, false, false), bounded),
+        ("single-decree, faulty", single(2, false, true), full),
+        ("multi-paxos", multi(2, false, false), bounded),
+        ("multi-paxos, faulty", multi(2, false, true), bounded),
+    ];
+    let buggy = [
+        ("single-decree, buggy", single(1, true, false), bounded),
+        ("multi-paxos, buggy", multi(2, true, false), bounded),
+    ];
+    let mut failed = false;
+    for (name, factory, depth) in correct {
+        failed |= explore(name, factory, depth, threads);
+    }
+    for (name, factory, depth) in buggy {
+        failed |= !explore(name, factory, depth, threads);
+    }
+    if failed {
+        println!("Unexpected result");
+        exit(1);
+    }
+}
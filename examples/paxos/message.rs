@@ -0,0 +1,144 @@
+use flurry::json::Value;
+
+/// Ballot is ordered by the round, then by the proposer.
+pub type Ballot = (u64, flurry::ProcessId);
+
+/// Value accepted by the acceptor in the slot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accepted {
+    pub slot: u64,
+    pub ballot: Ballot,
+    pub value: String,
+}
+
+/// Messages of Paxos, encoded as JSON.
+/// Single-decree Paxos uses only the slot `0`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Prepare {
+        ballot: Ballot,
+    },
+    /// Reply to [`Message::Prepare`] of the ballot, with the highest promised ballot
+    /// and values accepted in all slots.
+    /// Prepare is rejected if the promised ballot is higher.
+    Promise {
+        ballot: Ballot,
+        promised: Ballot,
+        accepted: Vec<Accepted>,
+    },
+    Accept {
+        ballot: Ballot,
+        slot: u64,
+        value: String,
+    },
+    /// Reply to [`Message::Accept`] of the ballot, with the highest promised ballot.
+    AcceptReply {
+        ballot: Ballot,
+        slot: u64,
+        promised: Ballot,
+    },
+}
+
+fn ballot_to_json(ballot: Ballot) -> Value {
+    vec![ballot.0.into(), ballot.1.into()].into()
+}
+
+fn ballot_from_json(value: &Value) -> Option<Ballot> {
+    match value.as_array()? {
+        [round, proposer] => Some((round.as_u64()?, proposer.as_u64()? as usize)),
+        _ => None,
+    }
+}
+
+impl Message {
+    pub fn encode(&self) -> String {
+        let value = match self {
+            Message::Prepare { ballot } => Value::object([
+                ("type", "prepare".into()),
+                ("ballot", ballot_to_json(*ballot)),
+            ]),
+            Message::Promise {
+                ballot,
+                promised,
+                accepted,
+            } => {
+                let accepted: Vec<Value> = accepted
+                    .iter()
+                    .map(|accepted| {
+                        Value::object([
+                            ("slot", accepted.slot.into()),
+                            ("ballot", ballot_to_json(accepted.ballot)),
+                            ("value", accepted.value.as_str().into()),
+                        ])
+                    })
+                    .collect();
+                Value::object([
+                    ("type", "promise".into()),
+                    ("ballot", ballot_to_json(*ballot)),
+                    ("promised", ballot_to_json(*promised)),
+                    ("accepted", accepted.into()),
+                ])
+            }
+            Message::Accept {
+                ballot,
+                slot,
+                value,
+            } => Value::object([
+                ("type", "accept".into()),
+                ("ballot", ballot_to_json(*ballot)),
+                ("slot", (*slot).into()),
+                ("value", value.as_str().into()),
+            ]),
+            Message::AcceptReply {
+                ballot,
+                slot,
+                promised,
+            } => Value::object([
+                ("type", "accept_reply".into()),
+                ("ballot", ballot_to_json(*ballot)),
+                ("slot", (*slot).into()),
+                ("promised", ballot_to_json(*promised)),
+            ]),
+        };
+        value.to_string()
+    }
+
+    pub fn decode(msg: &str) -> Option<Message> {
+        let value = Value::parse(msg).ok()?;
+        let ballot = || ballot_from_json(value.get("ballot")?);
+        let slot = || value.get("slot")?.as_u64();
+        let text = || Some(value.get("value")?.as_str()?.to_string());
+        let promised = || ballot_from_json(value.get("promised")?);
+        let message = match value.get("type")?.as_str()? {
+            "prepare" => Message::Prepare { ballot: ballot()? },
+            "promise" => Message::Promise {
+                ballot: ballot()?,
+                promised: promised()?,
+                accepted: value
+                    .get("accepted")?
+                    .as_array()?
+                    .iter()
+                    .map(|accepted| {
+                        Some(Accepted {
+                            slot: accepted.get("slot")?.as_u64()?,
+                            ballot: ballot_from_json(accepted.get("ballot")?)?,
+                            value: accepted.get("value")?.as_str()?.to_string(),
+                        })
+                    })
+                    .collect::<Option<_>>()?,
+            },
+            "accept" => Message::Accept {
+                ballot: ballot()?,
+                slot: slot()?,
+                value: text()?,
+            },
+            "accept_reply" => Message::AcceptReply {
+                ballot: ballot()?,
+                slot: slot()?,
+                promised: promised()?,
+            },
+            _ => return None,
+        };
+        Some(message)
+    }
+}
@@ -0,0 +1,142 @@
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+use crate::{
+    message::{Ballot, Message},
+    roles::{Acceptor, Learner, Proposer},
+};
+
+/// State of the process as the leader of Multi-Paxos.
+#[derive(Default)]
+struct Leader {
+    /// Ballot for which phase 1 succeeded, which is used for all following slots.
+    ballot: Option<Ballot>,
+    next_slot: u64,
+    queue: VecDeque<String>,
+    running: bool,
+}
+
+/// Multi-Paxos replicated log: every process is the acceptor,
+/// and appends the command of the local message `submit {cmd}` to the log.
+///
+/// The process becomes the leader by running phase 1 once for all slots,
+/// re-proposing values accepted in them, and then runs only phase 2 for every command,
+/// until another leader preempts it.
+/// Phase 1 is retried up to `max_rounds` times, then queued commands wait for the next submit.
+pub struct MultiPaxos {
+    acceptor: Rc<RefCell<Acceptor>>,
+    learner: Rc<RefCell<Learner>>,
+    proposer: Rc<RefCell<Proposer>>,
+    leader: Rc<RefCell<Leader>>,
+    max_rounds: usize,
+}
+
+impl MultiPaxos {
+    pub fn new(me: flurry::ProcessId, processes: usize, max_rounds: usize, buggy: bool) -> Self {
+        let acceptor = Rc::new(RefCell::new(Acceptor::default()));
+        Self {
+            proposer: Proposer::new(me, processes, acceptor.clone(), buggy),
+            acceptor,
+            learner: Rc::new(RefCell::new(Learner::default())),
+            leader: Rc::new(RefCell::new(Leader::default())),
+            max_rounds,
+        }
+    }
+}
+
+/// Tries to become the leader, deciding values accepted by previous leaders.
+async fn elect(
+    proposer: &Rc<RefCell<Proposer>>,
+    learner: &Rc<RefCell<Learner>>,
+    leader: &Rc<RefCell<Leader>>,
+) {
+    let Some((ballot, accepted)) = Proposer::prepare(proposer).await else {
+        return;
+    };
+    let recovered = proposer.borrow().recover(&accepted);
+    let mut next_slot = learner
+        .borrow()
+        .decided
+        .keys()
+        .next_back()
+        .map_or(0, |slot| slot + 1);
+    for (slot, value) in recovered {
+        next_slot = next_slot.max(slot + 1);
+        if learner.borrow().decided.contains_key(&slot) {
+            continue;
+        }
+        if !Proposer::accept(proposer, ballot, slot, value.clone()).await {
+            return;
+        }
+        learner.borrow_mut().decide(slot, value);
+    }
+
+    let mut state = leader.borrow_mut();
+    state.ballot = Some(ballot);
+    state.next_slot = next_slot;
+}
+
+async fn lead(
+    proposer: Rc<RefCell<Proposer>>,
+    learner: Rc<RefCell<Learner>>,
+    leader: Rc<RefCell<Leader>>,
+    max_rounds: usize,
+) {
+    let mut rounds = 0;
+    loop {
+        let ballot = leader.borrow().ballot;
+        let ballot = match ballot {
+            Some(ballot) => ballot,
+            None if rounds < max_rounds => {
+                rounds += 1;
+                elect(&proposer, &learner, &leader).await;
+                continue;
+            }
+            None => break,
+        };
+
+        let (cmd, slot) = {
+            let mut state = leader.borrow_mut();
+            let Some(cmd) = state.queue.pop_front() else {
+                break;
+            };
+            state.next_slot += 1;
+            (cmd, state.next_slot - 1)
+        };
+        if Proposer::accept(&proposer, ballot, slot, cmd.clone()).await {
+            learner.borrow_mut().decide(slot, cmd);
+        } else {
+            let mut state = leader.borrow_mut();
+            state.queue.push_front(cmd);
+            state.ballot = None;
+        }
+    }
+    leader.borrow_mut().running = false;
+}
+
+impl flurry::Process for MultiPaxos {
+    fn on_message(&mut self, from: flurry::ProcessId, msg: String) {
+        let msg = Message::decode(&msg).expect("invalid message");
+        if let Some(reply) = self.acceptor.borrow_mut().on_message(&msg) {
+            flurry::send_unacked(from, reply.encode());
+        }
+        self.proposer.borrow_mut().on_message(from, &msg);
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        let cmd = msg
+            .strip_prefix("submit ")
+            .expect("unexpected local message");
+        let mut leader = self.leader.borrow_mut();
+        leader.queue.push_back(cmd.to_string());
+        if leader.running {
+            return;
+        }
+        leader.running = true;
+        flurry::spawn(lead(
+            self.proposer.clone(),
+            self.learner.clone(),
+            self.leader.clone(),
+            self.max_rounds,
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use flurry::quorum::{self, Replies};
+
+use crate::message::{Accepted, Ballot, Message};
+
+/// Acceptor of all slots, which shares the promised ballot between them.
+#[derive(Default)]
+pub struct Acceptor {
+    promised: Ballot,
+    accepted: BTreeMap<u64, (Ballot, String)>,
+}
+
+impl Acceptor {
+    /// Handles request of the proposer, returning the reply.
+    pub fn on_message(&mut self, msg: &Message) -> Option<Message> {
+        match msg {
+            Message::Prepare { ballot } => {
+                if *ballot >= self.promised {
+                    self.promised = *ballot;
+                }
+                Some(Message::Promise {
+                    ballot: *ballot,
+                    promised: self.promised,
+                    accepted: self
+                        .accepted
+                        .iter()
+                        .map(|(slot, (ballot, value))| Accepted {
+                            slot: *slot,
+                            ballot: *ballot,
+                            value: value.clone(),
+                        })
+                        .collect(),
+                })
+            }
+            Message::Accept {
+                ballot,
+                slot,
+                value,
+            } => {
+                if *ballot >= self.promised {
+                    self.promised = *ballot;
+                    self.accepted.insert(*slot, (*ballot, value.clone()));
+                }
+                Some(Message::AcceptReply {
+                    ballot: *ballot,
+                    slot: *slot,
+                    promised: self.promised,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Values decided by the proposer, which are reported as `decide {slot} {value}`.
+#[derive(Default)]
+pub struct Learner {
+    pub decided: BTreeMap<u64, String>,
+}
+
+impl Learner {
+    pub fn decide(&mut self, slot: u64, value: String) {
+        flurry::send_local(format!("decide {slot} {value}"));
+        self.decided.insert(slot, value);
+    }
+}
+
+/// Reply which the proposer waits for, used as the round of its replies.
+#[derive(Clone, Copy, PartialEq)]
+enum Phase {
+    Prepare(Ballot),
+    Accept(Ballot, u64),
+}
+
+/// Proposer, which runs phases of Paxos over the majority quorum of all processes.
+///
+/// Acceptor of the process is called directly and others are sent messages without acks,
+/// so the search explores fewer orders of events.
+pub struct Proposer {
+    me: flurry::ProcessId,
+    processes: usize,
+    acceptor: Rc<RefCell<Acceptor>>,
+    /// Buggy proposer ignores values accepted before its ballot,
+    /// so different values can be decided.
+    buggy: bool,
+    max_round: u64,
+    replies: Replies<Message, Phase>,
+}
+
+impl Proposer {
+    pub fn new(
+        me: flurry::ProcessId,
+        processes: usize,
+        acceptor: Rc<RefCell<Acceptor>>,
+        buggy: bool,
+    ) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            me,
+            processes,
+            acceptor,
+            buggy,
+            max_round: 0,
+            replies: Replies::new(),
+        }))
+    }
+
+    /// Handles reply of the acceptor, ignoring replies of previous phases.
+    pub fn on_message(&mut self, from: flurry::ProcessId, msg: &Message) {
+        let (phase, promised) = match msg {
+            Message::Promise {
+                ballot, promised, ..
+            } => (Phase::Prepare(*ballot), *promised),
+            Message::AcceptReply {
+                ballot,
+                slot,
+                promised,
+            } => (Phase::Accept(*ballot, *slot), *promised),
+            _ => return,
+        };
+        self.max_round = self.max_round.max(promised.0);
+        self.replies.put(from, phase, msg.clone());
+    }
+
+    /// Returns the value accepted with the highest ballot in the slot,
+    /// which must be proposed instead of a new one.
+    pub fn choose(&self, accepted: &[Accepted], slot: u64) -> Option<String> {
+        if self.buggy {
+            return None;
+        }
+        accepted
+            .iter()
+            .filter(|accepted| accepted.slot == slot)
+            .max_by_key(|accepted| accepted.ballot)
+            .map(|accepted| accepted.value.clone())
+    }
+
+    /// Returns values which must be proposed in every slot, see [`Proposer::choose`].
+    pub fn recover(&self, accepted: &[Accepted]) -> BTreeMap<u64, String> {
+        accepted
+            .iter()
+            .filter_map(|a| Some((a.slot, self.choose(accepted, a.slot)?)))
+            .collect()
+    }
+
+    /// Broadcasts request and waits for replies of the majority, including own acceptor,
+    /// returning them if none of the acceptors promised a higher ballot.
+    async fn request(
+        proposer: &Rc<RefCell<Self>>,
+        ballot: Ballot,
+        phase: Phase,
+        msg: Message,
+    ) -> Option<Vec<Message>> {
+        let (collect, own) = {
+            let state = proposer.borrow();
+            let own = state.acceptor.borrow_mut().on_message(&msg).unwrap();
+            let me = state.me;
+            let peers = (0..state.processes).filter(move |proc| *proc != me);
+            let collect = quorum::broadcast_unacked(peers, msg.encode()).collect(
+                &state.replies,
+                phase,
+                state.processes / 2,
+            );
+            (collect, own)
+        };
+        let replies = collect.await;
+        let mut replies: Vec<Message> = replies.ok()?.into_iter().map(|(_, reply)| reply).collect();
+        replies.push(own);
+        let rejected = replies.iter().any(|reply| match reply {
+            Message::Promise { promised, .. } | Message::AcceptReply { promised, .. } => {
+                *promised != ballot
+            }
+            _ => true,
+        });
+        (!rejected).then_some(replies)
+    }
+
+    /// Runs phase 1 with a new ballot, higher than all seen before.
+    /// Returns the ballot and values accepted by the quorum, `None` if the ballot was rejected.
+    pub async fn prepare(proposer: &Rc<RefCell<Self>>) -> Option<(Ballot, Vec<Accepted>)> {
+        let ballot = {
+            let mut state = proposer.borrow_mut();
+            state.max_round += 1;
+            (state.max_round, state.me)
+        };
+        let msg = Message::Prepare { ballot };
+        let replies = Self::request(proposer, ballot, Phase::Prepare(ballot), msg).await?;
+        let accepted = replies
+            .into_iter()
+            .flat_map(|reply| match reply {
+                Message::Promise { accepted, .. } => accepted,
+                _ => Vec::new(),
+            })
+            .collect();
+        Some((ballot, accepted))
+    }
+
+    /// Runs phase 2 for the value in the slot, returning `true` if the quorum accepted it.
+    pub async fn accept(
+        proposer: &Rc<RefCell<Self>>,
+        ballot: Ballot,
+        slot: u64,
+        value: String,
+    ) -> bool {
+        let msg = Message::Accept {
+            ballot,
+            slot,
+            value,
+        };
+        Self::request(proposer, ballot, Phase::Accept(ballot, slot), msg)
+            .await
+            .is_some()
+    }
+}
@@ -0,0 +1,63 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    message::Message,
+    roles::{Acceptor, Learner, Proposer},
+};
+
+/// Single-decree Paxos: every process is the acceptor,
+/// and proposes the value of the local message `propose {value}`.
+/// Proposal is retried with higher ballots up to `max_rounds` times.
+pub struct SingleDecree {
+    acceptor: Rc<RefCell<Acceptor>>,
+    learner: Rc<RefCell<Learner>>,
+    proposer: Rc<RefCell<Proposer>>,
+    max_rounds: usize,
+}
+
+impl SingleDecree {
+    pub fn new(me: flurry::ProcessId, processes: usize, max_rounds: usize, buggy: bool) -> Self {
+        let acceptor = Rc::new(RefCell::new(Acceptor::default()));
+        Self {
+            proposer: Proposer::new(me, processes, acceptor.clone(), buggy),
+            acceptor,
+            learner: Rc::new(RefCell::new(Learner::default())),
+            max_rounds,
+        }
+    }
+}
+
+impl flurry::Process for SingleDecree {
+    fn on_message(&mut self, from: flurry::ProcessId, msg: String) {
+        let msg = Message::decode(&msg).expect("invalid message");
+        if let Some(reply) = self.acceptor.borrow_mut().on_message(&msg) {
+            flurry::send_unacked(from, reply.encode());
+        }
+        self.proposer.borrow_mut().on_message(from, &msg);
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        let own = msg
+            .strip_prefix("propose ")
+            .expect("unexpected local message")
+            .to_string();
+        let proposer = self.proposer.clone();
+        let learner = self.learner.clone();
+        let max_rounds = self.max_rounds;
+        flurry::spawn(async move {
+            for _ in 0..max_rounds {
+                let Some((ballot, accepted)) = Proposer::prepare(&proposer).await else {
+                    continue;
+                };
+                let value = proposer
+                    .borrow()
+                    .choose(&accepted, 0)
+                    .unwrap_or(own.clone());
+                if Proposer::accept(&proposer, ballot, 0, value.clone()).await {
+                    learner.borrow_mut().decide(0, value);
+                    return;
+                }
+            }
+        });
+    }
+}
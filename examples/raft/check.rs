@@ -0,0 +1,197 @@
+//! Runs Raft under random crashes, message loss and partitions,
+//! checking its invariants after every step.
+//!
+//! Usage: `raft-check [runs] [snapshot threshold]`.
+//! Schedule of the first failed run is written to `raft-violation.jsonl`
+//! in the temporary directory.
+
+mod invariants;
+mod message;
+mod raft;
+
+use std::{cell::RefCell, fs::File, process::exit, rc::Rc};
+
+use flurry::{EventKind, Latency, Topology};
+use invariants::Invariants;
+use raft::{Config, Node, RaftProcess, Role};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+const PROCESSES: usize = 5;
+/// Faults are injected until this time, then the system must recover.
+const FAULTS_UNTIL: f64 = 300.0;
+/// Commands are submitted until this time, then they must be applied by all correct nodes.
+const SUBMIT_UNTIL: f64 = 350.0;
+const RUN_UNTIL: f64 = 400.0;
+
+struct Run {
+    system: flurry::System,
+    nodes: Vec<Rc<RefCell<Node>>>,
+    invariants: Invariants,
+    rng: StdRng,
+}
+
+impl Run {
+    fn new(seed: u64, config: &Config) -> Self {
+        let mut system = flurry::System::with_seed(seed);
+        system.set_latency(Latency::Uniform(0.2, 1.0));
+        let mut nodes = Vec::new();
+        for me in 0..PROCESSES {
+            let process = RaftProcess::new(me, PROCESSES, config.clone());
+            nodes.push(process.node());
+            system.add_process(process);
+        }
+        for proc in 0..PROCESSES {
+            system.send_local_message(proc, "start");
+        }
+        Self {
+            system,
+            nodes,
+            invariants: Invariants::default(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn correct(&self) -> Vec<flurry::ProcessId> {
+        (0..PROCESSES)
+            .filter(|proc| !self.system.is_crashed(*proc))
+            .collect()
+    }
+
+    fn leaders(&self) -> Vec<flurry::ProcessId> {
+        self.correct()
+            .into_iter()
+            .filter(|proc| self.nodes[*proc].borrow().role == Role::Leader)
+            .collect()
+    }
+
+    /// Makes one fault or submits command with some probability.
+    fn inject(&mut self, submitted: &mut usize) {
+        let roll: f64 = self.rng.gen();
+        let pending = self.system.get_pending_events();
+        if roll < 0.05 {
+            let messages: Vec<_> = (0..pending.len())
+                .filter(|event| matches!(pending[*event], EventKind::MessageDelivered(..)))
+                .collect();
+            if let Some(event) = messages.choose(&mut self.rng) {
+                self.system.drop_pending_event(*event);
+            }
+        } else if roll < 0.055 {
+            let crashed = PROCESSES - self.correct().len();
+            if crashed < (PROCESSES - 1) / 2 {
+                let proc = *self.correct().choose(&mut self.rng).unwrap();
+                self.system.crash_process(proc);
+            }
+        } else if roll < 0.06 {
+            let mut group: Vec<_> = (0..PROCESSES).collect();
+            group.shuffle(&mut self.rng);
+            let side = self.rng.gen_range(1..PROCESSES);
+            self.system.set_topology(partition(&group[..side]));
+        } else if roll < 0.07 {
+            self.system.set_topology(Topology::full_mesh(PROCESSES));
+        } else if roll < 0.1 {
+            self.submit(submitted);
+        }
+    }
+
+    fn submit(&mut self, submitted: &mut usize) {
+        if let Some(leader) = self.leaders().choose(&mut self.rng) {
+            self.system
+                .send_local_message(*leader, &format!("submit c{submitted}"));
+            *submitted += 1;
+        }
+    }
+
+    fn step(&mut self) -> Result<(), String> {
+        if !self.system.step() {
+            return Err("no pending events".to_string());
+        }
+        self.invariants.check(&self.nodes)
+    }
+
+    fn run(&mut self) -> Result<(), String> {
+        let mut submitted = 0;
+        while self.system.now() < FAULTS_UNTIL {
+            self.inject(&mut submitted);
+            self.step()?;
+        }
+        self.system.set_topology(Topology::full_mesh(PROCESSES));
+        while self.system.now() < RUN_UNTIL {
+            if self.system.now() < SUBMIT_UNTIL && self.rng.gen::<f64>() < 0.03 {
+                self.submit(&mut submitted);
+            }
+            self.step()?;
+        }
+        self.check_recovered()
+    }
+
+    fn applied(&self) -> usize {
+        self.nodes
+            .iter()
+            .map(|node| node.borrow().applied.len())
+            .max()
+            .unwrap()
+    }
+
+    /// After faults stop, the leader is elected and all correct nodes apply the same commands.
+    fn check_recovered(&self) -> Result<(), String> {
+        if self.leaders().len() != 1 {
+            return Err(format!("leaders after recovery: {:?}", self.leaders()));
+        }
+        let leader = self.nodes[self.leaders()[0]].borrow();
+        for proc in self.correct() {
+            let node = self.nodes[proc].borrow();
+            if node.applied.len() != leader.applied.len() {
+                return Err(format!(
+                    "node {proc} applied {} commands after recovery, leader {} applied {}",
+                    node.applied.len(),
+                    leader.me,
+                    leader.applied.len()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Cuts links between the group and other processes.
+fn partition(group: &[flurry::ProcessId]) -> Topology {
+    Topology::custom(
+        (0..PROCESSES)
+            .map(|proc| {
+                (0..PROCESSES)
+                    .filter(|other| {
+                        *other != proc && group.contains(other) == group.contains(&proc)
+                    })
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let runs = args
+        .next()
+        .map_or(100, |arg| arg.parse().expect("number of runs expected"));
+    let config = Config {
+        snapshot_threshold: args
+            .next()
+            .map(|arg| arg.parse().expect("snapshot threshold expected")),
+        ..Default::default()
+    };
+
+    let mut applied = 0;
+    for seed in 0..runs {
+        let mut run = Run::new(seed, &config);
+        if let Err(error) = run.run() {
+            println!("Failed with seed {seed}: {error}");
+            let path = std::env::temp_dir().join("raft-violation.jsonl");
+            let file = File::create(&path).unwrap();
+            run.system.get_schedule().write(file).unwrap();
+            println!("Schedule written to {}", path.display());
+            exit(1);
+        }
+        applied += run.applied();
+    }
+    println!("Passed {runs} runs, {applied} commands applied");
+}
@@ -0,0 +1,84 @@
+//! Explores all orders of events of three Raft nodes up to the depth,
+//! checking invariants in every state.
+//! Timers fire in any order, so elections can overlap in all possible ways.
+//!
+//! Usage: `raft-explore [depth] [threads]`.
+
+mod invariants;
+mod message;
+mod raft;
+
+use std::{cell::RefCell, rc::Rc};
+
+use flurry::{Explorer, Model};
+use invariants::Invariants;
+use raft::{Config, Node, RaftProcess, Role};
+
+const PROCESSES: usize = 3;
+
+struct RaftModel {
+    system: flurry::System,
+    nodes: Vec<Rc<RefCell<Node>>>,
+    invariants: Invariants,
+    submitted: bool,
+}
+
+impl RaftModel {
+    fn new() -> Self {
+        let mut system = flurry::System::default();
+        let mut nodes = Vec::new();
+        for me in 0..PROCESSES {
+            let process = RaftProcess::new(me, PROCESSES, Config::default());
+            nodes.push(process.node());
+            system.add_process(process);
+        }
+        for proc in 0..PROCESSES {
+            system.send_local_message(proc, "start");
+        }
+        Self {
+            system,
+            nodes,
+            invariants: Invariants::default(),
+            submitted: false,
+        }
+    }
+}
+
+impl Model for RaftModel {
+    fn system(&mut self) -> &mut flurry::System {
+        &mut self.system
+    }
+
+    /// Submits one command to the first elected leader.
+    fn visit(&mut self) -> Result<bool, String> {
+        self.invariants.check(&self.nodes)?;
+        if !self.submitted {
+            let leader =
+                (0..PROCESSES).find(|proc| self.nodes[*proc].borrow().role == Role::Leader);
+            if let Some(leader) = leader {
+                self.system.send_local_message(leader, "submit x");
+                self.submitted = true;
+            }
+        }
+        Ok(false)
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let depth = args
+        .next()
+        .map_or(7, |arg| arg.parse().expect("depth expected"));
+    let mut explorer = Explorer::new(RaftModel::new).with_max_depth(depth);
+    if let Some(threads) = args.next() {
+        explorer = explorer.with_threads(threads.parse().expect("number of threads expected"));
+    }
+    let report = explorer.explore();
+    if let Some(violation) = report.violation {
+        println!("Failed: {}", violation.error);
+        println!("Path: {:?}", violation.path);
+        return;
+    }
+    println!("Processed {} states", report.states);
+    println!("Elapsed time: {:?}", report.elapsed);
+}
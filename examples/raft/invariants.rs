@@ -0,0 +1,82 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::raft::{Node, Role};
+
+/// Safety invariants of Raft, checked over the states of all nodes after every step.
+/// Leaders and applied commands seen before are remembered,
+/// as nodes can step down or crash later.
+#[derive(Default)]
+pub struct Invariants {
+    leaders: HashMap<u64, flurry::ProcessId>,
+    applied: Vec<String>,
+}
+
+impl Invariants {
+    pub fn check(&mut self, nodes: &[Rc<RefCell<Node>>]) -> Result<(), String> {
+        let nodes: Vec<_> = nodes.iter().map(|node| node.borrow()).collect();
+        for node in nodes.iter() {
+            self.election_safety(node)?;
+            self.state_machine_safety(node)?;
+        }
+        for (i, a) in nodes.iter().enumerate() {
+            for b in nodes[i + 1..].iter() {
+                log_matching(a, b)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// At most one leader is elected in a term.
+    fn election_safety(&mut self, node: &Node) -> Result<(), String> {
+        if node.role != Role::Leader {
+            return Ok(());
+        }
+        let leader = *self.leaders.entry(node.term).or_insert(node.me);
+        if leader != node.me {
+            return Err(format!(
+                "election safety: nodes {leader} and {} are leaders in term {}",
+                node.me, node.term
+            ));
+        }
+        Ok(())
+    }
+
+    /// No two nodes apply different commands at the same index.
+    fn state_machine_safety(&mut self, node: &Node) -> Result<(), String> {
+        for (index, (cmd, seen)) in (1..).zip(node.applied.iter().zip(self.applied.iter())) {
+            if cmd != seen {
+                return Err(format!(
+                    "state machine safety: node {} applied '{cmd}' at index {index}, \
+                     but '{seen}' was applied before",
+                    node.me
+                ));
+            }
+        }
+        if node.applied.len() > self.applied.len() {
+            self.applied = node.applied.clone();
+        }
+        Ok(())
+    }
+}
+
+/// If logs of two nodes contain entries with the same index and term,
+/// then the logs are identical up to that index.
+/// Only entries which are not compacted in both logs are compared.
+fn log_matching(a: &Node, b: &Node) -> Result<(), String> {
+    let first = a.snapshot_index.max(b.snapshot_index) + 1;
+    let last = a.last_index().min(b.last_index());
+    let Some(matching) = (first..=last)
+        .rev()
+        .find(|index| a.term_at(*index) == b.term_at(*index))
+    else {
+        return Ok(());
+    };
+    match (first..=matching).find(|index| a.entry(*index) != b.entry(*index)) {
+        Some(index) => Err(format!(
+            "log matching: logs of nodes {} and {} have the same term at index {matching}, \
+             but differ at index {index}",
+            a.me, b.me
+        )),
+        None => Ok(()),
+    }
+}
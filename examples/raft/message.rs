@@ -0,0 +1,170 @@
+use flurry::json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub term: u64,
+    pub cmd: String,
+}
+
+/// Messages of the Raft protocol, encoded as JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    RequestVote {
+        term: u64,
+        last_index: u64,
+        last_term: u64,
+    },
+    Vote {
+        term: u64,
+        granted: bool,
+    },
+    AppendEntries {
+        term: u64,
+        prev_index: u64,
+        prev_term: u64,
+        entries: Vec<Entry>,
+        commit: u64,
+    },
+    /// Reply to [`Message::AppendEntries`] and [`Message::InstallSnapshot`].
+    /// On failure `last_index` is the last index of the follower's log.
+    AppendReply {
+        term: u64,
+        success: bool,
+        last_index: u64,
+    },
+    InstallSnapshot {
+        term: u64,
+        index: u64,
+        last_term: u64,
+        /// Commands applied to the state machine up to the index.
+        state: Vec<String>,
+    },
+}
+
+impl Message {
+    pub fn encode(&self) -> String {
+        let value = match self {
+            Message::RequestVote {
+                term,
+                last_index,
+                last_term,
+            } => Value::object([
+                ("type", "request_vote".into()),
+                ("term", (*term).into()),
+                ("last_index", (*last_index).into()),
+                ("last_term", (*last_term).into()),
+            ]),
+            Message::Vote { term, granted } => Value::object([
+                ("type", "vote".into()),
+                ("term", (*term).into()),
+                ("granted", (*granted).into()),
+            ]),
+            Message::AppendEntries {
+                term,
+                prev_index,
+                prev_term,
+                entries,
+                commit,
+            } => {
+                let entries: Vec<Value> = entries
+                    .iter()
+                    .map(|entry| {
+                        Value::object([
+                            ("term", entry.term.into()),
+                            ("cmd", entry.cmd.as_str().into()),
+                        ])
+                    })
+                    .collect();
+                Value::object([
+                    ("type", "append_entries".into()),
+                    ("term", (*term).into()),
+                    ("prev_index", (*prev_index).into()),
+                    ("prev_term", (*prev_term).into()),
+                    ("entries", entries.into()),
+                    ("commit", (*commit).into()),
+                ])
+            }
+            Message::AppendReply {
+                term,
+                success,
+                last_index,
+            } => Value::object([
+                ("type", "append_reply".into()),
+                ("term", (*term).into()),
+                ("success", (*success).into()),
+                ("last_index", (*last_index).into()),
+            ]),
+            Message::InstallSnapshot {
+                term,
+                index,
+                last_term,
+                state,
+            } => {
+                let state: Vec<Value> = state.iter().map(|cmd| cmd.as_str().into()).collect();
+                Value::object([
+                    ("type", "install_snapshot".into()),
+                    ("term", (*term).into()),
+                    ("index", (*index).into()),
+                    ("last_term", (*last_term).into()),
+                    ("state", state.into()),
+                ])
+            }
+        };
+        value.to_string()
+    }
+
+    pub fn decode(msg: &str) -> Option<Message> {
+        let value = Value::parse(msg).ok()?;
+        let number = |key: &str| value.get(key).and_then(Value::as_u64);
+        let flag = |key: &str| match value.get(key)? {
+            Value::Bool(flag) => Some(*flag),
+            _ => None,
+        };
+        let message = match value.get("type")?.as_str()? {
+            "request_vote" => Message::RequestVote {
+                term: number("term")?,
+                last_index: number("last_index")?,
+                last_term: number("last_term")?,
+            },
+            "vote" => Message::Vote {
+                term: number("term")?,
+                granted: flag("granted")?,
+            },
+            "append_entries" => Message::AppendEntries {
+                term: number("term")?,
+                prev_index: number("prev_index")?,
+                prev_term: number("prev_term")?,
+                entries: value
+                    .get("entries")?
+                    .as_array()?
+                    .iter()
+                    .map(|entry| {
+                        Some(Entry {
+                            term: entry.get("term")?.as_u64()?,
+                            cmd: entry.get("cmd")?.as_str()?.to_string(),
+                        })
+                    })
+                    .collect::<Option<_>>()?,
+                commit: number("commit")?,
+            },
+            "append_reply" => Message::AppendReply {
+                term: number("term")?,
+                success: flag("success")?,
+                last_index: number("last_index")?,
+            },
+            "install_snapshot" => Message::InstallSnapshot {
+                term: number("term")?,
+                index: number("index")?,
+                last_term: number("last_term")?,
+                state: value
+                    .get("state")?
+                    .as_array()?
+                    .iter()
+                    .map(|cmd| cmd.as_str().map(str::to_string))
+                    .collect::<Option<_>>()?,
+            },
+            _ => return None,
+        };
+        Some(message)
+    }
+}
@@ -0,0 +1,486 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+    rc::{Rc, Weak},
+};
+
+use futures::{
+    channel::oneshot,
+    future::{select, Either},
+};
+
+use crate::message::{Entry, Message};
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Bounds of the randomized election timeout.
+    pub election_timeout: (f64, f64),
+    pub heartbeat: f64,
+    /// Log is compacted into a snapshot when this many applied entries are in the log.
+    pub snapshot_threshold: Option<usize>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            election_timeout: (5.0, 10.0),
+            heartbeat: 1.0,
+            snapshot_threshold: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+/// State of the Raft node, which is shared with its timers
+/// and inspected by the invariants.
+pub struct Node {
+    pub me: flurry::ProcessId,
+    pub peers: Vec<flurry::ProcessId>,
+    config: Config,
+    this: Weak<RefCell<Node>>,
+
+    pub term: u64,
+    pub voted_for: Option<flurry::ProcessId>,
+    pub role: Role,
+    /// Entries after the snapshot, entry at position `i` has index `snapshot_index + 1 + i`.
+    pub log: Vec<Entry>,
+    pub snapshot_index: u64,
+    pub snapshot_term: u64,
+    pub commit_index: u64,
+    /// Commands applied to the state machine, command with index `i` is at position `i - 1`.
+    pub applied: Vec<String>,
+
+    votes: BTreeSet<flurry::ProcessId>,
+    next_index: HashMap<flurry::ProcessId, u64>,
+    match_index: HashMap<flurry::ProcessId, u64>,
+    /// Cancels the running election timer when dropped.
+    election_timer: Option<oneshot::Sender<()>>,
+}
+
+fn send(to: flurry::ProcessId, msg: Message) {
+    // Peers behind a partition are unreachable, the next heartbeat or election retries.
+    let _ = flurry::try_send_unacked(to, msg.encode());
+}
+
+impl Node {
+    pub fn last_index(&self) -> u64 {
+        self.snapshot_index + self.log.len() as u64
+    }
+
+    /// Returns term of the entry, `None` if it is compacted or missing.
+    pub fn term_at(&self, index: u64) -> Option<u64> {
+        if index == self.snapshot_index {
+            return Some(self.snapshot_term);
+        }
+        self.entry(index).map(|entry| entry.term)
+    }
+
+    pub fn entry(&self, index: u64) -> Option<&Entry> {
+        let pos = index.checked_sub(self.snapshot_index + 1)?;
+        self.log.get(pos as usize)
+    }
+
+    fn quorum(&self) -> usize {
+        let processes = self.peers.len() + 1;
+        processes / 2 + 1
+    }
+
+    fn reset_election_timer(&mut self) {
+        let (cancel, cancelled) = oneshot::channel();
+        self.election_timer = Some(cancel);
+        let (min, max) = self.config.election_timeout;
+        let unit = flurry::random() as f64 / (u64::MAX as f64 + 1.0);
+        let timeout = min + (max - min) * unit;
+        let node = self.this.upgrade().unwrap();
+        flurry::spawn(async move {
+            if let Either::Left(_) = select(flurry::sleep(timeout), cancelled).await {
+                node.borrow_mut().start_election();
+            }
+        });
+    }
+
+    fn start_election(&mut self) {
+        self.term += 1;
+        self.role = Role::Candidate;
+        self.voted_for = Some(self.me);
+        self.votes = BTreeSet::from([self.me]);
+        for to in self.peers.iter() {
+            send(
+                *to,
+                Message::RequestVote {
+                    term: self.term,
+                    last_index: self.last_index(),
+                    last_term: self.term_at(self.last_index()).unwrap(),
+                },
+            );
+        }
+        self.reset_election_timer();
+        if self.votes.len() >= self.quorum() {
+            self.become_leader();
+        }
+    }
+
+    fn become_leader(&mut self) {
+        self.role = Role::Leader;
+        self.election_timer = None;
+        let next = self.last_index() + 1;
+        self.next_index = self.peers.iter().map(|peer| (*peer, next)).collect();
+        self.match_index = self.peers.iter().map(|peer| (*peer, 0)).collect();
+        flurry::send_local(format!("leader {}", self.term));
+
+        let term = self.term;
+        let heartbeat = self.config.heartbeat;
+        let node = self.this.upgrade().unwrap();
+        flurry::spawn(async move {
+            loop {
+                {
+                    let node = node.borrow();
+                    if node.role != Role::Leader || node.term != term {
+                        return;
+                    }
+                    for to in node.peers.iter() {
+                        node.replicate(*to);
+                    }
+                }
+                flurry::sleep(heartbeat).await;
+            }
+        });
+    }
+
+    /// Steps down if the message has newer term.
+    fn observe_term(&mut self, term: u64) {
+        if term <= self.term {
+            return;
+        }
+        let was_leader = self.role == Role::Leader;
+        self.term = term;
+        self.voted_for = None;
+        self.role = Role::Follower;
+        if was_leader {
+            self.reset_election_timer();
+        }
+    }
+
+    fn replicate(&self, to: flurry::ProcessId) {
+        let next = self.next_index[&to];
+        if next <= self.snapshot_index {
+            send(
+                to,
+                Message::InstallSnapshot {
+                    term: self.term,
+                    index: self.snapshot_index,
+                    last_term: self.snapshot_term,
+                    state: self.applied[..self.snapshot_index as usize].to_vec(),
+                },
+            );
+            return;
+        }
+        let prev_index = next - 1;
+        send(
+            to,
+            Message::AppendEntries {
+                term: self.term,
+                prev_index,
+                prev_term: self.term_at(prev_index).unwrap(),
+                entries: self.log[(prev_index - self.snapshot_index) as usize..].to_vec(),
+                commit: self.commit_index,
+            },
+        );
+    }
+
+    fn submit(&mut self, cmd: String) {
+        if self.role != Role::Leader {
+            return;
+        }
+        self.log.push(Entry {
+            term: self.term,
+            cmd,
+        });
+        for to in self.peers.iter() {
+            self.replicate(*to);
+        }
+        self.advance_commit();
+    }
+
+    fn on_request_vote(
+        &mut self,
+        from: flurry::ProcessId,
+        term: u64,
+        last_index: u64,
+        last_term: u64,
+    ) {
+        self.observe_term(term);
+        let my_last = (self.term_at(self.last_index()).unwrap(), self.last_index());
+        let granted = term == self.term
+            && self.voted_for.is_none_or(|voted| voted == from)
+            && (last_term, last_index) >= my_last;
+        if granted {
+            self.voted_for = Some(from);
+            self.reset_election_timer();
+        }
+        send(
+            from,
+            Message::Vote {
+                term: self.term,
+                granted,
+            },
+        );
+    }
+
+    fn on_vote(&mut self, from: flurry::ProcessId, term: u64, granted: bool) {
+        self.observe_term(term);
+        if self.role != Role::Candidate || term != self.term || !granted {
+            return;
+        }
+        self.votes.insert(from);
+        if self.votes.len() >= self.quorum() {
+            self.become_leader();
+        }
+    }
+
+    /// Accepts leader of the current term.
+    fn follow(&mut self) {
+        self.role = Role::Follower;
+        self.reset_election_timer();
+    }
+
+    fn on_append_entries(
+        &mut self,
+        from: flurry::ProcessId,
+        term: u64,
+        mut prev_index: u64,
+        mut prev_term: u64,
+        mut entries: Vec<Entry>,
+        commit: u64,
+    ) {
+        self.observe_term(term);
+        if term < self.term {
+            return self.reply(from, false, self.last_index());
+        }
+        self.follow();
+
+        let last_new = prev_index + entries.len() as u64;
+        if prev_index < self.snapshot_index {
+            // Entries up to the snapshot are committed, so they match.
+            let skip = (self.snapshot_index - prev_index).min(entries.len() as u64);
+            entries.drain(..skip as usize);
+            prev_index = self.snapshot_index;
+            prev_term = self.snapshot_term;
+            if entries.is_empty() {
+                return self.reply(from, true, last_new);
+            }
+        }
+        if self.term_at(prev_index) != Some(prev_term) {
+            return self.reply(from, false, self.last_index());
+        }
+
+        for (index, entry) in (prev_index + 1..).zip(entries) {
+            match self.term_at(index) {
+                Some(term) if term == entry.term => continue,
+                Some(_) => self
+                    .log
+                    .truncate((index - self.snapshot_index - 1) as usize),
+                None => {}
+            }
+            self.log.push(entry);
+        }
+        if commit > self.commit_index {
+            self.commit_index = commit.min(last_new).max(self.commit_index);
+            self.apply();
+        }
+        self.reply(from, true, last_new);
+    }
+
+    /// Replies with the last replicated index on success, or the last index of the log.
+    fn reply(&self, to: flurry::ProcessId, success: bool, last_index: u64) {
+        send(
+            to,
+            Message::AppendReply {
+                term: self.term,
+                success,
+                last_index,
+            },
+        );
+    }
+
+    fn on_install_snapshot(
+        &mut self,
+        from: flurry::ProcessId,
+        term: u64,
+        index: u64,
+        last_term: u64,
+        state: Vec<String>,
+    ) {
+        self.observe_term(term);
+        if term < self.term {
+            return self.reply(from, false, self.last_index());
+        }
+        self.follow();
+
+        if index > self.commit_index {
+            if self.term_at(index) == Some(last_term) {
+                self.log.drain(..(index - self.snapshot_index) as usize);
+            } else {
+                self.log.clear();
+            }
+            self.snapshot_index = index;
+            self.snapshot_term = last_term;
+            self.commit_index = index;
+            for (index, cmd) in (1..).zip(state).skip(self.applied.len()) {
+                flurry::send_local(format!("apply {index} {cmd}"));
+                self.applied.push(cmd);
+            }
+        }
+        self.reply(from, true, index);
+    }
+
+    fn on_append_reply(
+        &mut self,
+        from: flurry::ProcessId,
+        term: u64,
+        success: bool,
+        last_index: u64,
+    ) {
+        self.observe_term(term);
+        if self.role != Role::Leader || term != self.term {
+            return;
+        }
+        if success {
+            let matched = self.match_index.get_mut(&from).unwrap();
+            *matched = (*matched).max(last_index);
+            let next = self.next_index.get_mut(&from).unwrap();
+            *next = (*next).max(last_index + 1);
+            self.advance_commit();
+        } else {
+            let next = self.next_index.get_mut(&from).unwrap();
+            *next = (*next - 1).min(last_index + 1).max(1);
+            self.replicate(from);
+        }
+    }
+
+    /// Commits the last entry of the current term which is replicated on the quorum.
+    fn advance_commit(&mut self) {
+        let replicated = |index: u64| {
+            1 + self
+                .match_index
+                .values()
+                .filter(|matched| **matched >= index)
+                .count()
+        };
+        let committed = (self.commit_index + 1..=self.last_index())
+            .rev()
+            .find(|index| {
+                self.term_at(*index) == Some(self.term) && replicated(*index) >= self.quorum()
+            });
+        if let Some(index) = committed {
+            self.commit_index = index;
+            self.apply();
+        }
+    }
+
+    /// Applies committed entries and compacts the log.
+    fn apply(&mut self) {
+        while (self.applied.len() as u64) < self.commit_index {
+            let index = self.applied.len() as u64 + 1;
+            let cmd = self.entry(index).unwrap().cmd.clone();
+            flurry::send_local(format!("apply {index} {cmd}"));
+            self.applied.push(cmd);
+        }
+        let Some(threshold) = self.config.snapshot_threshold else {
+            return;
+        };
+        let applied = self.applied.len() as u64;
+        if applied - self.snapshot_index >= threshold as u64 {
+            self.snapshot_term = self.term_at(applied).unwrap();
+            self.log.drain(..(applied - self.snapshot_index) as usize);
+            self.snapshot_index = applied;
+        }
+    }
+}
+
+/// Raft node, which starts on the local message `start`
+/// and appends `cmd` to the log on the local message `submit {cmd}` if it is the leader.
+///
+/// Reports `leader {term}` when it becomes the leader and `apply {index} {cmd}`
+/// when the command is applied to the state machine.
+pub struct RaftProcess {
+    node: Rc<RefCell<Node>>,
+}
+
+impl RaftProcess {
+    pub fn new(me: flurry::ProcessId, processes: usize, config: Config) -> Self {
+        let node = Rc::new_cyclic(|this| {
+            RefCell::new(Node {
+                me,
+                peers: (0..processes).filter(|proc| *proc != me).collect(),
+                config,
+                this: this.clone(),
+                term: 0,
+                voted_for: None,
+                role: Role::Follower,
+                log: Vec::new(),
+                snapshot_index: 0,
+                snapshot_term: 0,
+                commit_index: 0,
+                applied: Vec::new(),
+                votes: BTreeSet::new(),
+                next_index: HashMap::new(),
+                match_index: HashMap::new(),
+                election_timer: None,
+            })
+        });
+        Self { node }
+    }
+
+    /// Returns state of the node, e.g. to check invariants.
+    pub fn node(&self) -> Rc<RefCell<Node>> {
+        self.node.clone()
+    }
+}
+
+impl flurry::Process for RaftProcess {
+    fn on_message(&mut self, from: flurry::ProcessId, msg: String) {
+        let mut node = self.node.borrow_mut();
+        match Message::decode(&msg).expect("invalid message") {
+            Message::RequestVote {
+                term,
+                last_index,
+                last_term,
+            } => node.on_request_vote(from, term, last_index, last_term),
+            Message::Vote { term, granted } => node.on_vote(from, term, granted),
+            Message::AppendEntries {
+                term,
+                prev_index,
+                prev_term,
+                entries,
+                commit,
+            } => node.on_append_entries(from, term, prev_index, prev_term, entries, commit),
+            Message::AppendReply {
+                term,
+                success,
+                last_index,
+            } => node.on_append_reply(from, term, success, last_index),
+            Message::InstallSnapshot {
+                term,
+                index,
+                last_term,
+                state,
+            } => node.on_install_snapshot(from, term, index, last_term, state),
+        }
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        let mut node = self.node.borrow_mut();
+        match msg.split_once(' ') {
+            Some(("submit", cmd)) => node.submit(cmd.to_string()),
+            None if msg == "start" => node.reset_election_timer(),
+            _ => panic!("unexpected local message '{msg}'"),
+        }
+    }
+}
@@ -7,14 +7,51 @@ use std::{
 
 use futures::Future;
 
-use crate::shared::SharedState;
+use crate::{
+    event::MessageId,
+    shared::SharedState,
+    time::{sleep, Sleep},
+};
+
+/// Result of waiting for the acknowledgement of the message.
+///
+/// In the [`crate::System`] every [`AckHandle`] is resolved
+/// once the message is acknowledged or can not be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckOutcome {
+    Delivered,
+    /// Message or its ack was dropped.
+    Lost,
+    /// Receiver crashed before the message was delivered.
+    ReceiverCrashed,
+    /// Ack did not arrive before the timeout of [`AckHandle::timeout`].
+    TimedOut,
+    /// Sender crashed before the ack arrived.
+    Cancelled,
+}
 
 pub struct AckHandle {
-    pub(crate) flag: Rc<RefCell<SharedState<bool>>>,
+    pub(crate) flag: Rc<RefCell<SharedState<AckOutcome>>>,
+    pub(crate) msg_id: MessageId,
+}
+
+impl AckHandle {
+    /// Returns id of the message, which is used in its events.
+    pub fn msg_id(&self) -> MessageId {
+        self.msg_id
+    }
+
+    /// Waits for the ack no longer than the duration, measured like in [`crate::sleep`].
+    pub fn timeout(self, duration: f64) -> AckTimeout {
+        AckTimeout {
+            ack: self,
+            sleep: Some(sleep(duration)),
+        }
+    }
 }
 
 impl Future for AckHandle {
-    type Output = bool;
+    type Output = AckOutcome;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if let Some(value) = self.flag.borrow_mut().take(cx.waker().clone()) {
@@ -24,3 +61,32 @@ impl Future for AckHandle {
         }
     }
 }
+
+/// Future returned by [`AckHandle::timeout`],
+/// which is resolved with [`AckOutcome::TimedOut`] if the timer fires first.
+/// Its timer is cancelled once it is resolved or dropped.
+pub struct AckTimeout {
+    ack: AckHandle,
+    sleep: Option<Sleep>,
+}
+
+impl Future for AckTimeout {
+    type Output = AckOutcome;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(outcome) = Pin::new(&mut self.ack).poll(cx) {
+            self.sleep = None;
+            return Poll::Ready(outcome);
+        }
+        let Some(sleep) = self.sleep.as_mut() else {
+            return Poll::Ready(AckOutcome::TimedOut);
+        };
+        match Pin::new(sleep).poll(cx) {
+            Poll::Ready(()) => {
+                self.sleep = None;
+                Poll::Ready(AckOutcome::TimedOut)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
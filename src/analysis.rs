@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::{
+    clock::VectorClock,
+    event::{Event, EventKind},
+};
+
+/// Answers causality questions about the trace.
+///
+/// Events are referenced by their indices in the trace
+/// which was passed to [`TraceAnalysis::new`].
+pub struct TraceAnalysis {
+    clocks: Vec<VectorClock>,
+    lamport: Vec<u64>,
+}
+
+impl TraceAnalysis {
+    pub fn new(trace: &[Event]) -> Self {
+        let mut process_time: Vec<u64> = Vec::new();
+        let mut messages = HashMap::new();
+        let mut acks = HashMap::new();
+        let mut lamport = Vec::with_capacity(trace.len());
+        for event in trace.iter() {
+            let proc = event.kind.process();
+            if process_time.len() <= proc {
+                process_time.resize(proc + 1, 0);
+            }
+            let received = match &event.kind {
+                EventKind::MessageDelivered(_, _, msg_id, _) => messages.get(msg_id).copied(),
+                EventKind::AckDelivered(_, _, msg_id) => acks.get(msg_id).copied(),
+                _ => None,
+            };
+            let time = process_time[proc].max(received.unwrap_or(0)) + 1;
+            process_time[proc] = time;
+            match &event.kind {
+                EventKind::MessageSent(_, _, msg_id, _) => {
+                    messages.insert(*msg_id, time);
+                }
+                EventKind::AckSent(_, _, msg_id) => {
+                    acks.insert(*msg_id, time);
+                }
+                _ => {}
+            }
+            lamport.push(time);
+        }
+
+        Self {
+            clocks: trace.iter().map(|event| event.clock.clone()).collect(),
+            lamport,
+        }
+    }
+
+    /// Returns number of analyzed events.
+    pub fn len(&self) -> usize {
+        self.clocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clocks.is_empty()
+    }
+
+    /// Returns `true` if event `e1` happens before event `e2`.
+    pub fn happens_before(&self, e1: usize, e2: usize) -> bool {
+        self.clocks[e1].happens_before(&self.clocks[e2])
+    }
+
+    /// Returns `true` if none of events `e1` and `e2` happens before other.
+    pub fn concurrent(&self, e1: usize, e2: usize) -> bool {
+        e1 != e2 && self.clocks[e1].concurrent(&self.clocks[e2])
+    }
+
+    /// Returns indices of all events which happen before event `e`, in the trace order.
+    pub fn causal_past(&self, e: usize) -> Vec<usize> {
+        (0..e).filter(|i| self.happens_before(*i, e)).collect()
+    }
+
+    /// Returns Lamport timestamp of event `e`.
+    ///
+    /// Timestamps are consistent with happens-before relation:
+    /// if `e1` happens before `e2`, then timestamp of `e1` is less than timestamp of `e2`.
+    pub fn lamport(&self, e: usize) -> u64 {
+        self.lamport[e]
+    }
+
+    /// Returns Lamport timestamps of all events in the trace order.
+    pub fn lamport_timestamps(&self) -> &[u64] {
+        &self.lamport
+    }
+}
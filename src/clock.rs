@@ -0,0 +1,111 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::{
+    event::{EventKind, MessageId},
+    process::ProcessId,
+};
+
+/// Vector clock of the event.
+///
+/// Entry `i` is the number of events of the process `i`
+/// which happened before or at the event.
+/// Missing entries are zeros.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct VectorClock(Vec<u64>);
+
+impl VectorClock {
+    pub fn get(&self, proc: ProcessId) -> u64 {
+        self.0.get(proc).copied().unwrap_or(0)
+    }
+
+    /// Returns non-zero entries of the clock.
+    pub fn entries(&self) -> impl Iterator<Item = (ProcessId, u64)> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value > 0)
+            .map(|(proc, value)| (proc, *value))
+    }
+
+    /// Returns `true` if event with this clock happened before event with the `other` clock.
+    pub fn happens_before(&self, other: &VectorClock) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Less)
+    }
+
+    /// Returns `true` if events with these clocks are not ordered by happens-before relation.
+    pub fn concurrent(&self, other: &VectorClock) -> bool {
+        self.partial_cmp(other).is_none()
+    }
+
+    fn tick(&mut self, proc: ProcessId) {
+        if self.0.len() <= proc {
+            self.0.resize(proc + 1, 0);
+        }
+        self.0[proc] += 1;
+    }
+
+    fn merge(&mut self, other: &VectorClock) {
+        if self.0.len() < other.0.len() {
+            self.0.resize(other.0.len(), 0);
+        }
+        for (mine, theirs) in self.0.iter_mut().zip(other.0.iter()) {
+            *mine = (*mine).max(*theirs);
+        }
+    }
+}
+
+impl PartialOrd for VectorClock {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let len = self.0.len().max(other.0.len());
+        let mut result = Ordering::Equal;
+        for proc in 0..len {
+            match (self.get(proc).cmp(&other.get(proc)), result) {
+                (Ordering::Equal, _) => {}
+                (ord, Ordering::Equal) => result = ord,
+                (ord, current) if ord != current => return None,
+                _ => {}
+            }
+        }
+        Some(result)
+    }
+}
+
+/// Maintains vector clocks of processes while events are observed in the trace order.
+/// Messages and acks carry clock of the event which sent them.
+#[derive(Default)]
+pub(crate) struct Clocks {
+    processes: Vec<VectorClock>,
+    messages: HashMap<MessageId, VectorClock>,
+    acks: HashMap<MessageId, VectorClock>,
+}
+
+impl Clocks {
+    /// Returns vector clock of the observed event.
+    pub(crate) fn observe(&mut self, kind: &EventKind) -> VectorClock {
+        let proc = kind.process();
+        if self.processes.len() <= proc {
+            self.processes.resize(proc + 1, VectorClock::default());
+        }
+        let received = match kind {
+            EventKind::MessageDelivered(_, _, msg_id, _) => self.messages.get(msg_id),
+            EventKind::AckDelivered(_, _, msg_id) => self.acks.get(msg_id),
+            _ => None,
+        };
+        let clock = &mut self.processes[proc];
+        if let Some(received) = received {
+            clock.merge(received);
+        }
+        clock.tick(proc);
+        let clock = clock.clone();
+        match kind {
+            EventKind::MessageSent(_, _, msg_id, _) => {
+                self.messages.insert(*msg_id, clock.clone());
+            }
+            EventKind::AckSent(_, _, msg_id) => {
+                self.acks.insert(*msg_id, clock.clone());
+            }
+            _ => {}
+        }
+        clock
+    }
+}
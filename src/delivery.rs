@@ -0,0 +1,26 @@
+/// Delivery guarantees of the link, set with [`crate::System::set_delivery`].
+///
+/// Messages and acks sent over the link from one process to another
+/// share the guarantees, acks travel over the link back to the sender.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Delivery {
+    /// Messages can be delivered in any order or dropped, like in UDP.
+    #[default]
+    Unordered,
+    /// Messages are delivered in the order of sending, but can be dropped.
+    /// Only the first message in flight is pending.
+    FifoLossy,
+    /// Messages are delivered in the order of sending and can not be dropped, like in TCP.
+    /// They are still lost when the receiver crashes.
+    FifoReliable,
+}
+
+impl Delivery {
+    pub fn is_fifo(&self) -> bool {
+        matches!(self, Delivery::FifoLossy | Delivery::FifoReliable)
+    }
+
+    pub fn is_reliable(&self) -> bool {
+        matches!(self, Delivery::FifoReliable)
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    event::{Event, EventKind},
+    process::ProcessId,
+};
+
+/// Selects optional layers of the rendered diagrams.
+/// Messages delivered between processes are always drawn.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiagramOptions {
+    /// Draw acknowledgements as dashed arrows.
+    pub acks: bool,
+    /// Draw local messages of the user and processes and fired timers as notes.
+    pub local_messages: bool,
+}
+
+enum Step {
+    Arrow {
+        from: ProcessId,
+        to: ProcessId,
+        label: String,
+        dashed: bool,
+    },
+    /// Message or ack which was dropped
+    /// or was not delivered until the end of the trace.
+    Lost {
+        from: ProcessId,
+        to: ProcessId,
+        label: String,
+        reason: &'static str,
+    },
+    Note {
+        proc: ProcessId,
+        label: String,
+    },
+}
+
+struct Diagram {
+    processes: usize,
+    steps: Vec<(f64, Step)>,
+}
+
+fn message_label(msg_id: usize, msg: &str) -> String {
+    format!("m{msg_id}: {msg}")
+}
+
+fn ack_label(msg_id: usize) -> String {
+    format!("ack m{msg_id}")
+}
+
+/// Matches sent and delivered events by message id
+/// and builds steps which are common for all renderers.
+fn layout(trace: &[Event], options: &DiagramOptions) -> Diagram {
+    let mut processes = 0;
+    let mut delivered = HashSet::new();
+    let mut acked = HashSet::new();
+    for event in trace {
+        match &event.kind {
+            EventKind::ProcLocalMessage(proc, _)
+            | EventKind::UserLocalMessage(proc, _)
+            | EventKind::ProcessCrashed(proc)
+            | EventKind::TimerSet(proc, _)
+            | EventKind::TimerFired(proc, _)
+            | EventKind::ClockJumped(proc, _) => {
+                processes = processes.max(proc + 1);
+            }
+            EventKind::MessageSent(from, to, _, _)
+            | EventKind::AckSent(from, to, _)
+            | EventKind::MessageRetried(from, to, _, _) => {
+                processes = processes.max(from + 1).max(to + 1);
+            }
+            EventKind::MessageDelivered(from, to, msg_id, _)
+            | EventKind::MessageDropped(from, to, msg_id) => {
+                processes = processes.max(from + 1).max(to + 1);
+                delivered.insert(*msg_id);
+            }
+            EventKind::AckDelivered(from, to, msg_id) | EventKind::AckDropped(from, to, msg_id) => {
+                processes = processes.max(from + 1).max(to + 1);
+                acked.insert(*msg_id);
+            }
+        }
+    }
+
+    let mut messages = HashMap::new();
+
+    let mut steps = Vec::new();
+    let mut lost = Vec::new();
+    for event in trace {
+        let step = match &event.kind {
+            EventKind::ProcessCrashed(proc) => Step::Note {
+                proc: *proc,
+                label: "crashed".to_string(),
+            },
+            EventKind::ClockJumped(proc, jump_id) => Step::Note {
+                proc: *proc,
+                label: format!("clock jump j{jump_id}"),
+            },
+            EventKind::MessageRetried(from, _, msg_id, attempt) => Step::Note {
+                proc: *from,
+                label: format!("retry m{msg_id} #{attempt}"),
+            },
+            EventKind::UserLocalMessage(proc, msg) if options.local_messages => Step::Note {
+                proc: *proc,
+                label: format!("user: {msg}"),
+            },
+            EventKind::ProcLocalMessage(proc, msg) if options.local_messages => Step::Note {
+                proc: *proc,
+                label: format!("local: {msg}"),
+            },
+            EventKind::TimerFired(proc, timer_id) if options.local_messages => Step::Note {
+                proc: *proc,
+                label: format!("timer t{timer_id}"),
+            },
+            EventKind::MessageDelivered(from, to, msg_id, msg) => Step::Arrow {
+                from: *from,
+                to: *to,
+                label: message_label(*msg_id, msg),
+                dashed: false,
+            },
+            EventKind::AckDelivered(from, to, msg_id) if options.acks => Step::Arrow {
+                from: *from,
+                to: *to,
+                label: ack_label(*msg_id),
+                dashed: true,
+            },
+            EventKind::MessageDropped(from, to, msg_id) => Step::Lost {
+                from: *from,
+                to: *to,
+                label: message_label(*msg_id, messages.get(msg_id).copied().unwrap_or("")),
+                reason: "dropped",
+            },
+            EventKind::AckDropped(from, to, msg_id) if options.acks => Step::Lost {
+                from: *from,
+                to: *to,
+                label: ack_label(*msg_id),
+                reason: "dropped",
+            },
+            EventKind::MessageSent(from, to, msg_id, msg) => {
+                messages.insert(*msg_id, msg.as_str());
+                if !delivered.contains(msg_id) {
+                    lost.push((
+                        event.time,
+                        Step::Lost {
+                            from: *from,
+                            to: *to,
+                            label: message_label(*msg_id, msg),
+                            reason: "in flight",
+                        },
+                    ));
+                }
+                continue;
+            }
+            EventKind::AckSent(from, to, msg_id) if options.acks && !acked.contains(msg_id) => {
+                lost.push((
+                    event.time,
+                    Step::Lost {
+                        from: *from,
+                        to: *to,
+                        label: ack_label(*msg_id),
+                        reason: "in flight",
+                    },
+                ));
+                continue;
+            }
+            _ => continue,
+        };
+        steps.push((event.time, step));
+    }
+    steps.extend(lost);
+
+    Diagram { processes, steps }
+}
+
+/// Mermaid treats `#` and `;` specially, so they are replaced with entity codes.
+fn mermaid_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '#' => out.push_str("#35;"),
+            ';' => out.push_str("#59;"),
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Renders trace as a Mermaid sequence diagram.
+///
+/// Every process is drawn as a lifeline
+/// and every delivered message as an arrow at the moment of delivery.
+/// Dropped messages are drawn with crossed arrows at the moment of drop,
+/// and messages which are still in flight are drawn the same way at the end.
+pub fn render_mermaid(trace: &[Event], options: &DiagramOptions) -> String {
+    let diagram = layout(trace, options);
+    let mut out = String::from("sequenceDiagram\n");
+    for proc in 0..diagram.processes {
+        out += &format!("    participant P{proc}\n");
+    }
+    for (_, step) in diagram.steps {
+        let line = match step {
+            Step::Arrow {
+                from,
+                to,
+                label,
+                dashed,
+            } => {
+                let arrow = if dashed { "-->>" } else { "->>" };
+                format!("P{from}{arrow}P{to}: {}", mermaid_text(&label))
+            }
+            Step::Lost {
+                from,
+                to,
+                label,
+                reason,
+            } => format!("P{from}-xP{to}: {} ({reason})", mermaid_text(&label)),
+            Step::Note { proc, label } => {
+                format!("Note over P{proc}: {}", mermaid_text(&label))
+            }
+        };
+        out += &format!("    {line}\n");
+    }
+    out
+}
+
+/// Renders trace as a PlantUML sequence diagram.
+/// Layout is the same as in [`render_mermaid`].
+pub fn render_plantuml(trace: &[Event], options: &DiagramOptions) -> String {
+    let diagram = layout(trace, options);
+    let mut out = String::from("@startuml\n");
+    for proc in 0..diagram.processes {
+        out += &format!("participant P{proc}\n");
+    }
+    for (_, step) in diagram.steps {
+        let line = match step {
+            Step::Arrow {
+                from,
+                to,
+                label,
+                dashed,
+            } => {
+                let arrow = if dashed { "-->" } else { "->" };
+                format!(
+                    "P{from} {arrow} P{to} : {}",
+                    label.replace(['\n', '\r'], " ")
+                )
+            }
+            Step::Lost {
+                from,
+                to,
+                label,
+                reason,
+            } => format!(
+                "P{from} ->x P{to} : {} ({reason})",
+                label.replace(['\n', '\r'], " ")
+            ),
+            Step::Note { proc, label } => {
+                format!("note over P{proc} : {}", label.replace(['\n', '\r'], " "))
+            }
+        };
+        out += &line;
+        out.push('\n');
+    }
+    out += "@enduml\n";
+    out
+}
+
+/// Renders trace as a space-time diagram for the terminal.
+///
+/// Every row is one step with its time on the left and label on the right.
+/// Sender is marked with `o`, receiver with `+`,
+/// receiver of the dropped or still in flight message with `x`
+/// and process which got local message or crashed with `*`.
+pub fn render_ascii(trace: &[Event], options: &DiagramOptions) -> String {
+    let diagram = layout(trace, options);
+    let width = format!("P{}", diagram.processes).len() + 2;
+    let columns = diagram.processes.saturating_sub(1) * width + 1;
+
+    let mut out = String::from("  time ");
+    for proc in 0..diagram.processes {
+        out += &format!("{:<width$}", format!("P{proc}"));
+    }
+    out = out.trim_end().to_string();
+    out.push('\n');
+
+    for (time, step) in diagram.steps {
+        let mut row: Vec<char> = (0..columns)
+            .map(|i| if i % width == 0 { '|' } else { ' ' })
+            .collect();
+        let (line, label) = match step {
+            Step::Arrow {
+                from, to, label, ..
+            } => ((from, to, '+'), label),
+            Step::Lost {
+                from,
+                to,
+                label,
+                reason,
+            } => ((from, to, 'x'), format!("{label} ({reason})")),
+            Step::Note { proc, label } => {
+                row[proc * width] = '*';
+                ((proc, proc, '*'), label)
+            }
+        };
+        let (from, to, mark) = line;
+        let (a, b) = (from * width, to * width);
+        if a != b {
+            let (lo, hi) = (a.min(b), a.max(b));
+            for cell in row.iter_mut().take(hi).skip(lo + 1) {
+                *cell = '-';
+            }
+            if a < b {
+                row[b - 1] = '>';
+            } else {
+                row[b + 1] = '<';
+            }
+            row[a] = 'o';
+            row[b] = mark;
+        } else if mark != '*' {
+            row[a] = '@';
+        }
+        let row: String = row.into_iter().collect();
+        let line = format!("{time:>6} {row}  {}", label.replace(['\n', '\r'], " "));
+        out += line.trim_end();
+        out.push('\n');
+    }
+    out
+}
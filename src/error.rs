@@ -0,0 +1,42 @@
+use std::fmt::{self, Display};
+
+use crate::process::ProcessId;
+
+/// Misuse of the API, reported by the `try_*` functions instead of panic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    NoSuchProcess(ProcessId),
+    /// There is no pending event with the index.
+    NoSuchEvent(usize),
+    /// Local message can not be sent to the crashed process, and its clock can not jump.
+    ProcessCrashed(ProcessId),
+    /// There is no link or route between processes in the [`crate::Topology`].
+    NoLink(ProcessId, ProcessId),
+    /// Pending event can not be dropped, because the link is [`crate::Delivery::FifoReliable`].
+    ReliableLink(ProcessId, ProcessId),
+    /// Pending event with the index is not a message or ack.
+    NotMessage(usize),
+    /// Function of the process was called outside of the process,
+    /// e.g. not during the step of the system.
+    NoProcessContext,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoSuchProcess(proc) => write!(f, "no process with id {proc}"),
+            Error::NoSuchEvent(event) => write!(f, "no pending event with index {event}"),
+            Error::ProcessCrashed(proc) => write!(f, "process {proc} is crashed"),
+            Error::NoLink(from, to) => write!(f, "no link from process {from} to process {to}"),
+            Error::NotMessage(event) => write!(f, "pending event {event} is not a message or ack"),
+            Error::ReliableLink(from, to) => {
+                write!(f, "link from process {from} to process {to} is reliable")
+            }
+            Error::NoProcessContext => {
+                write!(f, "no system available, called outside of the process")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
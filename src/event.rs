@@ -1,4 +1,8 @@
-use crate::ProcessId;
+use crate::{
+    clock::VectorClock,
+    time::{JumpId, TimerId},
+    ProcessId,
+};
 
 pub type MessageId = usize;
 
@@ -10,10 +14,48 @@ pub enum EventKind {
     MessageDelivered(ProcessId, ProcessId, MessageId, String),
     AckSent(ProcessId, ProcessId, MessageId),
     AckDelivered(ProcessId, ProcessId, MessageId),
+    MessageDropped(ProcessId, ProcessId, MessageId),
+    AckDropped(ProcessId, ProcessId, MessageId),
+    ProcessCrashed(ProcessId),
+    /// Process started timer with [`crate::sleep`].
+    TimerSet(ProcessId, TimerId),
+    TimerFired(ProcessId, TimerId),
+    /// Local clock of the process was moved by the jump scheduled with [`crate::System::jump_clock`].
+    ClockJumped(ProcessId, JumpId),
+    /// Process sent the message with [`crate::reliable::send`] again,
+    /// with id of the first attempt and number of the attempt.
+    MessageRetried(ProcessId, ProcessId, MessageId, usize),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Event {
     pub time: f64,
     pub kind: EventKind,
+    /// Vector clock of the process on which the event happens.
+    /// Acks are treated as messages, so [`EventKind::AckDelivered`] carries
+    /// knowledge of the receiver back to the sender.
+    pub clock: VectorClock,
+}
+
+impl EventKind {
+    /// Returns process on which the event happens:
+    /// sender for sent messages and acks,
+    /// receiver for delivered and dropped ones.
+    pub fn process(&self) -> ProcessId {
+        match self {
+            EventKind::ProcLocalMessage(proc, _)
+            | EventKind::UserLocalMessage(proc, _)
+            | EventKind::ProcessCrashed(proc)
+            | EventKind::TimerSet(proc, _)
+            | EventKind::TimerFired(proc, _)
+            | EventKind::ClockJumped(proc, _) => *proc,
+            EventKind::MessageSent(from, _, _, _)
+            | EventKind::AckSent(from, _, _)
+            | EventKind::MessageRetried(from, _, _, _) => *from,
+            EventKind::MessageDelivered(_, to, _, _)
+            | EventKind::AckDelivered(_, to, _)
+            | EventKind::MessageDropped(_, to, _)
+            | EventKind::AckDropped(_, to, _) => *to,
+        }
+    }
 }
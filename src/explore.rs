@@ -0,0 +1,405 @@
+use std::{
+    collections::{
+        hash_map::{DefaultHasher, Entry},
+        HashMap, VecDeque,
+    },
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    event::{EventKind, MessageId},
+    process::ProcessId,
+    schedule::{Schedule, ScheduleStep},
+    system::System,
+    time::TimerId,
+    workload::{Driver, Workload},
+};
+
+const VISITED_SHARDS: usize = 64;
+
+/// Run explored by the [`Explorer`]: the system
+/// and everything else which is needed to check it.
+///
+/// Only the system is fingerprinted, so the rest of the model must be
+/// determined by the trace of the system, e.g. updated only in [`Model::visit`]
+/// from the events, otherwise runs with different models are explored once.
+pub trait Model {
+    fn system(&mut self) -> &mut System;
+
+    /// Called when the run is created and after every applied event.
+    ///
+    /// Returns error if the run is incorrect
+    /// and `true` if the run is finished, even if there are pending events.
+    /// Run without pending events is always finished.
+    fn visit(&mut self) -> Result<bool, String> {
+        Ok(false)
+    }
+}
+
+impl Model for System {
+    fn system(&mut self) -> &mut System {
+        self
+    }
+}
+
+/// System with the workload, which sends requests when the system has no pending events.
+impl<W: Workload> Model for (System, Driver<W>) {
+    fn system(&mut self) -> &mut System {
+        &mut self.0
+    }
+
+    fn visit(&mut self) -> Result<bool, String> {
+        let (system, driver) = self;
+        loop {
+            if driver.step(system)? {
+                return Ok(true);
+            }
+            if system.get_pending_events_count() > 0 {
+                return Ok(false);
+            }
+        }
+    }
+}
+
+/// Incorrect run found by the [`Explorer`].
+#[derive(Debug, Clone)]
+pub struct Violation {
+    /// Indices of pending events applied after the run was created.
+    pub path: Vec<usize>,
+    pub error: String,
+    /// Schedule of the run, which can be replayed with [`crate::replay`].
+    pub schedule: Schedule,
+}
+
+/// Result of the exploration.
+#[derive(Debug, Clone)]
+pub struct ExploreReport {
+    /// Number of distinct explored states.
+    pub states: usize,
+    /// First found incorrect run, `None` if all explored runs are correct.
+    pub violation: Option<Violation>,
+    pub elapsed: Duration,
+}
+
+/// Explores all orders of pending events on several threads.
+///
+/// Every worker thread creates its own runs with the factory
+/// and restores states by applying pending events from the start.
+/// Frontier is sharded between workers, and idle workers steal from others.
+/// States with equal fingerprints are explored once,
+/// or again if reached by a shorter path when depth is limited.
+/// Fingerprint consists of events observed by every process,
+/// pending events and drawn random values,
+/// and in the timing mode of virtual time, due times and local clocks,
+/// so processes must be deterministic.
+pub struct Explorer<F> {
+    factory: F,
+    threads: usize,
+    max_depth: Option<usize>,
+}
+
+struct Shared {
+    frontiers: Vec<Mutex<VecDeque<Vec<usize>>>>,
+    /// Smallest depth at which every fingerprint was visited.
+    visited: Vec<Mutex<HashMap<u64, usize>>>,
+    /// Paths in frontiers or in processing.
+    unfinished: AtomicUsize,
+    states: AtomicUsize,
+    stop: AtomicBool,
+    violation: Mutex<Option<Violation>>,
+    /// Idle workers wait on `wake` until paths are added or the exploration ends.
+    idle: Mutex<()>,
+    wake: Condvar,
+}
+
+enum Visit {
+    New,
+    /// Seen before, but at a greater depth, so its subtree was cut by the limit.
+    Shallower,
+    Seen,
+}
+
+impl Shared {
+    fn take(&self, worker: usize) -> Option<Vec<usize>> {
+        if let Some(path) = self.frontiers[worker].lock().unwrap().pop_back() {
+            return Some(path);
+        }
+        (1..self.frontiers.len())
+            .map(|i| (worker + i) % self.frontiers.len())
+            .find_map(|other| self.frontiers[other].lock().unwrap().pop_front())
+    }
+
+    fn has_paths(&self) -> bool {
+        self.frontiers
+            .iter()
+            .any(|frontier| !frontier.lock().unwrap().is_empty())
+    }
+
+    /// Wakes idle workers. Taking the lock ensures that a worker
+    /// which found no paths is already waiting and does not miss the wake.
+    fn wake_all(&self) {
+        let _idle = self.idle.lock().unwrap();
+        self.wake.notify_all();
+    }
+
+    fn visit(&self, fingerprint: u64, depth: usize) -> Visit {
+        let shard = fingerprint as usize % VISITED_SHARDS;
+        match self.visited[shard].lock().unwrap().entry(fingerprint) {
+            Entry::Vacant(entry) => {
+                entry.insert(depth);
+                Visit::New
+            }
+            Entry::Occupied(mut entry) if depth < *entry.get() => {
+                entry.insert(depth);
+                Visit::Shallower
+            }
+            Entry::Occupied(_) => Visit::Seen,
+        }
+    }
+
+    fn report(&self, violation: Violation) {
+        let mut found = self.violation.lock().unwrap();
+        if found.is_none() {
+            *found = Some(violation);
+        }
+        self.stop.store(true, Ordering::SeqCst);
+        drop(found);
+        self.wake_all();
+    }
+}
+
+impl<F, M> Explorer<F>
+where
+    F: Fn() -> M + Sync,
+    M: Model,
+{
+    /// Creates explorer of runs made by the factory,
+    /// which uses all available cores.
+    pub fn new(factory: F) -> Self {
+        Self {
+            factory,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            max_depth: None,
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0, "explorer needs at least one thread");
+        self.threads = threads;
+        self
+    }
+
+    /// Limits number of events applied in every run.
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Explores runs until all of them are explored or incorrect run is found.
+    pub fn explore(&self) -> ExploreReport {
+        let start = Instant::now();
+        let shared = Shared {
+            frontiers: (0..self.threads)
+                .map(|_| Mutex::new(VecDeque::new()))
+                .collect(),
+            visited: (0..VISITED_SHARDS)
+                .map(|_| Mutex::new(HashMap::new()))
+                .collect(),
+            unfinished: AtomicUsize::new(1),
+            states: AtomicUsize::new(0),
+            stop: AtomicBool::new(false),
+            violation: Mutex::new(None),
+            idle: Mutex::new(()),
+            wake: Condvar::new(),
+        };
+        shared.frontiers[0].lock().unwrap().push_back(Vec::new());
+
+        thread::scope(|scope| {
+            for worker in 0..self.threads {
+                let shared = &shared;
+                scope.spawn(move || self.work(worker, shared));
+            }
+        });
+
+        ExploreReport {
+            states: shared.states.into_inner(),
+            violation: shared.violation.into_inner().unwrap(),
+            elapsed: start.elapsed(),
+        }
+    }
+
+    fn work(&self, worker: usize, shared: &Shared) {
+        while !shared.stop.load(Ordering::SeqCst) {
+            if let Some(path) = shared.take(worker) {
+                self.explore_path(worker, path, shared);
+                if shared.unfinished.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    shared.wake_all();
+                }
+                continue;
+            }
+            let idle = shared.idle.lock().unwrap();
+            if shared.stop.load(Ordering::SeqCst) || shared.unfinished.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            if !shared.has_paths() {
+                drop(shared.wake.wait(idle).unwrap());
+            }
+        }
+    }
+
+    /// Restores the state of the path and goes deep from it
+    /// through the first pending events, leaving other events to the frontier.
+    fn explore_path(&self, worker: usize, mut path: Vec<usize>, shared: &Shared) {
+        let mut model = (self.factory)();
+        let mut visited = model.visit();
+        for event in path.iter() {
+            model.system().apply_pending_event(*event);
+            visited = model.visit();
+        }
+
+        loop {
+            if shared.stop.load(Ordering::SeqCst) {
+                return;
+            }
+            let finished = match visited {
+                Ok(finished) => finished,
+                Err(error) => {
+                    let schedule = model.system().get_schedule();
+                    shared.report(Violation {
+                        path,
+                        error,
+                        schedule,
+                    });
+                    return;
+                }
+            };
+            // Without the limit every state is expanded fully at any depth.
+            let depth = self.max_depth.map_or(0, |_| path.len());
+            match shared.visit(fingerprint(model.system()), depth) {
+                Visit::New => {
+                    shared.states.fetch_add(1, Ordering::SeqCst);
+                }
+                Visit::Shallower => {}
+                Visit::Seen => return,
+            }
+
+            let pending = model.system().get_pending_events_count();
+            if finished || pending == 0 || self.max_depth == Some(path.len()) {
+                return;
+            }
+            shared.unfinished.fetch_add(pending - 1, Ordering::SeqCst);
+            shared.frontiers[worker]
+                .lock()
+                .unwrap()
+                .extend((1..pending).rev().map(|event| {
+                    let mut next = path.clone();
+                    next.push(event);
+                    next
+                }));
+            if pending > 1 {
+                shared.wake_all();
+            }
+
+            model.system().apply_pending_event(0);
+            path.push(0);
+            visited = model.visit();
+        }
+    }
+}
+
+/// Hashes state of the system, replacing message and timer ids,
+/// which depend on the order of events, with numbers of sends and timers of the process.
+fn fingerprint(system: &System) -> u64 {
+    let mut sends: HashMap<ProcessId, u64> = HashMap::new();
+    let mut ids: HashMap<MessageId, (ProcessId, u64)> = HashMap::new();
+    let mut timers: HashMap<TimerId, u64> = HashMap::new();
+    let mut set_timers: HashMap<ProcessId, u64> = HashMap::new();
+    let mut processes: Vec<DefaultHasher> = Vec::new();
+    for event in system.get_trace() {
+        let proc = event.kind.process();
+        if let EventKind::MessageSent(from, _, msg_id, _) = &event.kind {
+            let count = sends.entry(*from).or_default();
+            ids.insert(*msg_id, (*from, *count));
+            *count += 1;
+        }
+        if let EventKind::TimerSet(proc, timer_id) = &event.kind {
+            let count = set_timers.entry(*proc).or_default();
+            timers.insert(*timer_id, *count);
+            *count += 1;
+        }
+        if processes.len() <= proc {
+            processes.resize_with(proc + 1, DefaultHasher::new);
+        }
+        hash_kind(&event.kind, &ids, &timers, &mut processes[proc]);
+    }
+
+    let timing = system.get_timing();
+    let mut pending: Vec<u64> = system
+        .get_pending_events()
+        .iter()
+        .enumerate()
+        .map(|(event, kind)| {
+            let mut hasher = DefaultHasher::new();
+            hash_kind(kind, &ids, &timers, &mut hasher);
+            if let Some((due, _)) = &timing {
+                due[event].to_bits().hash(&mut hasher);
+            }
+            hasher.finish()
+        })
+        .collect();
+    pending.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    if let Some((_, clocks)) = &timing {
+        system.now().to_bits().hash(&mut hasher);
+        for clock in clocks {
+            (clock.offset().to_bits(), clock.drift().to_bits()).hash(&mut hasher);
+        }
+    }
+    for process in processes {
+        process.finish().hash(&mut hasher);
+    }
+    pending.hash(&mut hasher);
+    for step in system.get_schedule().steps {
+        if let ScheduleStep::Random(value) = step {
+            value.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+fn hash_kind(
+    kind: &EventKind,
+    ids: &HashMap<MessageId, (ProcessId, u64)>,
+    timers: &HashMap<TimerId, u64>,
+    hasher: &mut impl Hasher,
+) {
+    let id = |msg_id: &MessageId| ids.get(msg_id).copied();
+    match kind {
+        EventKind::ProcLocalMessage(proc, msg) => (0, proc, msg).hash(hasher),
+        EventKind::UserLocalMessage(proc, msg) => (1, proc, msg).hash(hasher),
+        EventKind::MessageSent(from, to, msg_id, msg) => {
+            (2, from, to, id(msg_id), msg).hash(hasher)
+        }
+        EventKind::MessageDelivered(from, to, msg_id, msg) => {
+            (3, from, to, id(msg_id), msg).hash(hasher)
+        }
+        EventKind::AckSent(from, to, msg_id) => (4, from, to, id(msg_id)).hash(hasher),
+        EventKind::AckDelivered(from, to, msg_id) => (5, from, to, id(msg_id)).hash(hasher),
+        EventKind::MessageDropped(from, to, msg_id) => (6, from, to, id(msg_id)).hash(hasher),
+        EventKind::AckDropped(from, to, msg_id) => (7, from, to, id(msg_id)).hash(hasher),
+        EventKind::ProcessCrashed(proc) => (8, proc).hash(hasher),
+        EventKind::TimerSet(proc, timer_id) => (9, proc, timers.get(timer_id)).hash(hasher),
+        EventKind::TimerFired(proc, timer_id) => (10, proc, timers.get(timer_id)).hash(hasher),
+        EventKind::ClockJumped(proc, jump_id) => (11, proc, jump_id).hash(hasher),
+        EventKind::MessageRetried(from, to, msg_id, attempt) => {
+            (12, from, to, id(msg_id), attempt).hash(hasher)
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{event::Event, process::ProcessId, report::Run};
+
+/// Suspicions and leaders reported by processes, in the order of the trace.
+struct History {
+    run: Run,
+    /// Processes suspected by every process at the end of the run.
+    suspected: BTreeMap<ProcessId, BTreeSet<ProcessId>>,
+    /// Last leader reported by every process.
+    leaders: BTreeMap<ProcessId, ProcessId>,
+    /// Suspicions of processes which were not crashed at the time.
+    premature: Vec<(ProcessId, ProcessId)>,
+}
+
+impl History {
+    fn new(trace: &[Event], processes: usize) -> Result<Self, String> {
+        let mut suspected: BTreeMap<ProcessId, BTreeSet<_>> = BTreeMap::new();
+        let mut leaders = BTreeMap::new();
+        let mut premature = Vec::new();
+        let run = Run::read(trace, processes, |run, proc, report, other| {
+            if !matches!(report, "suspect" | "restore" | "leader") {
+                return Ok(());
+            }
+            let other: ProcessId = other.parse().map_err(|_| {
+                format!("process {proc} reported invalid process in '{report} {other}'")
+            })?;
+            let suspected = suspected.entry(proc).or_default();
+            match report {
+                "suspect" => {
+                    suspected.insert(other);
+                    if !run.crashed.contains(&other) {
+                        premature.push((proc, other));
+                    }
+                }
+                "restore" => {
+                    suspected.remove(&other);
+                }
+                _ => {
+                    leaders.insert(proc, other);
+                }
+            }
+            Ok(())
+        })?;
+        Ok(History {
+            run,
+            suspected,
+            leaders,
+            premature,
+        })
+    }
+
+    fn suspected(&self, proc: ProcessId) -> BTreeSet<ProcessId> {
+        self.suspected.get(&proc).cloned().unwrap_or_default()
+    }
+}
+
+/// Checks that every crashed process is suspected by every correct process at the end of the run.
+pub fn check_strong_completeness(trace: &[Event], processes: usize) -> Result<(), String> {
+    let history = History::new(trace, processes)?;
+    for proc in history.run.correct() {
+        let suspected = history.suspected(proc);
+        let mut crashed: Vec<_> = history.run.crashed.iter().copied().collect();
+        crashed.sort_unstable();
+        if let Some(missed) = crashed.into_iter().find(|other| !suspected.contains(other)) {
+            return Err(format!(
+                "crashed process {missed} is not suspected by correct process {proc}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that no process was suspected before it crashed.
+pub fn check_strong_accuracy(trace: &[Event], processes: usize) -> Result<(), String> {
+    let history = History::new(trace, processes)?;
+    match history.premature.first() {
+        Some((proc, other)) => Err(format!(
+            "process {proc} suspected process {other} before it crashed"
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Checks that no correct process is suspected by a correct process at the end of the run.
+pub fn check_eventual_strong_accuracy(trace: &[Event], processes: usize) -> Result<(), String> {
+    let history = History::new(trace, processes)?;
+    for proc in history.run.correct() {
+        let suspected = history.suspected(proc);
+        if let Some(other) = history
+            .run
+            .correct()
+            .find(|other| suspected.contains(other))
+        {
+            return Err(format!(
+                "correct process {other} is still suspected by correct process {proc}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every correct process trusts the same correct leader at the end of the run.
+pub fn check_eventual_leadership(trace: &[Event], processes: usize) -> Result<(), String> {
+    let history = History::new(trace, processes)?;
+    let mut leader = None;
+    for proc in history.run.correct() {
+        let Some(trusted) = history.leaders.get(&proc).copied() else {
+            return Err(format!("correct process {proc} reported no leader"));
+        };
+        if history.run.crashed.contains(&trusted) {
+            return Err(format!(
+                "correct process {proc} trusts crashed process {trusted}"
+            ));
+        }
+        match leader {
+            Some(leader) if leader != trusted => {
+                return Err(format!(
+                    "correct processes trust different leaders {leader} and {trusted}"
+                ))
+            }
+            _ => leader = Some(trusted),
+        }
+    }
+    Ok(())
+}
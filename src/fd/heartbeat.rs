@@ -0,0 +1,129 @@
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+
+use crate::{process::ProcessId, send::send_unacked, spawn::spawn, time::sleep};
+
+use super::{notify, Changed, FailureDetector, Watchers};
+
+/// Message sent by [`EventuallyPerfect`] detectors to each other.
+pub const HEARTBEAT: &str = "fd heartbeat";
+
+struct HeartbeatState {
+    peers: Vec<ProcessId>,
+    period: f64,
+    timeout: f64,
+    /// Peers which sent heartbeat since the last check.
+    heard: BTreeSet<ProcessId>,
+    suspected: BTreeSet<ProcessId>,
+    watchers: Watchers,
+}
+
+impl HeartbeatState {
+    /// Suspects peers which were not heard of since the last check.
+    fn check(&mut self) -> bool {
+        let mut changed = false;
+        for proc in self.peers.iter() {
+            if !self.heard.contains(proc) {
+                changed |= self.suspected.insert(*proc);
+            }
+        }
+        self.heard.clear();
+        changed
+    }
+}
+
+/// Detector which sends heartbeats to peers and suspects peers
+/// whose heartbeats did not arrive within the timeout.
+///
+/// When heartbeat of the suspected peer arrives, the suspicion was false,
+/// so the peer is restored and the timeout grows by the period.
+/// Once the timeout exceeds delays of heartbeats, correct peers are never suspected again.
+///
+/// Timeouts are measured with [`crate::sleep`], so in the [`crate::System`]
+/// the detector is meaningful in the timing mode.
+/// Detector sends heartbeats forever, so the run must be stopped by time.
+#[derive(Clone)]
+pub struct EventuallyPerfect(Rc<RefCell<HeartbeatState>>);
+
+impl EventuallyPerfect {
+    /// Starts sending heartbeats to peers every period and checking them every timeout.
+    /// Must be called by the process, e.g. on its first local message.
+    ///
+    /// Heartbeats stop when the detector and all its clones are dropped.
+    pub fn start(peers: impl IntoIterator<Item = ProcessId>, period: f64, timeout: f64) -> Self {
+        let detector = Self(Rc::new(RefCell::new(HeartbeatState {
+            peers: peers.into_iter().collect(),
+            period,
+            timeout,
+            heard: BTreeSet::new(),
+            suspected: BTreeSet::new(),
+            watchers: Watchers::default(),
+        })));
+
+        let state = Rc::downgrade(&detector.0);
+        spawn(async move {
+            while let Some((peers, period)) = state.upgrade().map(|state| {
+                let state = state.borrow();
+                (state.peers.clone(), state.period)
+            }) {
+                for to in peers {
+                    send_unacked(to, HEARTBEAT.to_string());
+                }
+                sleep(period).await;
+            }
+        });
+
+        let state = Rc::downgrade(&detector.0);
+        spawn(async move {
+            while let Some(timeout) = state.upgrade().map(|state| state.borrow().timeout) {
+                sleep(timeout).await;
+                let Some(state) = state.upgrade() else {
+                    return;
+                };
+                let flags = {
+                    let mut state = state.borrow_mut();
+                    match state.check() {
+                        true => state.watchers.take(),
+                        false => Vec::new(),
+                    }
+                };
+                notify(flags);
+            }
+        });
+
+        detector
+    }
+
+    /// Handles message received by the process, returning `true` if it is a heartbeat.
+    pub fn on_message(&self, from: ProcessId, msg: &str) -> bool {
+        if msg != HEARTBEAT {
+            return false;
+        }
+        let flags = {
+            let mut state = self.0.borrow_mut();
+            state.heard.insert(from);
+            if state.suspected.remove(&from) {
+                state.timeout += state.period;
+                state.watchers.take()
+            } else {
+                Vec::new()
+            }
+        };
+        notify(flags);
+        true
+    }
+
+    /// Returns current timeout, which grows with every false suspicion.
+    pub fn timeout(&self) -> f64 {
+        self.0.borrow().timeout
+    }
+}
+
+impl FailureDetector for EventuallyPerfect {
+    fn suspected(&self) -> BTreeSet<ProcessId> {
+        self.0.borrow().suspected.clone()
+    }
+
+    fn changed(&self) -> Changed {
+        self.0.borrow_mut().watchers.watch()
+    }
+}
@@ -0,0 +1,125 @@
+//! Failure detectors, which tell the process which other processes are suspected to have crashed.
+//!
+//! [`Perfect`] is driven by the crashes of the [`crate::System`],
+//! [`EventuallyPerfect`] by heartbeats and timers,
+//! and [`Omega`] elects the leader with any of them.
+//!
+//! [`report_suspicions`] emits `suspect <proc>` and `restore <proc>` local messages,
+//! and [`report_leader`] emits `leader <proc>`.
+//! [`check_strong_accuracy`] looks at every suspicion, while the eventual properties
+//! only look at the last reports, so the run must last until the detector stabilizes.
+//! Checkers are given the number of processes, so a process which never reported
+//! anything fails them instead of being skipped.
+
+mod check;
+mod heartbeat;
+mod omega;
+mod perfect;
+
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll},
+};
+
+use crate::{process::ProcessId, send::send_local, shared::SharedState, spawn::spawn};
+
+pub use check::{
+    check_eventual_leadership, check_eventual_strong_accuracy, check_strong_accuracy,
+    check_strong_completeness,
+};
+pub use heartbeat::{EventuallyPerfect, HEARTBEAT};
+pub use omega::Omega;
+pub use perfect::Perfect;
+
+/// Failure detector, which is queried by the process.
+pub trait FailureDetector {
+    /// Returns processes which are currently suspected.
+    fn suspected(&self) -> BTreeSet<ProcessId>;
+
+    /// Returns future which is resolved when suspected processes change.
+    fn changed(&self) -> Changed;
+}
+
+/// Future returned by [`FailureDetector::changed`].
+pub struct Changed {
+    flag: Rc<RefCell<SharedState<()>>>,
+}
+
+impl Future for Changed {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.flag.borrow_mut().take(cx.waker().clone()).is_some() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Flags of [`Changed`] futures, which are put on the next change.
+#[derive(Default)]
+struct Watchers(Vec<Weak<RefCell<SharedState<()>>>>);
+
+impl Watchers {
+    fn watch(&mut self) -> Changed {
+        let flag = Rc::new(RefCell::new(SharedState::default()));
+        self.0.push(Rc::downgrade(&flag));
+        Changed { flag }
+    }
+
+    /// Takes flags of alive futures, which must be notified
+    /// after the state of the detector is released, as notifying wakes tasks.
+    fn take(&mut self) -> Vec<Rc<RefCell<SharedState<()>>>> {
+        self.0.drain(..).filter_map(|flag| flag.upgrade()).collect()
+    }
+}
+
+fn notify(flags: Vec<Rc<RefCell<SharedState<()>>>>) {
+    for flag in flags {
+        flag.borrow_mut().put(());
+    }
+}
+
+/// Spawns task which reports every change of suspected processes
+/// as local messages `suspect {proc}` and `restore {proc}`, which are read by the checkers.
+pub fn report_suspicions<D: FailureDetector + 'static>(detector: D) {
+    spawn(async move {
+        let mut reported = BTreeSet::new();
+        loop {
+            let changed = detector.changed();
+            let suspected = detector.suspected();
+            for proc in suspected.difference(&reported) {
+                send_local(format!("suspect {proc}"));
+            }
+            for proc in reported.difference(&suspected) {
+                send_local(format!("restore {proc}"));
+            }
+            reported = suspected;
+            changed.await;
+        }
+    });
+}
+
+/// Spawns task which reports the leader as local message `leader {proc}`
+/// when the process starts to trust it, which is read by the checkers.
+pub fn report_leader<D: FailureDetector + 'static>(omega: Omega<D>) {
+    spawn(async move {
+        let mut reported = None;
+        loop {
+            let changed = omega.changed();
+            let leader = omega.leader();
+            if leader != reported {
+                if let Some(leader) = leader {
+                    send_local(format!("leader {leader}"));
+                }
+                reported = leader;
+            }
+            changed.await;
+        }
+    });
+}
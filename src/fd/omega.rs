@@ -0,0 +1,45 @@
+use std::collections::BTreeSet;
+
+use crate::process::ProcessId;
+
+use super::{Changed, FailureDetector};
+
+/// Leader oracle: the leader is the first of the processes which is not suspected.
+///
+/// With [`super::EventuallyPerfect`] all correct processes eventually trust
+/// the same correct leader.
+#[derive(Debug, Clone)]
+pub struct Omega<D> {
+    processes: Vec<ProcessId>,
+    detector: D,
+}
+
+impl<D: FailureDetector> Omega<D> {
+    /// Creates oracle which elects one of the processes, in the order of preference.
+    pub fn new(processes: impl IntoIterator<Item = ProcessId>, detector: D) -> Self {
+        Self {
+            processes: processes.into_iter().collect(),
+            detector,
+        }
+    }
+
+    /// Returns current leader, `None` if all processes are suspected.
+    pub fn leader(&self) -> Option<ProcessId> {
+        let suspected = self.detector.suspected();
+        self.processes
+            .iter()
+            .copied()
+            .find(|proc| !suspected.contains(proc))
+    }
+}
+
+/// Leader can change only when suspected processes change.
+impl<D: FailureDetector> FailureDetector for Omega<D> {
+    fn suspected(&self) -> BTreeSet<ProcessId> {
+        self.detector.suspected()
+    }
+
+    fn changed(&self) -> Changed {
+        self.detector.changed()
+    }
+}
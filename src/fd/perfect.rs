@@ -0,0 +1,39 @@
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+
+use crate::{process::ProcessId, runtime::NodeHandle, shared::SharedState, system::SystemHandle};
+
+use super::{Changed, FailureDetector};
+
+/// Detector which suspects exactly the crashed processes of the [`crate::System`],
+/// right when they crash.
+///
+/// In the [`crate::runtime`] crashes are not known, so it suspects nobody.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Perfect;
+
+impl Perfect {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl FailureDetector for Perfect {
+    fn suspected(&self) -> BTreeSet<ProcessId> {
+        match NodeHandle::current() {
+            Some(_) => BTreeSet::new(),
+            None => SystemHandle::current()
+                .unwrap_or_else(|err| panic!("{err}"))
+                .crashed(),
+        }
+    }
+
+    fn changed(&self) -> Changed {
+        let flag = match NodeHandle::current() {
+            Some(_) => Rc::new(RefCell::new(SharedState::default())),
+            None => SystemHandle::current()
+                .unwrap_or_else(|err| panic!("{err}"))
+                .watch_crashes(),
+        };
+        Changed { flag }
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, Write},
+    ops::RangeInclusive,
+};
+
+use crate::{
+    clock::Clocks,
+    event::{Event, EventKind},
+    json::Value,
+};
+
+/// Name written into the header line of every trace file.
+const TRACE_FORMAT: &str = "flurry-trace";
+
+/// Version of the trace format produced by [`write_trace`].
+///
+/// Version 2 added events of timers, clocks, drops, crashes and retries.
+/// Traces of older versions are still read, since events were only added.
+pub const TRACE_FORMAT_VERSION: u64 = 2;
+
+/// Error returned by [`read_trace`] and [`crate::Schedule::read`].
+#[derive(Debug)]
+pub enum TraceReadError {
+    Io(io::Error),
+    /// Header line is missing or describes unknown format.
+    Header(String),
+    /// Version of the trace is not supported by this reader.
+    Version(u64),
+    /// Line (1-based) is not a valid event.
+    Parse {
+        line: usize,
+        reason: String,
+    },
+}
+
+impl Display for TraceReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceReadError::Io(err) => write!(f, "failed to read trace: {err}"),
+            TraceReadError::Header(reason) => write!(f, "invalid trace header: {reason}"),
+            TraceReadError::Version(version) => {
+                write!(f, "unsupported trace format version: {version}")
+            }
+            TraceReadError::Parse { line, reason } => {
+                write!(f, "invalid event at line {line}: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TraceReadError {}
+
+impl From<io::Error> for TraceReadError {
+    fn from(err: io::Error) -> Self {
+        TraceReadError::Io(err)
+    }
+}
+
+pub(crate) fn kind_to_json(kind: &EventKind) -> Vec<(&'static str, Value)> {
+    match kind {
+        EventKind::ProcLocalMessage(proc, msg) => vec![
+            ("kind", "ProcLocalMessage".into()),
+            ("proc", (*proc).into()),
+            ("msg", msg.as_str().into()),
+        ],
+        EventKind::UserLocalMessage(proc, msg) => vec![
+            ("kind", "UserLocalMessage".into()),
+            ("proc", (*proc).into()),
+            ("msg", msg.as_str().into()),
+        ],
+        EventKind::MessageSent(from, to, msg_id, msg) => vec![
+            ("kind", "MessageSent".into()),
+            ("from", (*from).into()),
+            ("to", (*to).into()),
+            ("msg_id", (*msg_id).into()),
+            ("msg", msg.as_str().into()),
+        ],
+        EventKind::MessageDelivered(from, to, msg_id, msg) => vec![
+            ("kind", "MessageDelivered".into()),
+            ("from", (*from).into()),
+            ("to", (*to).into()),
+            ("msg_id", (*msg_id).into()),
+            ("msg", msg.as_str().into()),
+        ],
+        EventKind::AckSent(from, to, msg_id) => vec![
+            ("kind", "AckSent".into()),
+            ("from", (*from).into()),
+            ("to", (*to).into()),
+            ("msg_id", (*msg_id).into()),
+        ],
+        EventKind::AckDelivered(from, to, msg_id) => vec![
+            ("kind", "AckDelivered".into()),
+            ("from", (*from).into()),
+            ("to", (*to).into()),
+            ("msg_id", (*msg_id).into()),
+        ],
+        EventKind::MessageDropped(from, to, msg_id) => vec![
+            ("kind", "MessageDropped".into()),
+            ("from", (*from).into()),
+            ("to", (*to).into()),
+            ("msg_id", (*msg_id).into()),
+        ],
+        EventKind::AckDropped(from, to, msg_id) => vec![
+            ("kind", "AckDropped".into()),
+            ("from", (*from).into()),
+            ("to", (*to).into()),
+            ("msg_id", (*msg_id).into()),
+        ],
+        EventKind::ProcessCrashed(proc) => {
+            vec![("kind", "ProcessCrashed".into()), ("proc", (*proc).into())]
+        }
+        EventKind::TimerSet(proc, timer_id) => vec![
+            ("kind", "TimerSet".into()),
+            ("proc", (*proc).into()),
+            ("timer_id", (*timer_id).into()),
+        ],
+        EventKind::TimerFired(proc, timer_id) => vec![
+            ("kind", "TimerFired".into()),
+            ("proc", (*proc).into()),
+            ("timer_id", (*timer_id).into()),
+        ],
+        EventKind::ClockJumped(proc, jump_id) => vec![
+            ("kind", "ClockJumped".into()),
+            ("proc", (*proc).into()),
+            ("jump_id", (*jump_id).into()),
+        ],
+        EventKind::MessageRetried(from, to, msg_id, attempt) => vec![
+            ("kind", "MessageRetried".into()),
+            ("from", (*from).into()),
+            ("to", (*to).into()),
+            ("msg_id", (*msg_id).into()),
+            ("attempt", (*attempt).into()),
+        ],
+    }
+}
+
+pub(crate) fn kind_from_json(value: &Value) -> Result<EventKind, String> {
+    let field = |key: &str| value.get(key).ok_or(format!("missing field '{key}'"));
+    let id = |key: &str| {
+        field(key)?
+            .as_u64()
+            .map(|id| id as usize)
+            .ok_or(format!("field '{key}' must be a non-negative integer"))
+    };
+    let text = |key: &str| {
+        field(key)?
+            .as_str()
+            .map(str::to_string)
+            .ok_or(format!("field '{key}' must be a string"))
+    };
+
+    let kind = field("kind")?
+        .as_str()
+        .ok_or("field 'kind' must be a string")?;
+    match kind {
+        "ProcLocalMessage" => Ok(EventKind::ProcLocalMessage(id("proc")?, text("msg")?)),
+        "UserLocalMessage" => Ok(EventKind::UserLocalMessage(id("proc")?, text("msg")?)),
+        "MessageSent" => Ok(EventKind::MessageSent(
+            id("from")?,
+            id("to")?,
+            id("msg_id")?,
+            text("msg")?,
+        )),
+        "MessageDelivered" => Ok(EventKind::MessageDelivered(
+            id("from")?,
+            id("to")?,
+            id("msg_id")?,
+            text("msg")?,
+        )),
+        "AckSent" => Ok(EventKind::AckSent(id("from")?, id("to")?, id("msg_id")?)),
+        "AckDelivered" => Ok(EventKind::AckDelivered(
+            id("from")?,
+            id("to")?,
+            id("msg_id")?,
+        )),
+        "MessageDropped" => Ok(EventKind::MessageDropped(
+            id("from")?,
+            id("to")?,
+            id("msg_id")?,
+        )),
+        "AckDropped" => Ok(EventKind::AckDropped(id("from")?, id("to")?, id("msg_id")?)),
+        "ProcessCrashed" => Ok(EventKind::ProcessCrashed(id("proc")?)),
+        "TimerSet" => Ok(EventKind::TimerSet(id("proc")?, id("timer_id")?)),
+        "TimerFired" => Ok(EventKind::TimerFired(id("proc")?, id("timer_id")?)),
+        "ClockJumped" => Ok(EventKind::ClockJumped(id("proc")?, id("jump_id")?)),
+        "MessageRetried" => Ok(EventKind::MessageRetried(
+            id("from")?,
+            id("to")?,
+            id("msg_id")?,
+            id("attempt")?,
+        )),
+        other => Err(format!("unknown event kind '{other}'")),
+    }
+}
+
+fn event_to_json(event: &Event) -> Value {
+    let mut fields = vec![("time", event.time.into())];
+    fields.extend(kind_to_json(&event.kind));
+    Value::object(fields)
+}
+
+fn event_from_json(value: &Value, clocks: &mut Clocks) -> Result<Event, String> {
+    let time = value
+        .get("time")
+        .ok_or("missing field 'time'")?
+        .as_f64()
+        .ok_or("field 'time' must be a number")?;
+    let kind = kind_from_json(value)?;
+    Ok(Event {
+        time,
+        clock: clocks.observe(&kind),
+        kind,
+    })
+}
+
+/// Writes header line with format name and version,
+/// and then every value on its own line.
+pub(crate) fn write_jsonl<W: Write>(
+    mut writer: W,
+    format: &str,
+    version: u64,
+    values: impl Iterator<Item = Value>,
+) -> io::Result<()> {
+    let header = Value::object(vec![("format", format.into()), ("version", version.into())]);
+    writeln!(writer, "{header}")?;
+    for value in values {
+        writeln!(writer, "{value}")?;
+    }
+    writer.flush()
+}
+
+/// Reads lines written by [`write_jsonl`] with one of the versions
+/// and converts them with `parse`.
+/// Empty lines are ignored.
+pub(crate) fn read_jsonl<R: BufRead, T>(
+    reader: R,
+    format: &str,
+    versions: RangeInclusive<u64>,
+    mut parse: impl FnMut(&Value) -> Result<T, String>,
+) -> Result<Vec<T>, TraceReadError> {
+    let mut lines = reader.lines().enumerate();
+
+    let header = loop {
+        match lines.next() {
+            None => return Err(TraceReadError::Header("file is empty".to_string())),
+            Some((_, line)) => {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    break Value::parse(&line).map_err(TraceReadError::Header)?;
+                }
+            }
+        }
+    };
+    if header.get("format").and_then(Value::as_str) != Some(format) {
+        return Err(TraceReadError::Header(format!(
+            "expected format '{format}'"
+        )));
+    }
+    let found = header
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or(TraceReadError::Header("missing version".to_string()))?;
+    if !versions.contains(&found) {
+        return Err(TraceReadError::Version(found));
+    }
+
+    let mut result = Vec::new();
+    for (index, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let item = Value::parse(&line)
+            .and_then(|value| parse(&value))
+            .map_err(|reason| TraceReadError::Parse {
+                line: index + 1,
+                reason,
+            })?;
+        result.push(item);
+    }
+    Ok(result)
+}
+
+/// Writes trace in the JSON lines format.
+///
+/// The first line is a header with the format name and version,
+/// every next line describes one event.
+pub fn write_trace<W: Write>(trace: &[Event], writer: W) -> io::Result<()> {
+    write_jsonl(
+        writer,
+        TRACE_FORMAT,
+        TRACE_FORMAT_VERSION,
+        trace.iter().map(event_to_json),
+    )
+}
+
+/// Reads trace written by [`write_trace`].
+/// Empty lines are ignored.
+///
+/// Vector clocks are not stored in the file,
+/// they are restored from the order of events.
+pub fn read_trace<R: BufRead>(reader: R) -> Result<Vec<Event>, TraceReadError> {
+    let mut clocks = Clocks::default();
+    read_jsonl(reader, TRACE_FORMAT, 1..=TRACE_FORMAT_VERSION, |value| {
+        event_from_json(value, &mut clocks)
+    })
+}
@@ -0,0 +1,39 @@
+use std::rc::Rc;
+
+use crate::random::SeededRng;
+
+/// Distribution of the time it takes a message or ack to travel over a link,
+/// used by the timing mode of the [`crate::System`].
+#[derive(Clone)]
+pub enum Latency {
+    Constant(f64),
+    /// Uniform between the bounds.
+    Uniform(f64, f64),
+    /// Exponential with the mean.
+    Exponential(f64),
+    /// Function which maps a number drawn uniformly from `[0, 1)` to the latency,
+    /// e.g. inverse of the distribution function.
+    Custom(Rc<dyn Fn(f64) -> f64>),
+}
+
+impl Latency {
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(f64) -> f64 + 'static,
+    {
+        Latency::Custom(Rc::new(f))
+    }
+
+    /// Draws latency from the distribution.
+    /// Negative values are treated as zero.
+    pub(crate) fn sample(&self, rng: &mut SeededRng) -> f64 {
+        let u = rng.draw_unit();
+        let latency = match self {
+            Latency::Constant(latency) => *latency,
+            Latency::Uniform(min, max) => min + (max - min) * u,
+            Latency::Exponential(mean) => -mean * (1.0 - u).ln(),
+            Latency::Custom(f) => f(u),
+        };
+        latency.max(0.0)
+    }
+}
@@ -1,18 +1,53 @@
 mod ack;
+mod analysis;
+mod clock;
+mod delivery;
+mod diagram;
+mod error;
 mod event;
+mod explore;
+pub mod fd;
 mod join;
+pub mod json;
+mod jsonl;
+mod latency;
+pub mod maelstrom;
 mod process;
+pub mod protocols;
+pub mod quorum;
+mod random;
+pub mod reliable;
+mod report;
+pub mod runtime;
+mod schedule;
 mod send;
 mod shared;
+mod shiviz;
 mod spawn;
 mod system;
 mod task;
+mod time;
+mod topology;
 mod waker;
+pub mod workload;
 
-pub use ack::AckHandle;
-pub use event::{Event, EventKind};
+pub use ack::{AckHandle, AckOutcome, AckTimeout};
+pub use analysis::TraceAnalysis;
+pub use clock::VectorClock;
+pub use delivery::Delivery;
+pub use diagram::{render_ascii, render_mermaid, render_plantuml, DiagramOptions};
+pub use error::Error;
+pub use event::{Event, EventKind, MessageId};
+pub use explore::{ExploreReport, Explorer, Model, Violation};
 pub use join::JoinHandle;
+pub use jsonl::{read_trace, write_trace, TraceReadError, TRACE_FORMAT_VERSION};
+pub use latency::Latency;
 pub use process::{Process, ProcessId};
-pub use send::{send, send_local};
-pub use spawn::spawn;
+pub use random::{random, random_range, try_random, try_random_range};
+pub use schedule::{replay, Divergence, Schedule, ScheduleStep, SCHEDULE_FORMAT_VERSION};
+pub use send::{send, send_local, send_unacked, try_send, try_send_local, try_send_unacked};
+pub use shiviz::{write_shiviz, SHIVIZ_REGEX};
+pub use spawn::{spawn, try_spawn};
 pub use system::System;
+pub use time::{now, sleep, try_now, try_sleep, JumpId, LocalClock, Sleep, TimerId};
+pub use topology::Topology;
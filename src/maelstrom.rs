@@ -0,0 +1,224 @@
+//! Adapter which runs a [`Process`] as a [Maelstrom](https://github.com/jepsen-io/maelstrom) node,
+//! speaking JSON over stdin and stdout.
+//!
+//! After the `init` message the process is created by the factory,
+//! which gets identifier of the node and number of nodes.
+//! Node `node_ids[i]` has [`ProcessId`] `i`.
+//!
+//! * [`crate::send`] is sent to the other node as a `flurry` message,
+//!   and its [`crate::AckHandle`] is resolved when the `flurry_ok` reply arrives.
+//! * Request of the client is passed to [`Process::on_local_message`] as its JSON body.
+//!   Every request gets unique `msg_id`, so the process replies with [`crate::send_local`],
+//!   passing the body with `in_reply_to` set to that `msg_id`.
+//!   Replies are routed to the client which made the request,
+//!   and other local messages are written to the log, which is stderr in [`run`].
+//!
+//! So process which is driven by JSON bodies in the [`crate::System`]
+//! runs unmodified in Maelstrom.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    rc::Rc,
+    sync::{mpsc::channel, Arc, Mutex},
+    thread,
+};
+
+use crate::{
+    event::MessageId,
+    json::Value,
+    process::{Process, ProcessId},
+    runtime::node::{Input, Node, Transport},
+};
+
+/// Requests of clients which wait for reply,
+/// by `msg_id` given to them by the adapter.
+#[derive(Default)]
+struct Requests {
+    next_id: u64,
+    waiting: HashMap<u64, (String, Option<Value>)>,
+}
+
+struct MaelstromTransport<W: Write, L: Write> {
+    me: String,
+    nodes: Arc<Vec<String>>,
+    requests: Arc<Mutex<Requests>>,
+    output: Rc<RefCell<W>>,
+    /// Local messages which are not replies to clients.
+    log: RefCell<L>,
+}
+
+impl<W: Write, L: Write> MaelstromTransport<W, L> {
+    fn write(&self, dest: &str, body: Value) {
+        let msg = Value::object([
+            ("src", self.me.as_str().into()),
+            ("dest", dest.into()),
+            ("body", body),
+        ]);
+        let mut output = self.output.borrow_mut();
+        writeln!(output, "{msg}")
+            .and_then(|_| output.flush())
+            .expect("failed to write message");
+    }
+}
+
+impl<W: Write, L: Write> Transport for MaelstromTransport<W, L> {
+    fn processes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn send(&self, to: ProcessId, msg_id: MessageId, msg: &str) {
+        let body = Value::object([
+            ("type", "flurry".into()),
+            ("msg_id", msg_id.into()),
+            ("msg", msg.into()),
+        ]);
+        self.write(&self.nodes[to], body);
+    }
+
+    fn ack(&self, to: ProcessId, msg_id: MessageId) {
+        let body = Value::object([("type", "flurry_ok".into()), ("in_reply_to", msg_id.into())]);
+        self.write(&self.nodes[to], body);
+    }
+
+    fn local(&self, msg: String) {
+        let reply = Value::parse(&msg).ok().and_then(|mut body| {
+            let id = body.get("in_reply_to")?.as_u64()?;
+            let (client, msg_id) = self.requests.lock().unwrap().waiting.remove(&id)?;
+            body.set("in_reply_to", msg_id.unwrap_or(Value::Null));
+            Some((client, body))
+        });
+        match reply {
+            Some((client, body)) => self.write(&client, body),
+            None => {
+                let mut log = self.log.borrow_mut();
+                writeln!(log, "{msg}")
+                    .and_then(|_| log.flush())
+                    .expect("failed to write log");
+            }
+        }
+    }
+}
+
+/// Converts message of Maelstrom into input of the node.
+fn parse_input(line: &str, nodes: &[String], requests: &Mutex<Requests>) -> Option<Input> {
+    let msg = Value::parse(line).ok()?;
+    let src = msg.get("src")?.as_str()?;
+    let mut body = msg.get("body")?.clone();
+    if let Some(from) = nodes.iter().position(|node| node == src) {
+        return match body.get("type")?.as_str()? {
+            "flurry" => Some(Input::Message {
+                from,
+                msg_id: body.get("msg_id")?.as_u64()? as MessageId,
+                msg: body.get("msg")?.as_str()?.to_string(),
+            }),
+            "flurry_ok" => Some(Input::Ack {
+                msg_id: body.get("in_reply_to")?.as_u64()? as MessageId,
+            }),
+            _ => None,
+        };
+    }
+
+    let mut requests = requests.lock().unwrap();
+    let id = requests.next_id;
+    requests.next_id += 1;
+    let msg_id = body.get("msg_id").cloned();
+    requests.waiting.insert(id, (src.to_string(), msg_id));
+    body.set("msg_id", id.into());
+    Some(Input::Local(body.to_string()))
+}
+
+/// Runs process as Maelstrom node, reading messages from `input`
+/// and writing them to `output`, until input is closed.
+/// Local messages which are not replies are written to `log`.
+pub fn run_with<R, W, L, P, F>(mut input: R, output: W, log: L, factory: F) -> io::Result<()>
+where
+    R: BufRead + Send + 'static,
+    W: Write + 'static,
+    L: Write + 'static,
+    P: Process + 'static,
+    F: FnOnce(ProcessId, usize) -> P,
+{
+    let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+
+    let mut line = String::new();
+    let init = loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Err(invalid("input closed before init"));
+        }
+        if let Ok(msg) = Value::parse(&line) {
+            if msg
+                .get("body")
+                .and_then(|b| b.get("type"))
+                .and_then(Value::as_str)
+                == Some("init")
+            {
+                break msg;
+            }
+        }
+    };
+    let body = init.get("body").unwrap();
+    let me = body
+        .get("node_id")
+        .and_then(Value::as_str)
+        .ok_or(invalid("init without node_id"))?
+        .to_string();
+    let nodes: Vec<String> = body
+        .get("node_ids")
+        .and_then(Value::as_array)
+        .ok_or(invalid("init without node_ids"))?
+        .iter()
+        .filter_map(|node| node.as_str().map(str::to_string))
+        .collect();
+    let proc = nodes
+        .iter()
+        .position(|node| *node == me)
+        .ok_or(invalid("node_id is not in node_ids"))?;
+
+    let count = nodes.len();
+    let nodes = Arc::new(nodes);
+    let requests = Arc::new(Mutex::new(Requests::default()));
+    let transport = MaelstromTransport {
+        me,
+        nodes: nodes.clone(),
+        requests: requests.clone(),
+        output: Rc::new(RefCell::new(output)),
+        log: RefCell::new(log),
+    };
+    let client = init.get("src").and_then(Value::as_str).unwrap_or_default();
+    let msg_id = body.get("msg_id").cloned().unwrap_or(Value::Null);
+    transport.write(
+        client,
+        Value::object([("type", "init_ok".into()), ("in_reply_to", msg_id)]),
+    );
+
+    let (sender, inbox) = channel();
+    let reader = thread::spawn(move || -> io::Result<()> {
+        for line in input.lines() {
+            if let Some(input) = parse_input(&line?, &nodes, &requests) {
+                if sender.send(input).is_err() {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    });
+    Node::new(proc, Box::new(factory(proc, count)), Box::new(transport)).run(inbox);
+    reader.join().expect("reader panicked")
+}
+
+/// Runs process as Maelstrom node on stdin and stdout.
+pub fn run<P, F>(factory: F) -> io::Result<()>
+where
+    P: Process + 'static,
+    F: FnOnce(ProcessId, usize) -> P,
+{
+    run_with(
+        io::BufReader::new(io::stdin()),
+        io::stdout(),
+        io::stderr(),
+        factory,
+    )
+}
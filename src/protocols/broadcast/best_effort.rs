@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+
+use crate::{process::ProcessId, send::send_unacked};
+
+use super::{decode, encode, Broadcast, Delivered};
+
+/// Sends every message directly to all processes.
+/// If the origin crashes while sending, only some processes deliver the message.
+pub struct BestEffort {
+    me: ProcessId,
+    processes: usize,
+    next_seq: u64,
+    delivered: HashSet<(ProcessId, u64)>,
+}
+
+impl BestEffort {
+    pub fn new(me: ProcessId, processes: usize) -> Self {
+        Self {
+            me,
+            processes,
+            next_seq: 0,
+            delivered: HashSet::new(),
+        }
+    }
+}
+
+impl Broadcast for BestEffort {
+    fn broadcast(&mut self, payload: String) -> Vec<Delivered> {
+        let msg = Delivered {
+            origin: self.me,
+            seq: self.next_seq,
+            payload,
+        };
+        self.next_seq += 1;
+        for to in (0..self.processes).filter(|to| *to != self.me) {
+            send_unacked(to, encode(&msg));
+        }
+        self.delivered.insert((msg.origin, msg.seq));
+        vec![msg]
+    }
+
+    fn on_message(&mut self, _from: ProcessId, msg: &str) -> Vec<Delivered> {
+        match decode(msg) {
+            Some(msg) if self.delivered.insert((msg.origin, msg.seq)) => vec![msg],
+            _ => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{json::Value, process::ProcessId};
+
+use super::{Broadcast, Delivered};
+
+/// Delivers message only after all messages which were broadcast or delivered by its origin
+/// before it was broadcast, using vector of delivered counts attached to the payload.
+pub struct Causal<B> {
+    me: ProcessId,
+    inner: B,
+    broadcasts: u64,
+    /// Number of delivered messages of every origin.
+    delivered: Vec<u64>,
+    /// Messages delivered by the inner broadcast with their dependencies.
+    held: Vec<(Delivered, Vec<u64>)>,
+}
+
+impl<B: Broadcast> Causal<B> {
+    pub fn new(me: ProcessId, inner: B) -> Self {
+        Self {
+            me,
+            inner,
+            broadcasts: 0,
+            delivered: Vec::new(),
+            held: Vec::new(),
+        }
+    }
+
+    fn count(&self, origin: ProcessId) -> u64 {
+        self.delivered.get(origin).copied().unwrap_or(0)
+    }
+
+    fn order(&mut self, delivered: Vec<Delivered>) -> Vec<Delivered> {
+        for mut msg in delivered {
+            let Some((deps, payload)) = decode(&msg.payload) else {
+                continue;
+            };
+            msg.payload = payload;
+            self.held.push((msg, deps));
+        }
+
+        let mut ready = Vec::new();
+        while let Some(index) = self.held.iter().position(|(msg, deps)| {
+            deps.iter().enumerate().all(|(origin, count)| {
+                if origin == msg.origin {
+                    *count == self.count(origin)
+                } else {
+                    *count <= self.count(origin)
+                }
+            })
+        }) {
+            let (msg, _) = self.held.remove(index);
+            if self.delivered.len() <= msg.origin {
+                self.delivered.resize(msg.origin + 1, 0);
+            }
+            self.delivered[msg.origin] += 1;
+            ready.push(msg);
+        }
+        ready
+    }
+}
+
+impl<B: Broadcast> Broadcast for Causal<B> {
+    fn broadcast(&mut self, payload: String) -> Vec<Delivered> {
+        let mut deps = self.delivered.clone();
+        if deps.len() <= self.me {
+            deps.resize(self.me + 1, 0);
+        }
+        // Own messages may be delivered later, but still must be delivered in order.
+        deps[self.me] = self.broadcasts;
+        self.broadcasts += 1;
+        let delivered = self.inner.broadcast(encode(&deps, payload));
+        self.order(delivered)
+    }
+
+    fn on_message(&mut self, from: ProcessId, msg: &str) -> Vec<Delivered> {
+        let delivered = self.inner.on_message(from, msg);
+        self.order(delivered)
+    }
+}
+
+fn encode(deps: &[u64], payload: String) -> String {
+    let deps: Vec<Value> = deps.iter().map(|count| (*count).into()).collect();
+    Value::object([("deps", deps.into()), ("payload", payload.into())]).to_string()
+}
+
+fn decode(payload: &str) -> Option<(Vec<u64>, String)> {
+    let value = Value::parse(payload).ok()?;
+    let deps = value
+        .get("deps")?
+        .as_array()?
+        .iter()
+        .map(Value::as_u64)
+        .collect::<Option<_>>()?;
+    Some((deps, value.get("payload")?.as_str()?.to_string()))
+}
@@ -0,0 +1,181 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::{event::Event, process::ProcessId, report::Run};
+
+/// Message identified by its origin and sequence number.
+type MessageId = (ProcessId, u64);
+
+enum Step {
+    Broadcast(MessageId),
+    Deliver(MessageId),
+}
+
+/// Broadcasts and deliveries reported by processes, in the order of the trace.
+struct History {
+    steps: Vec<(ProcessId, Step)>,
+    run: Run,
+}
+
+impl History {
+    fn new(trace: &[Event], processes: usize) -> Result<Self, String> {
+        let mut steps = Vec::new();
+        let mut broadcasts: HashMap<ProcessId, u64> = HashMap::new();
+        let run = Run::read(trace, processes, |_, proc, report, rest| {
+            match report {
+                "broadcast" => {
+                    let seq = broadcasts.entry(proc).or_default();
+                    steps.push((proc, Step::Broadcast((proc, *seq))));
+                    *seq += 1;
+                }
+                "deliver" => {
+                    let mut parts = rest.splitn(3, ' ');
+                    let id = parts
+                        .next()
+                        .and_then(|origin| origin.parse().ok())
+                        .zip(parts.next().and_then(|seq| seq.parse().ok()))
+                        .ok_or(format!(
+                            "process {proc} reported invalid delivery 'deliver {rest}'"
+                        ))?;
+                    steps.push((proc, Step::Deliver(id)));
+                }
+                _ => {}
+            }
+            Ok(())
+        })?;
+        Ok(History { steps, run })
+    }
+
+    fn delivered(&self, proc: ProcessId) -> impl Iterator<Item = MessageId> + '_ {
+        self.steps.iter().filter_map(move |(p, step)| match step {
+            Step::Deliver(id) if *p == proc => Some(*id),
+            _ => None,
+        })
+    }
+
+    fn broadcasts(&self) -> impl Iterator<Item = MessageId> + '_ {
+        self.steps.iter().filter_map(|(_, step)| match step {
+            Step::Broadcast(id) => Some(*id),
+            _ => None,
+        })
+    }
+
+    /// Checks that every correct process delivered every message delivered by the `deliverers`.
+    fn agreement(&self, deliverers: impl Iterator<Item = ProcessId>) -> Result<(), String> {
+        let mut all = BTreeSet::new();
+        for proc in deliverers {
+            all.extend(self.delivered(proc).map(|id| (id, proc)));
+        }
+        for proc in self.run.correct() {
+            let delivered: HashSet<_> = self.delivered(proc).collect();
+            if let Some(((origin, seq), by)) = all.iter().find(|(id, _)| !delivered.contains(id)) {
+                return Err(format!(
+                    "process {proc} did not deliver message {origin}:{seq} delivered by process {by}"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that every correct process delivered every message which it broadcast.
+pub fn check_validity(trace: &[Event], processes: usize) -> Result<(), String> {
+    let history = History::new(trace, processes)?;
+    for proc in history.run.correct() {
+        let delivered: HashSet<_> = history.delivered(proc).collect();
+        let missing = history
+            .broadcasts()
+            .find(|id| id.0 == proc && !delivered.contains(id));
+        if let Some((_, seq)) = missing {
+            return Err(format!(
+                "process {proc} did not deliver its message {proc}:{seq}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every message is delivered by every process at most once,
+/// and only if it was broadcast.
+pub fn check_no_duplication(trace: &[Event], processes: usize) -> Result<(), String> {
+    let history = History::new(trace, processes)?;
+    let broadcasts: HashSet<_> = history.broadcasts().collect();
+    for proc in 0..history.run.processes {
+        let mut delivered = HashSet::new();
+        for (origin, seq) in history.delivered(proc) {
+            if !broadcasts.contains(&(origin, seq)) {
+                return Err(format!(
+                    "process {proc} delivered message {origin}:{seq} which was not broadcast"
+                ));
+            }
+            if !delivered.insert((origin, seq)) {
+                return Err(format!(
+                    "process {proc} delivered message {origin}:{seq} twice"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that messages delivered by any correct process are delivered by all correct processes.
+pub fn check_agreement(trace: &[Event], processes: usize) -> Result<(), String> {
+    let history = History::new(trace, processes)?;
+    history.agreement(history.run.correct())
+}
+
+/// Checks that messages delivered by any process, even crashed,
+/// are delivered by all correct processes.
+pub fn check_uniform_agreement(trace: &[Event], processes: usize) -> Result<(), String> {
+    let history = History::new(trace, processes)?;
+    history.agreement(0..history.run.processes)
+}
+
+/// Checks that every process delivered messages of every origin in the order of broadcasts.
+pub fn check_fifo_order(trace: &[Event], processes: usize) -> Result<(), String> {
+    let history = History::new(trace, processes)?;
+    for proc in 0..history.run.processes {
+        let mut next: HashMap<ProcessId, u64> = HashMap::new();
+        for (origin, seq) in history.delivered(proc) {
+            let expected = next.entry(origin).or_default();
+            if seq != *expected {
+                return Err(format!(
+                    "process {proc} delivered message {origin}:{seq} before {origin}:{expected}"
+                ));
+            }
+            *expected += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every process delivered message only after all messages
+/// which were broadcast or delivered by its origin before it was broadcast.
+pub fn check_causal_order(trace: &[Event], processes: usize) -> Result<(), String> {
+    let history = History::new(trace, processes)?;
+    let mut deps: HashMap<MessageId, BTreeSet<MessageId>> = HashMap::new();
+    let mut past: HashMap<ProcessId, BTreeSet<MessageId>> = HashMap::new();
+    let mut delivered: HashMap<ProcessId, HashSet<MessageId>> = HashMap::new();
+    for (proc, step) in history.steps.iter() {
+        let past = past.entry(*proc).or_default();
+        match step {
+            Step::Broadcast(id) => {
+                deps.insert(*id, past.clone());
+                past.insert(*id);
+            }
+            Step::Deliver(id) => {
+                let id_deps = deps.get(id).cloned().unwrap_or_default();
+                let delivered = delivered.entry(*proc).or_default();
+                if let Some((origin, seq)) = id_deps.iter().find(|dep| !delivered.contains(dep)) {
+                    return Err(format!(
+                        "process {proc} delivered message {}:{} before {origin}:{seq}",
+                        id.0, id.1
+                    ));
+                }
+                delivered.insert(*id);
+                past.insert(*id);
+                past.extend(id_deps);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use crate::process::ProcessId;
+
+use super::{Broadcast, Delivered};
+
+/// Delivers messages of every origin in the order of their broadcasts,
+/// holding messages delivered by the inner broadcast out of order.
+pub struct Fifo<B> {
+    inner: B,
+    next: HashMap<ProcessId, u64>,
+    held: HashMap<(ProcessId, u64), Delivered>,
+}
+
+impl<B: Broadcast> Fifo<B> {
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            next: HashMap::new(),
+            held: HashMap::new(),
+        }
+    }
+
+    fn order(&mut self, delivered: Vec<Delivered>) -> Vec<Delivered> {
+        let mut ready = Vec::new();
+        for msg in delivered {
+            let origin = msg.origin;
+            self.held.insert((origin, msg.seq), msg);
+            let next = self.next.entry(origin).or_default();
+            while let Some(msg) = self.held.remove(&(origin, *next)) {
+                ready.push(msg);
+                *next += 1;
+            }
+        }
+        ready
+    }
+}
+
+impl<B: Broadcast> Broadcast for Fifo<B> {
+    fn broadcast(&mut self, payload: String) -> Vec<Delivered> {
+        let delivered = self.inner.broadcast(payload);
+        self.order(delivered)
+    }
+
+    fn on_message(&mut self, from: ProcessId, msg: &str) -> Vec<Delivered> {
+        let delivered = self.inner.on_message(from, msg);
+        self.order(delivered)
+    }
+}
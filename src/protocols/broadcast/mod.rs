@@ -0,0 +1,106 @@
+//! Broadcast abstractions from the textbook, which can be composed:
+//! [`Fifo`] and [`Causal`] order messages delivered by the inner broadcast.
+//!
+//! Every message is identified by its origin and the sequence number of the broadcast there.
+//! Messages are sent with [`crate::send_unacked`], so links are assumed to be reliable.
+//!
+//! [`BroadcastProcess`] reports `broadcast <payload>` and `deliver <origin> <seq> <payload>`
+//! local messages, from which checkers like [`check_agreement`] rebuild the run.
+//! A message still in flight counts as not delivered, so check only finished runs.
+//! Checkers also take the number of processes from [`crate::System::get_processes_count`],
+//! because a process which no message reached leaves no events in the trace.
+
+mod best_effort;
+mod causal;
+mod check;
+mod fifo;
+mod reliable;
+mod uniform;
+
+use crate::{json::Value, process::Process, process::ProcessId, send::send_local};
+
+pub use best_effort::BestEffort;
+pub use causal::Causal;
+pub use check::{
+    check_agreement, check_causal_order, check_fifo_order, check_no_duplication,
+    check_uniform_agreement, check_validity,
+};
+pub use fifo::Fifo;
+pub use reliable::Reliable;
+pub use uniform::Uniform;
+
+/// Message delivered by the broadcast.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivered {
+    pub origin: ProcessId,
+    /// Number of broadcasts made by the origin before this one.
+    pub seq: u64,
+    pub payload: String,
+}
+
+/// Broadcast protocol, driven by the process which uses it.
+pub trait Broadcast {
+    /// Broadcasts payload, returning messages delivered right away.
+    fn broadcast(&mut self, payload: String) -> Vec<Delivered>;
+
+    /// Handles message of the protocol received from the process,
+    /// returning delivered messages.
+    /// Messages which do not belong to the protocol are ignored.
+    fn on_message(&mut self, from: ProcessId, msg: &str) -> Vec<Delivered>;
+}
+
+/// Reports broadcast of the payload as a local message, which is read by the checkers.
+pub fn report_broadcast(payload: &str) {
+    send_local(format!("broadcast {payload}"));
+}
+
+/// Reports delivered messages as local messages, which are read by the checkers.
+pub fn report_delivered(delivered: &[Delivered]) {
+    for msg in delivered {
+        send_local(format!(
+            "deliver {} {} {}",
+            msg.origin, msg.seq, msg.payload
+        ));
+    }
+}
+
+/// Process which broadcasts every local message and reports broadcasts and deliveries.
+pub struct BroadcastProcess<B> {
+    protocol: B,
+}
+
+impl<B: Broadcast> BroadcastProcess<B> {
+    pub fn new(protocol: B) -> Self {
+        Self { protocol }
+    }
+}
+
+impl<B: Broadcast> Process for BroadcastProcess<B> {
+    fn on_message(&mut self, from: ProcessId, msg: String) {
+        report_delivered(&self.protocol.on_message(from, &msg));
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        report_broadcast(msg);
+        report_delivered(&self.protocol.broadcast(msg.to_string()));
+    }
+}
+
+/// Message sent by [`BestEffort`], [`Reliable`] and [`Uniform`].
+fn encode(msg: &Delivered) -> String {
+    Value::object([
+        ("origin", msg.origin.into()),
+        ("seq", msg.seq.into()),
+        ("payload", msg.payload.as_str().into()),
+    ])
+    .to_string()
+}
+
+fn decode(msg: &str) -> Option<Delivered> {
+    let value = Value::parse(msg).ok()?;
+    Some(Delivered {
+        origin: value.get("origin")?.as_u64()? as ProcessId,
+        seq: value.get("seq")?.as_u64()?,
+        payload: value.get("payload")?.as_str()?.to_string(),
+    })
+}
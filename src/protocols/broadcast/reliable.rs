@@ -0,0 +1,56 @@
+use std::collections::HashSet;
+
+use crate::{process::ProcessId, send::send_unacked};
+
+use super::{decode, encode, Broadcast, Delivered};
+
+/// Eager reliable broadcast: every process relays message to all others
+/// when it delivers the message for the first time,
+/// so all correct processes deliver the same messages.
+pub struct Reliable {
+    me: ProcessId,
+    processes: usize,
+    next_seq: u64,
+    delivered: HashSet<(ProcessId, u64)>,
+}
+
+impl Reliable {
+    pub fn new(me: ProcessId, processes: usize) -> Self {
+        Self {
+            me,
+            processes,
+            next_seq: 0,
+            delivered: HashSet::new(),
+        }
+    }
+
+    fn relay(&self, msg: &Delivered, from: ProcessId) {
+        for to in (0..self.processes).filter(|to| *to != self.me && *to != from) {
+            send_unacked(to, encode(msg));
+        }
+    }
+}
+
+impl Broadcast for Reliable {
+    fn broadcast(&mut self, payload: String) -> Vec<Delivered> {
+        let msg = Delivered {
+            origin: self.me,
+            seq: self.next_seq,
+            payload,
+        };
+        self.next_seq += 1;
+        self.delivered.insert((msg.origin, msg.seq));
+        self.relay(&msg, self.me);
+        vec![msg]
+    }
+
+    fn on_message(&mut self, from: ProcessId, msg: &str) -> Vec<Delivered> {
+        match decode(msg) {
+            Some(msg) if self.delivered.insert((msg.origin, msg.seq)) => {
+                self.relay(&msg, from);
+                vec![msg]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{process::ProcessId, send::send_unacked};
+
+use super::{decode, encode, Broadcast, Delivered};
+
+/// Majority-ack uniform reliable broadcast: every process relays message to all others
+/// when it sees the message for the first time,
+/// and delivers it when the majority of processes relayed it.
+///
+/// So message delivered by any process, even crashed later,
+/// is delivered by all correct processes, as long as the majority is correct.
+pub struct Uniform {
+    me: ProcessId,
+    processes: usize,
+    next_seq: u64,
+    /// Processes which relayed the message, by its origin and sequence number.
+    seen: HashMap<(ProcessId, u64), HashSet<ProcessId>>,
+    delivered: HashSet<(ProcessId, u64)>,
+}
+
+impl Uniform {
+    pub fn new(me: ProcessId, processes: usize) -> Self {
+        Self {
+            me,
+            processes,
+            next_seq: 0,
+            seen: HashMap::new(),
+            delivered: HashSet::new(),
+        }
+    }
+
+    /// Records that the process relayed the message,
+    /// relaying it first if it is new, and delivers it once the majority relayed it.
+    fn observe(&mut self, msg: Delivered, from: ProcessId) -> Vec<Delivered> {
+        let id = (msg.origin, msg.seq);
+        if !self.seen.contains_key(&id) {
+            for to in (0..self.processes).filter(|to| *to != self.me) {
+                send_unacked(to, encode(&msg));
+            }
+        }
+        let relayed = self.seen.entry(id).or_default();
+        relayed.insert(self.me);
+        relayed.insert(from);
+        if relayed.len() * 2 > self.processes && self.delivered.insert(id) {
+            vec![msg]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl Broadcast for Uniform {
+    fn broadcast(&mut self, payload: String) -> Vec<Delivered> {
+        let msg = Delivered {
+            origin: self.me,
+            seq: self.next_seq,
+            payload,
+        };
+        self.next_seq += 1;
+        self.observe(msg, self.me)
+    }
+
+    fn on_message(&mut self, from: ProcessId, msg: &str) -> Vec<Delivered> {
+        match decode(msg) {
+            Some(msg) => self.observe(msg, from),
+            None => Vec::new(),
+        }
+    }
+}
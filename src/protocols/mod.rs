@@ -0,0 +1,4 @@
+//! Reusable implementations of standard distributed protocols,
+//! which are embedded into [`crate::Process`]es.
+
+pub mod broadcast;
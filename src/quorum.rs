@@ -0,0 +1,334 @@
+//! Sending to several processes and waiting for `k` of them,
+//! e.g. for a majority in consensus protocols.
+//!
+//! Futures resolve as soon as `k` peers answered,
+//! or as soon as it is known that `k` answers will never arrive,
+//! because too many messages were not acknowledged.
+//! Acks which arrive later are ignored, and so are replies of other rounds,
+//! which are told apart by the round given to `collect` and [`Replies::put`].
+
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    ack::{AckHandle, AckOutcome},
+    join::JoinHandle,
+    process::ProcessId,
+    send::{send, send_unacked},
+};
+
+/// Removes ready futures, returning their outputs.
+fn take_ready<F: Future + Unpin>(
+    pending: &mut Vec<(ProcessId, F)>,
+    cx: &mut Context<'_>,
+) -> Vec<(ProcessId, F::Output)> {
+    let mut ready = Vec::new();
+    pending.retain_mut(|(proc, future)| match Pin::new(future).poll(cx) {
+        Poll::Ready(output) => {
+            ready.push((*proc, output));
+            false
+        }
+        Poll::Pending => true,
+    });
+    ready
+}
+
+/// Message sent to every process of the quorum with [`broadcast`].
+pub struct Broadcast {
+    acks: Vec<(ProcessId, AckHandle)>,
+}
+
+/// Sends message to every process with [`crate::send`].
+pub fn broadcast(pids: impl IntoIterator<Item = ProcessId>, msg: String) -> Broadcast {
+    Broadcast {
+        acks: pids
+            .into_iter()
+            .map(|to| (to, send(to, msg.clone())))
+            .collect(),
+    }
+}
+
+impl Broadcast {
+    /// Waits for `k` acks, returning processes which acknowledged the message.
+    /// Returns error with them if `k` acks will not arrive.
+    pub fn await_k(self, k: usize) -> AwaitAcks {
+        AwaitAcks {
+            acks: self.acks,
+            k,
+            answered: Vec::new(),
+        }
+    }
+
+    /// Waits for `k` processes to reply in the round, returning their first replies
+    /// in the order of arrival.
+    /// Returns error with replies which arrived if `k` replies will not arrive,
+    /// as messages to other processes were not acknowledged.
+    ///
+    /// Replies collected before are discarded, and replies of other rounds are ignored,
+    /// so a late reply to the previous request is never counted.
+    pub fn collect<T, R: Clone + PartialEq>(
+        self,
+        replies: &Replies<T, R>,
+        round: R,
+        k: usize,
+    ) -> AwaitReplies<T, R> {
+        AwaitReplies::new(
+            self.acks.iter().map(|(proc, _)| *proc).collect(),
+            self.acks,
+            replies,
+            round,
+            k,
+        )
+    }
+}
+
+/// Message sent to every process of the quorum with [`broadcast_unacked`].
+pub struct UnackedBroadcast {
+    pids: Vec<ProcessId>,
+}
+
+/// Sends message to every process with [`crate::send_unacked`],
+/// so there are fewer orders of events for the [`crate::Explorer`].
+pub fn broadcast_unacked(
+    pids: impl IntoIterator<Item = ProcessId>,
+    msg: String,
+) -> UnackedBroadcast {
+    let pids: Vec<_> = pids.into_iter().collect();
+    for to in pids.iter() {
+        send_unacked(*to, msg.clone());
+    }
+    UnackedBroadcast { pids }
+}
+
+impl UnackedBroadcast {
+    /// Waits for `k` processes to reply, like [`Broadcast::collect`].
+    /// Lost messages are not noticed, so it waits until `k` replies arrive.
+    pub fn collect<T, R: Clone + PartialEq>(
+        self,
+        replies: &Replies<T, R>,
+        round: R,
+        k: usize,
+    ) -> AwaitReplies<T, R> {
+        AwaitReplies::new(self.pids, Vec::new(), replies, round, k)
+    }
+}
+
+/// Future returned by [`Broadcast::await_k`].
+pub struct AwaitAcks {
+    acks: Vec<(ProcessId, AckHandle)>,
+    k: usize,
+    answered: Vec<ProcessId>,
+}
+
+impl Future for AwaitAcks {
+    type Output = Result<Vec<ProcessId>, Vec<ProcessId>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        for (proc, outcome) in take_ready(&mut this.acks, cx) {
+            if outcome == AckOutcome::Delivered {
+                this.answered.push(proc);
+            }
+        }
+        if this.answered.len() >= this.k {
+            Poll::Ready(Ok(std::mem::take(&mut this.answered)))
+        } else if this.answered.len() + this.acks.len() < this.k {
+            Poll::Ready(Err(std::mem::take(&mut this.answered)))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+struct ReplyState<T, R> {
+    /// Round of the last collect, `None` before the first one.
+    round: Option<R>,
+    replies: Vec<(ProcessId, T)>,
+    waker: Option<Waker>,
+}
+
+/// Replies of processes, which are put by [`crate::Process::on_message`]
+/// and collected by [`Broadcast::collect`].
+///
+/// Every reply carries the round of the request, e.g. its ballot or request id.
+pub struct Replies<T, R = u64>(Rc<RefCell<ReplyState<T, R>>>);
+
+impl<T, R> Clone for Replies<T, R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T, R> Default for Replies<T, R> {
+    fn default() -> Self {
+        Self(Rc::new(RefCell::new(ReplyState {
+            round: None,
+            replies: Vec::new(),
+            waker: None,
+        })))
+    }
+}
+
+impl<T, R: PartialEq> Replies<T, R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds reply of the process in the round,
+    /// unless it already replied or the round is not collected now.
+    pub fn put(&self, from: ProcessId, round: R, reply: T) {
+        let waker = {
+            let mut state = self.0.borrow_mut();
+            if state.round.as_ref() != Some(&round)
+                || state.replies.iter().any(|(proc, _)| *proc == from)
+            {
+                return;
+            }
+            state.replies.push((from, reply));
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Future returned by [`Broadcast::collect`] and [`UnackedBroadcast::collect`].
+///
+/// It is resolved with error without replies if the next round is collected first.
+pub struct AwaitReplies<T, R = u64> {
+    pids: Vec<ProcessId>,
+    acks: Vec<(ProcessId, AckHandle)>,
+    replies: Replies<T, R>,
+    round: R,
+    /// Processes which did not acknowledge the message.
+    failed: Vec<ProcessId>,
+    k: usize,
+}
+
+impl<T, R: Clone + PartialEq> AwaitReplies<T, R> {
+    fn new(
+        pids: Vec<ProcessId>,
+        acks: Vec<(ProcessId, AckHandle)>,
+        replies: &Replies<T, R>,
+        round: R,
+        k: usize,
+    ) -> Self {
+        let mut state = replies.0.borrow_mut();
+        state.round = Some(round.clone());
+        state.replies.clear();
+        let previous = state.waker.take();
+        drop(state);
+        if let Some(waker) = previous {
+            waker.wake();
+        }
+        Self {
+            pids,
+            acks,
+            replies: replies.clone(),
+            round,
+            failed: Vec::new(),
+            k,
+        }
+    }
+}
+
+/// Replies and rounds are never pinned.
+impl<T, R> Unpin for AwaitReplies<T, R> {}
+
+impl<T, R: PartialEq> Future for AwaitReplies<T, R> {
+    type Output = Result<Vec<(ProcessId, T)>, Vec<(ProcessId, T)>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        for (proc, outcome) in take_ready(&mut this.acks, cx) {
+            if outcome != AckOutcome::Delivered {
+                this.failed.push(proc);
+            }
+        }
+
+        let mut state = this.replies.0.borrow_mut();
+        if state.round.as_ref() != Some(&this.round) {
+            return Poll::Ready(Err(Vec::new()));
+        }
+        let pids = &this.pids;
+        let replied = state
+            .replies
+            .iter()
+            .filter(|(proc, _)| pids.contains(proc))
+            .count();
+        let possible = pids
+            .iter()
+            .filter(|proc| {
+                !this.failed.contains(proc) && state.replies.iter().all(|(from, _)| from != *proc)
+            })
+            .count();
+        if replied >= this.k || replied + possible < this.k {
+            let replies = std::mem::take(&mut state.replies)
+                .into_iter()
+                .filter(|(proc, _)| pids.contains(proc))
+                .collect();
+            return Poll::Ready(if replied >= this.k {
+                Ok(replies)
+            } else {
+                Err(replies)
+            });
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// Tasks of several processes, e.g. requests made to them.
+pub struct Join<T> {
+    handles: Vec<(ProcessId, JoinHandle<T>)>,
+}
+
+/// Waits for tasks, labeled with processes which they talk to.
+pub fn join<T>(handles: impl IntoIterator<Item = (ProcessId, JoinHandle<T>)>) -> Join<T> {
+    Join {
+        handles: handles.into_iter().collect(),
+    }
+}
+
+impl<T> Join<T> {
+    /// Waits for `k` tasks, returning their results in the order of completion.
+    /// Returns results of all tasks if there are less than `k` of them.
+    pub fn await_k(self, k: usize) -> AwaitJoin<T> {
+        AwaitJoin {
+            handles: self.handles,
+            k,
+            done: Vec::new(),
+        }
+    }
+}
+
+/// Future returned by [`Join::await_k`].
+pub struct AwaitJoin<T> {
+    handles: Vec<(ProcessId, JoinHandle<T>)>,
+    k: usize,
+    done: Vec<(ProcessId, T)>,
+}
+
+/// Results are never pinned.
+impl<T> Unpin for AwaitJoin<T> {}
+
+impl<T> Future for AwaitJoin<T> {
+    type Output = Vec<(ProcessId, T)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let ready = take_ready(&mut this.handles, cx);
+        this.done.extend(ready);
+        if this.done.len() >= this.k || this.handles.is_empty() {
+            Poll::Ready(std::mem::take(&mut this.done))
+        } else {
+            Poll::Pending
+        }
+    }
+}
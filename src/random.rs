@@ -0,0 +1,75 @@
+use std::ops::Range;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{error::Error, runtime::NodeHandle, system::SystemHandle};
+
+/// Random number generator of the system.
+/// All draws are made from it, so runs with the same seed are reproducible.
+pub(crate) struct SeededRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl SeededRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub(crate) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub(crate) fn draw(&mut self, range: Option<Range<u64>>) -> u64 {
+        match range {
+            Some(range) => self.rng.gen_range(range),
+            None => self.rng.gen(),
+        }
+    }
+
+    /// Draws number from `[0, 1)`.
+    pub(crate) fn draw_unit(&mut self) -> f64 {
+        self.rng.gen()
+    }
+}
+
+impl Default for SeededRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Returns random number drawn from the seeded generator of the system.
+/// In the [`crate::runtime`] generator is seeded from the OS.
+pub fn random() -> u64 {
+    try_random().unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Returns random number from the provided range,
+/// drawn from the seeded generator of the system.
+///
+/// Panics if range is empty.
+pub fn random_range(range: Range<u64>) -> u64 {
+    try_random_range(range).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Same as [`random`], but returns error if called outside of the process.
+pub fn try_random() -> Result<u64, Error> {
+    match NodeHandle::current() {
+        Some(node) => Ok(node.random(None)),
+        None => Ok(SystemHandle::current()?.random(None)),
+    }
+}
+
+/// Same as [`random_range`], but returns error if called outside of the process.
+///
+/// Still panics if range is empty.
+pub fn try_random_range(range: Range<u64>) -> Result<u64, Error> {
+    match NodeHandle::current() {
+        Some(node) => Ok(node.random(Some(range))),
+        None => Ok(SystemHandle::current()?.random(Some(range))),
+    }
+}
@@ -0,0 +1,120 @@
+//! Sending which retries until the message is acknowledged.
+//!
+//! Every attempt is a new message sent with [`crate::send`],
+//! so the receiver can get the message several times and must handle it idempotently.
+//! In the [`crate::System`] this can be checked with [`crate::System::duplicate_pending_event`].
+
+use crate::{
+    ack::AckOutcome,
+    error::Error,
+    process::ProcessId,
+    random::try_random,
+    runtime::NodeHandle,
+    system::SystemHandle,
+    time::{try_now, try_sleep},
+};
+
+/// When and how many times [`send`] retries the message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Time to wait for the ack of the first attempt.
+    pub timeout: f64,
+    /// Factor by which the timeout grows after every attempt.
+    pub backoff: f64,
+    pub max_timeout: f64,
+    /// Timeout of every attempt is increased by up to this fraction, drawn with [`crate::random`].
+    pub jitter: f64,
+    pub max_attempts: usize,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: 1.0,
+            backoff: 2.0,
+            max_timeout: f64::INFINITY,
+            jitter: 0.0,
+            max_attempts: 5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(timeout: f64) -> Self {
+        Self {
+            timeout,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_backoff(mut self, backoff: f64) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn with_max_timeout(mut self, max_timeout: f64) -> Self {
+        self.max_timeout = max_timeout;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        assert!(max_attempts > 0, "at least one attempt must be made");
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    fn jittered(&self, timeout: f64) -> Result<f64, Error> {
+        if self.jitter <= 0.0 {
+            return Ok(timeout);
+        }
+        let unit = try_random()? as f64 / (u64::MAX as f64 + 1.0);
+        Ok(timeout * (1.0 + self.jitter * unit))
+    }
+}
+
+/// Sends message until it is acknowledged or attempts of the policy are exhausted.
+///
+/// Lost attempt is retried after its timeout, like the one which was not acknowledged.
+/// Retries are traced as [`crate::EventKind::MessageRetried`].
+/// Returns [`AckOutcome::Delivered`] or [`AckOutcome::TimedOut`] after the last attempt,
+/// and stops early with the outcome if the receiver or the sender crashed.
+pub async fn send(to: ProcessId, msg: String, policy: RetryPolicy) -> AckOutcome {
+    try_send(to, msg, policy)
+        .await
+        .unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Same as [`send`], but returns error if called outside of the process,
+/// if there is no receiver or no link to it.
+pub async fn try_send(
+    to: ProcessId,
+    msg: String,
+    policy: RetryPolicy,
+) -> Result<AckOutcome, Error> {
+    let mut timeout = policy.timeout;
+    let mut first = None;
+    for attempt in 1..=policy.max_attempts {
+        if let Some(msg_id) = first {
+            if NodeHandle::current().is_none() {
+                SystemHandle::current()?.retry(to, msg_id, attempt)?;
+            }
+        }
+        let ack = crate::send::try_send(to, msg.clone())?;
+        first.get_or_insert(ack.msg_id());
+
+        let wait = policy.jittered(timeout)?;
+        let deadline = try_now()? + wait;
+        match ack.timeout(wait).await {
+            AckOutcome::Lost => try_sleep(deadline - try_now()?)?.await,
+            AckOutcome::TimedOut => {}
+            outcome => return Ok(outcome),
+        }
+        timeout = (timeout * policy.backoff).min(policy.max_timeout);
+    }
+    Ok(AckOutcome::TimedOut)
+}
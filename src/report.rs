@@ -0,0 +1,53 @@
+use std::collections::HashSet;
+
+use crate::{
+    event::{Event, EventKind},
+    process::ProcessId,
+};
+
+/// Processes of the run, as seen by checkers which read reports from the trace.
+pub(crate) struct Run {
+    pub(crate) processes: usize,
+    pub(crate) crashed: HashSet<ProcessId>,
+}
+
+impl Run {
+    /// Reads the trace of the system with the number of processes,
+    /// so processes which made no events are counted too.
+    ///
+    /// Every report made by a process as local message is passed
+    /// to `on_report` split into the first word and the rest,
+    /// together with the run as it is at the time of the report.
+    pub(crate) fn read<F>(
+        trace: &[Event],
+        processes: usize,
+        mut on_report: F,
+    ) -> Result<Self, String>
+    where
+        F: FnMut(&Run, ProcessId, &str, &str) -> Result<(), String>,
+    {
+        let mut run = Run {
+            processes,
+            crashed: HashSet::new(),
+        };
+        for event in trace {
+            run.processes = run.processes.max(event.kind.process() + 1);
+            match &event.kind {
+                EventKind::ProcessCrashed(proc) => {
+                    run.crashed.insert(*proc);
+                }
+                EventKind::ProcLocalMessage(proc, msg) => {
+                    if let Some((report, rest)) = msg.split_once(' ') {
+                        on_report(&run, *proc, report, rest)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(run)
+    }
+
+    pub(crate) fn correct(&self) -> impl Iterator<Item = ProcessId> + '_ {
+        (0..self.processes).filter(|proc| !self.crashed.contains(proc))
+    }
+}
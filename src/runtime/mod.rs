@@ -0,0 +1,223 @@
+//! Runtime which runs [`Process`] implementations on real OS threads,
+//! exchanging messages over UDP sockets.
+//!
+//! Inside the process [`crate::send`], [`crate::send_local`] and [`crate::spawn`]
+//! work the same way as in the [`crate::System`],
+//! and [`crate::AckHandle`] is resolved when the receiver acknowledges the message.
+//! Every process has its own thread with single-threaded executor.
+
+pub(crate) mod node;
+mod udp;
+
+use std::{
+    io,
+    net::{SocketAddr, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Sender},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::process::{Process, ProcessId};
+
+pub(crate) use node::NodeHandle;
+use node::{Input, Node};
+use udp::UdpTransport;
+
+/// Local messages sent by the process, waiting to be read by the user.
+#[derive(Default)]
+pub(crate) struct Mailbox {
+    messages: Mutex<Vec<String>>,
+    ready: Condvar,
+}
+
+impl Mailbox {
+    pub(crate) fn put(&self, msg: String) {
+        self.messages.lock().unwrap().push(msg);
+        self.ready.notify_all();
+    }
+
+    fn take(&self, count: usize, timeout: Duration) -> Vec<String> {
+        let deadline = Instant::now() + timeout;
+        let mut messages = self.messages.lock().unwrap();
+        while messages.len() < count {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            messages = self.ready.wait_timeout(messages, deadline - now).unwrap().0;
+        }
+        std::mem::take(&mut *messages)
+    }
+}
+
+type ProcessFactory = Box<dyn FnOnce() -> Box<dyn Process> + Send>;
+
+enum Peer {
+    Local(UdpSocket, ProcessFactory),
+    Remote(SocketAddr),
+}
+
+/// Describes processes of the runtime before it is started.
+///
+/// Processes get identifiers in the order they are added,
+/// so every machine running a part of the system
+/// must add all processes in the same order.
+#[derive(Default)]
+pub struct Runtime {
+    peers: Vec<Peer>,
+}
+
+impl Runtime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds process which will run in this runtime.
+    ///
+    /// Socket is bound immediately, so port `0` can be used
+    /// to pick a free one, see [`RuntimeHandle::addr`].
+    /// Process is created by `factory` on its own thread.
+    pub fn add_process<P, F>(&mut self, addr: SocketAddr, factory: F) -> io::Result<ProcessId>
+    where
+        P: Process + 'static,
+        F: FnOnce() -> P + Send + 'static,
+    {
+        let socket = UdpSocket::bind(addr)?;
+        let id = self.peers.len();
+        self.peers.push(Peer::Local(
+            socket,
+            Box::new(move || Box::new(factory()) as Box<dyn Process>),
+        ));
+        Ok(id)
+    }
+
+    /// Adds process which runs somewhere else and listens on `addr`.
+    pub fn add_remote(&mut self, addr: SocketAddr) -> ProcessId {
+        let id = self.peers.len();
+        self.peers.push(Peer::Remote(addr));
+        id
+    }
+
+    /// Starts all local processes.
+    pub fn start(self) -> io::Result<RuntimeHandle> {
+        let addrs = self
+            .peers
+            .iter()
+            .map(|peer| match peer {
+                Peer::Local(socket, _) => socket.local_addr(),
+                Peer::Remote(addr) => Ok(*addr),
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        let addrs = Arc::new(addrs);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut handle = RuntimeHandle {
+            addrs: addrs.clone(),
+            inboxes: Vec::new(),
+            mailboxes: Vec::new(),
+            threads: Vec::new(),
+            stop: stop.clone(),
+        };
+        for (me, peer) in self.peers.into_iter().enumerate() {
+            let Peer::Local(socket, factory) = peer else {
+                handle.inboxes.push(None);
+                handle.mailboxes.push(None);
+                continue;
+            };
+            let (sender, inbox) = channel();
+            let mailbox = Arc::new(Mailbox::default());
+            let transport = UdpTransport {
+                me,
+                socket: socket.try_clone()?,
+                peers: addrs.clone(),
+                mailbox: mailbox.clone(),
+            };
+
+            let receiver = sender.clone();
+            let stop = stop.clone();
+            handle.threads.push(thread::spawn(move || {
+                udp::receive(socket, receiver, stop).expect("failed to receive datagram");
+            }));
+            handle.threads.push(thread::spawn(move || {
+                Node::new(me, factory(), Box::new(transport)).run(inbox);
+            }));
+            handle.inboxes.push(Some(sender));
+            handle.mailboxes.push(Some(mailbox));
+        }
+        Ok(handle)
+    }
+}
+
+/// Handle of the started [`Runtime`].
+/// Runtime is stopped when the handle is dropped.
+pub struct RuntimeHandle {
+    addrs: Arc<Vec<SocketAddr>>,
+    inboxes: Vec<Option<Sender<Input>>>,
+    mailboxes: Vec<Option<Arc<Mailbox>>>,
+    threads: Vec<thread::JoinHandle<()>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl RuntimeHandle {
+    /// Returns address on which the process listens.
+    pub fn addr(&self, proc: ProcessId) -> SocketAddr {
+        self.addrs[proc]
+    }
+
+    fn mailbox(&self, proc: ProcessId) -> &Mailbox {
+        self.mailboxes
+            .get(proc)
+            .and_then(Option::as_deref)
+            .unwrap_or_else(|| panic!("process {proc} is not running in this runtime"))
+    }
+
+    pub fn send_local_message(&self, to: ProcessId, msg: &str) {
+        self.inboxes
+            .get(to)
+            .and_then(Option::as_ref)
+            .unwrap_or_else(|| panic!("process {to} is not running in this runtime"))
+            .send(Input::Local(msg.to_string()))
+            .expect("process is stopped");
+    }
+
+    pub fn read_local(&self, proc: ProcessId) -> Vec<String> {
+        self.mailbox(proc).take(0, Duration::ZERO)
+    }
+
+    /// Waits until process sends at least `count` local messages or `timeout` expires,
+    /// and returns all sent messages.
+    pub fn wait_local(&self, proc: ProcessId, count: usize, timeout: Duration) -> Vec<String> {
+        self.mailbox(proc).take(count, timeout)
+    }
+
+    /// Returns `true` if all threads stopped without panic.
+    fn stop(&mut self) -> bool {
+        self.stop.store(true, Ordering::Relaxed);
+        for inbox in self.inboxes.iter().flatten() {
+            let _ = inbox.send(Input::Stop);
+        }
+        let joined: Vec<_> = self
+            .threads
+            .drain(..)
+            .map(|thread| thread.join().is_ok())
+            .collect();
+        joined.into_iter().all(|ok| ok)
+    }
+
+    /// Stops all processes and waits for their threads.
+    ///
+    /// Panics if some process panicked.
+    pub fn shutdown(mut self) {
+        assert!(self.stop(), "process panicked");
+    }
+}
+
+impl Drop for RuntimeHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
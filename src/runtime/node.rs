@@ -0,0 +1,311 @@
+use std::{
+    cell::RefCell,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    ops::Range,
+    rc::{Rc, Weak},
+    sync::{
+        mpsc::{Receiver, RecvTimeoutError},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use futures::{
+    task::{waker, ArcWake},
+    Future,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    ack::{AckHandle, AckOutcome},
+    error::Error,
+    event::MessageId,
+    join::JoinHandle,
+    process::{Process, ProcessId},
+    shared::SharedState,
+    task::{Task, TaskId},
+    time::{Sleep, TimerId},
+};
+
+/// Input of the node, produced by the transport and by the user.
+pub(crate) enum Input {
+    Message {
+        from: ProcessId,
+        msg_id: MessageId,
+        msg: String,
+    },
+    Ack {
+        msg_id: MessageId,
+    },
+    Local(String),
+    Stop,
+}
+
+/// Delivers outputs of the node to the outside world.
+pub(crate) trait Transport {
+    fn send(&self, to: ProcessId, msg_id: MessageId, msg: &str);
+
+    /// Acknowledges message which was received from the process `to`.
+    fn ack(&self, to: ProcessId, msg_id: MessageId);
+
+    /// Handles message sent with [`crate::send_local`].
+    fn local(&self, msg: String);
+
+    /// Returns number of processes which can receive messages.
+    fn processes(&self) -> usize;
+}
+
+/// State of the node, shared between its wakers and [`NodeHandle`]s.
+struct NodeState {
+    me: ProcessId,
+    transport: Box<dyn Transport>,
+    pending_tasks: VecDeque<TaskId>,
+    next_task_id: TaskId,
+    tasks: HashMap<TaskId, Task>,
+    next_msg_id: MessageId,
+    waiting_ack: HashMap<MessageId, Weak<RefCell<SharedState<AckOutcome>>>>,
+    rng: StdRng,
+    start: Instant,
+    next_timer_id: TimerId,
+    timers: BinaryHeap<Reverse<(Instant, TimerId)>>,
+    waiting_timer: HashMap<TimerId, Weak<RefCell<SharedState<()>>>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct NodeHandle(Weak<RefCell<NodeState>>);
+
+thread_local! {
+    static NODE_HANDLE: RefCell<Option<NodeHandle>> = const { RefCell::new(None) };
+}
+
+impl NodeHandle {
+    /// Returns handle of the node running on the current thread, if any.
+    pub(crate) fn current() -> Option<Self> {
+        NODE_HANDLE.with(|h| h.borrow().clone())
+    }
+
+    fn upgrade(&self) -> Rc<RefCell<NodeState>> {
+        self.0.upgrade().expect("node is not available")
+    }
+
+    fn schedule(&self, task_id: TaskId) {
+        self.upgrade().borrow_mut().pending_tasks.push_back(task_id);
+    }
+
+    pub(crate) fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        let this = self.upgrade();
+        let mut state = this.borrow_mut();
+        let (handle, task) = Task::from_future(state.me, future);
+        let id = state.next_task_id;
+        state.next_task_id += 1;
+        state.tasks.insert(id, task);
+        state.pending_tasks.push_back(id);
+        handle
+    }
+
+    pub(crate) fn send(&self, to: ProcessId, msg: String) -> Result<AckHandle, Error> {
+        let this = self.upgrade();
+        let mut state = this.borrow_mut();
+        if to >= state.transport.processes() {
+            return Err(Error::NoSuchProcess(to));
+        }
+
+        let flag = Rc::new(RefCell::new(SharedState::default()));
+        let msg_id = state.next_msg_id;
+        state.next_msg_id += 1;
+        state.waiting_ack.insert(msg_id, Rc::downgrade(&flag));
+        state.transport.send(to, msg_id, &msg);
+
+        Ok(AckHandle { flag, msg_id })
+    }
+
+    /// Sends message without waiting for the ack, which is ignored when it arrives.
+    pub(crate) fn send_unacked(&self, to: ProcessId, msg: String) -> Result<(), Error> {
+        let this = self.upgrade();
+        let mut state = this.borrow_mut();
+        if to >= state.transport.processes() {
+            return Err(Error::NoSuchProcess(to));
+        }
+
+        let msg_id = state.next_msg_id;
+        state.next_msg_id += 1;
+        state.transport.send(to, msg_id, &msg);
+        Ok(())
+    }
+
+    pub(crate) fn send_local(&self, msg: String) {
+        self.upgrade().borrow().transport.local(msg);
+    }
+
+    pub(crate) fn now(&self) -> f64 {
+        self.upgrade().borrow().start.elapsed().as_secs_f64()
+    }
+
+    pub(crate) fn sleep(&self, duration: f64) -> Sleep {
+        let this = self.upgrade();
+        let mut state = this.borrow_mut();
+        let flag = Rc::new(RefCell::new(SharedState::default()));
+        let timer_id = state.next_timer_id;
+        state.next_timer_id += 1;
+        let deadline = Instant::now() + Duration::from_secs_f64(duration.max(0.0));
+        state.timers.push(Reverse((deadline, timer_id)));
+        state.waiting_timer.insert(timer_id, Rc::downgrade(&flag));
+        Sleep { flag, timer: None }
+    }
+
+    pub(crate) fn random(&self, range: Option<Range<u64>>) -> u64 {
+        let this = self.upgrade();
+        let mut state = this.borrow_mut();
+        match range {
+            Some(range) => state.rng.gen_range(range),
+            None => state.rng.gen(),
+        }
+    }
+}
+
+struct NodeWaker {
+    node: NodeHandle,
+    task_id: TaskId,
+}
+
+// Node tasks are polled and woken only on the thread of the node.
+unsafe impl Sync for NodeWaker {}
+unsafe impl Send for NodeWaker {}
+
+impl ArcWake for NodeWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.node.schedule(arc_self.task_id);
+    }
+}
+
+/// Runs one process with real executor on the current thread.
+pub(crate) struct Node {
+    state: Rc<RefCell<NodeState>>,
+    process: Box<dyn Process>,
+}
+
+impl Node {
+    pub(crate) fn new(
+        me: ProcessId,
+        process: Box<dyn Process>,
+        transport: Box<dyn Transport>,
+    ) -> Self {
+        let state = NodeState {
+            me,
+            transport,
+            pending_tasks: VecDeque::new(),
+            next_task_id: 0,
+            tasks: HashMap::new(),
+            next_msg_id: 0,
+            waiting_ack: HashMap::new(),
+            rng: StdRng::from_entropy(),
+            start: Instant::now(),
+            next_timer_id: 0,
+            timers: BinaryHeap::new(),
+            waiting_timer: HashMap::new(),
+        };
+        Self {
+            state: Rc::new(RefCell::new(state)),
+            process,
+        }
+    }
+
+    fn handle(&self) -> NodeHandle {
+        NodeHandle(Rc::downgrade(&self.state))
+    }
+
+    /// Handles inputs and fires timers
+    /// until [`Input::Stop`] is received or all senders are dropped.
+    pub(crate) fn run(mut self, inbox: Receiver<Input>) {
+        NODE_HANDLE.with(|h| *h.borrow_mut() = Some(self.handle()));
+        loop {
+            let deadline = self.state.borrow().timers.peek().map(|timer| timer.0 .0);
+            let input = match deadline {
+                Some(deadline) => {
+                    match inbox.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                        Ok(input) => Some(input),
+                        Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                None => match inbox.recv() {
+                    Ok(input) => Some(input),
+                    Err(_) => break,
+                },
+            };
+            if let Some(input) = input {
+                if !self.on_input(input) {
+                    break;
+                }
+            }
+            self.fire_timers();
+        }
+        NODE_HANDLE.with(|h| *h.borrow_mut() = None);
+    }
+
+    fn fire_timers(&mut self) {
+        let now = Instant::now();
+        loop {
+            let waiter = {
+                let mut state = self.state.borrow_mut();
+                match state.timers.peek() {
+                    Some(Reverse((deadline, _))) if *deadline <= now => {}
+                    _ => break,
+                }
+                let Reverse((_, timer_id)) = state.timers.pop().unwrap();
+                state.waiting_timer.remove(&timer_id)
+            };
+            if let Some(waiter) = waiter.and_then(|waiter| waiter.upgrade()) {
+                waiter.borrow_mut().put(());
+            }
+        }
+        self.process_pending_tasks();
+    }
+
+    /// Returns `false` if node must stop.
+    fn on_input(&mut self, input: Input) -> bool {
+        match input {
+            Input::Message { from, msg_id, msg } => {
+                self.state.borrow().transport.ack(from, msg_id);
+                self.process.on_message(from, msg);
+            }
+            Input::Ack { msg_id } => {
+                let waiter = self.state.borrow_mut().waiting_ack.remove(&msg_id);
+                if let Some(waiter) = waiter.and_then(|waiter| waiter.upgrade()) {
+                    waiter.borrow_mut().put(AckOutcome::Delivered);
+                }
+            }
+            Input::Local(msg) => self.process.on_local_message(&msg),
+            Input::Stop => return false,
+        }
+        self.process_pending_tasks();
+        true
+    }
+
+    fn process_pending_tasks(&mut self) {
+        loop {
+            let (task_id, mut task) = {
+                let mut state = self.state.borrow_mut();
+                let Some(task_id) = state.pending_tasks.pop_front() else {
+                    return;
+                };
+                let Some(task) = state.tasks.remove(&task_id) else {
+                    continue;
+                };
+                (task_id, task)
+            };
+            let waker = waker(Arc::new(NodeWaker {
+                node: self.handle(),
+                task_id,
+            }));
+            let mut ctx = std::task::Context::from_waker(&waker);
+            if task.future().as_mut().poll(&mut ctx).is_pending() {
+                self.state.borrow_mut().tasks.insert(task_id, task);
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    io,
+    net::{SocketAddr, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{event::MessageId, process::ProcessId};
+
+use super::{
+    node::{Input, Transport},
+    Mailbox,
+};
+
+/// How often receiver checks if runtime is stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Transport which sends every message and ack in its own UDP datagram.
+///
+/// Datagram starts with the header line `M <from> <msg_id>` for messages
+/// or `A <from> <msg_id>` for acks, and message follows the header.
+pub(crate) struct UdpTransport {
+    pub(crate) me: ProcessId,
+    pub(crate) socket: UdpSocket,
+    pub(crate) peers: Arc<Vec<SocketAddr>>,
+    pub(crate) mailbox: Arc<Mailbox>,
+}
+
+impl UdpTransport {
+    fn send_datagram(&self, to: ProcessId, datagram: &[u8]) {
+        let addr = self
+            .peers
+            .get(to)
+            .unwrap_or_else(|| panic!("trying to send message to unknown process {to}"));
+        // Datagrams are unreliable anyway, so failed sending is the same as loss.
+        let _ = self.socket.send_to(datagram, addr);
+    }
+}
+
+impl Transport for UdpTransport {
+    fn processes(&self) -> usize {
+        self.peers.len()
+    }
+
+    fn send(&self, to: ProcessId, msg_id: MessageId, msg: &str) {
+        let datagram = format!("M {} {msg_id}\n{msg}", self.me);
+        self.send_datagram(to, datagram.as_bytes());
+    }
+
+    fn ack(&self, to: ProcessId, msg_id: MessageId) {
+        let datagram = format!("A {} {msg_id}\n", self.me);
+        self.send_datagram(to, datagram.as_bytes());
+    }
+
+    fn local(&self, msg: String) {
+        self.mailbox.put(msg);
+    }
+}
+
+fn parse_datagram(datagram: &[u8]) -> Option<Input> {
+    let datagram = std::str::from_utf8(datagram).ok()?;
+    let (header, msg) = datagram.split_once('\n')?;
+    let mut parts = header.split(' ');
+    let kind = parts.next()?;
+    let from = parts.next()?.parse().ok()?;
+    let msg_id = parts.next()?.parse().ok()?;
+    match kind {
+        "M" => Some(Input::Message {
+            from,
+            msg_id,
+            msg: msg.to_string(),
+        }),
+        "A" => Some(Input::Ack { msg_id }),
+        _ => None,
+    }
+}
+
+/// Receives datagrams from the socket and passes them to the node
+/// until `stop` is set or node is gone.
+/// Malformed datagrams are ignored.
+pub(crate) fn receive(
+    socket: UdpSocket,
+    inbox: Sender<Input>,
+    stop: Arc<AtomicBool>,
+) -> io::Result<()> {
+    socket.set_read_timeout(Some(POLL_INTERVAL))?;
+    let mut buf = vec![0; 65536];
+    while !stop.load(Ordering::Relaxed) {
+        let len = match socket.recv_from(&mut buf) {
+            Ok((len, _)) => len,
+            Err(err)
+                if err.kind() == io::ErrorKind::WouldBlock
+                    || err.kind() == io::ErrorKind::TimedOut =>
+            {
+                continue
+            }
+            Err(err) => return Err(err),
+        };
+        if let Some(input) = parse_datagram(&buf[..len]) {
+            if inbox.send(input).is_err() {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,240 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    event::EventKind,
+    json::Value,
+    jsonl::{kind_from_json, kind_to_json, read_jsonl, write_jsonl, TraceReadError},
+    process::ProcessId,
+    system::System,
+};
+
+const SCHEDULE_FORMAT: &str = "flurry-schedule";
+
+/// Version of the schedule format produced by [`Schedule::write`].
+///
+/// Version 2 added clock jumps, duplicated events and events of timers, clocks and retries.
+/// Schedules of older versions are still read.
+pub const SCHEDULE_FORMAT_VERSION: u64 = 2;
+
+/// One step of the system run, recorded in the [`Schedule`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleStep {
+    /// Seed of the random number generator of the system.
+    /// Always the first step of the schedule.
+    Seed(u64),
+    /// Local message was sent to the process with [`System::send_local_message`].
+    LocalMessage(ProcessId, String),
+    /// Pending event with index was applied with [`System::apply_pending_event`].
+    ApplyEvent(usize, EventKind),
+    /// Pending event with index was dropped with [`System::drop_pending_event`].
+    DropEvent(usize, EventKind),
+    /// Pending event with index was duplicated with [`System::duplicate_pending_event`].
+    DuplicateEvent(usize, EventKind),
+    /// Process was crashed with [`System::crash_process`].
+    Crash(ProcessId),
+    /// Value was drawn from the random number generator of the system.
+    Random(u64),
+    /// Jump of the local clock was scheduled with [`System::jump_clock`].
+    ClockJump(ProcessId, f64, f64),
+}
+
+/// Exact sequence of inputs, faults and random draws of the system run,
+/// which can be written to the file and replayed with [`replay`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schedule {
+    pub steps: Vec<ScheduleStep>,
+}
+
+fn step_to_json(step: &ScheduleStep) -> Value {
+    match step {
+        ScheduleStep::Seed(seed) => {
+            Value::object(vec![("step", "Seed".into()), ("seed", (*seed).into())])
+        }
+        ScheduleStep::LocalMessage(proc, msg) => Value::object(vec![
+            ("step", "LocalMessage".into()),
+            ("proc", (*proc).into()),
+            ("msg", msg.as_str().into()),
+        ]),
+        ScheduleStep::ApplyEvent(index, kind) => Value::object(vec![
+            ("step", "ApplyEvent".into()),
+            ("index", (*index).into()),
+            ("event", Value::object(kind_to_json(kind))),
+        ]),
+        ScheduleStep::DropEvent(index, kind) => Value::object(vec![
+            ("step", "DropEvent".into()),
+            ("index", (*index).into()),
+            ("event", Value::object(kind_to_json(kind))),
+        ]),
+        ScheduleStep::DuplicateEvent(index, kind) => Value::object(vec![
+            ("step", "DuplicateEvent".into()),
+            ("index", (*index).into()),
+            ("event", Value::object(kind_to_json(kind))),
+        ]),
+        ScheduleStep::Crash(proc) => {
+            Value::object(vec![("step", "Crash".into()), ("proc", (*proc).into())])
+        }
+        ScheduleStep::Random(value) => {
+            Value::object(vec![("step", "Random".into()), ("value", (*value).into())])
+        }
+        ScheduleStep::ClockJump(proc, at, delta) => Value::object(vec![
+            ("step", "ClockJump".into()),
+            ("proc", (*proc).into()),
+            ("at", (*at).into()),
+            ("delta", (*delta).into()),
+        ]),
+    }
+}
+
+fn step_from_json(value: &Value) -> Result<ScheduleStep, String> {
+    let field = |key: &str| value.get(key).ok_or(format!("missing field '{key}'"));
+    let number = |key: &str| {
+        field(key)?
+            .as_u64()
+            .ok_or(format!("field '{key}' must be a non-negative integer"))
+    };
+    let float = |key: &str| {
+        field(key)?
+            .as_f64()
+            .ok_or(format!("field '{key}' must be a number"))
+    };
+    let step = field("step")?
+        .as_str()
+        .ok_or("field 'step' must be a string")?;
+    match step {
+        "Seed" => Ok(ScheduleStep::Seed(number("seed")?)),
+        "LocalMessage" => Ok(ScheduleStep::LocalMessage(
+            number("proc")? as usize,
+            field("msg")?
+                .as_str()
+                .ok_or("field 'msg' must be a string")?
+                .to_string(),
+        )),
+        "ApplyEvent" => Ok(ScheduleStep::ApplyEvent(
+            number("index")? as usize,
+            kind_from_json(field("event")?)?,
+        )),
+        "DropEvent" => Ok(ScheduleStep::DropEvent(
+            number("index")? as usize,
+            kind_from_json(field("event")?)?,
+        )),
+        "DuplicateEvent" => Ok(ScheduleStep::DuplicateEvent(
+            number("index")? as usize,
+            kind_from_json(field("event")?)?,
+        )),
+        "Crash" => Ok(ScheduleStep::Crash(number("proc")? as usize)),
+        "Random" => Ok(ScheduleStep::Random(number("value")?)),
+        "ClockJump" => Ok(ScheduleStep::ClockJump(
+            number("proc")? as usize,
+            float("at")?,
+            float("delta")?,
+        )),
+        other => Err(format!("unknown step '{other}'")),
+    }
+}
+
+impl Schedule {
+    /// Writes schedule in the JSON lines format, one step per line.
+    pub fn write<W: Write>(&self, writer: W) -> io::Result<()> {
+        write_jsonl(
+            writer,
+            SCHEDULE_FORMAT,
+            SCHEDULE_FORMAT_VERSION,
+            self.steps.iter().map(step_to_json),
+        )
+    }
+
+    /// Reads schedule written by [`Schedule::write`].
+    pub fn read<R: BufRead>(reader: R) -> Result<Schedule, TraceReadError> {
+        let steps = read_jsonl(
+            reader,
+            SCHEDULE_FORMAT,
+            1..=SCHEDULE_FORMAT_VERSION,
+            step_from_json,
+        )?;
+        Ok(Schedule { steps })
+    }
+}
+
+/// First point where replayed run differs from the schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Index of the first differing step.
+    pub step: usize,
+    /// Step from the schedule, `None` if the run made more steps than recorded.
+    pub expected: Option<ScheduleStep>,
+    /// Step made by the run, `None` if the run could not make the expected step.
+    pub actual: Option<ScheduleStep>,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "run diverged from schedule at step {}: expected {:?}, got {:?}",
+            self.step, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+/// Re-executes the schedule on the system created by `factory`.
+///
+/// Factory must create the system in the same way as the recorded one,
+/// including seed and steps made before returning.
+/// Every recorded step is checked against the step made by the system,
+/// and the first mismatch is reported.
+/// On success the system in the final state is returned.
+#[allow(clippy::result_large_err)]
+pub fn replay<F>(schedule: &Schedule, factory: F) -> Result<System, Divergence>
+where
+    F: FnOnce() -> System,
+{
+    let mut system = factory();
+    let mut next = 0;
+    loop {
+        let recorded = system.recorded_steps(next);
+        for actual in recorded {
+            let expected = schedule.steps.get(next);
+            if expected != Some(&actual) {
+                return Err(Divergence {
+                    step: next,
+                    expected: expected.cloned(),
+                    actual: Some(actual),
+                });
+            }
+            next += 1;
+        }
+
+        let Some(step) = schedule.steps.get(next) else {
+            return Ok(system);
+        };
+        let missing = Divergence {
+            step: next,
+            expected: Some(step.clone()),
+            actual: None,
+        };
+        match step {
+            ScheduleStep::LocalMessage(proc, msg) => {
+                system.try_send_local_message(*proc, msg).or(Err(missing))?
+            }
+            ScheduleStep::ApplyEvent(index, _) => {
+                system.try_apply_pending_event(*index).or(Err(missing))?
+            }
+            ScheduleStep::DropEvent(index, _) => {
+                system.try_drop_pending_event(*index).or(Err(missing))?
+            }
+            ScheduleStep::DuplicateEvent(index, _) => system
+                .try_duplicate_pending_event(*index)
+                .or(Err(missing))?,
+            ScheduleStep::Crash(proc) => system.try_crash_process(*proc).or(Err(missing))?,
+            ScheduleStep::ClockJump(proc, at, delta) => {
+                system.try_jump_clock(*proc, *at, *delta).or(Err(missing))?;
+            }
+            ScheduleStep::Seed(_) | ScheduleStep::Random(_) => return Err(missing),
+        }
+    }
+}
@@ -1,9 +1,44 @@
-use crate::{ack::AckHandle, system::SystemHandle, ProcessId};
+use crate::{ack::AckHandle, error::Error, runtime::NodeHandle, system::SystemHandle, ProcessId};
 
 pub fn send_local(msg: String) {
-    SystemHandle::current().send_local(msg)
+    try_send_local(msg).unwrap_or_else(|err| panic!("{err}"))
 }
 
 pub fn send(to: ProcessId, msg: String) -> AckHandle {
-    SystemHandle::current().send(to, msg)
+    try_send(to, msg).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Sends message without acknowledgement, so there are no ack events
+/// and fewer orders of events for the [`crate::Explorer`].
+pub fn send_unacked(to: ProcessId, msg: String) {
+    try_send_unacked(to, msg).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Same as [`send_local`], but returns error if called outside of the process.
+pub fn try_send_local(msg: String) -> Result<(), Error> {
+    match NodeHandle::current() {
+        Some(node) => {
+            node.send_local(msg);
+            Ok(())
+        }
+        None => SystemHandle::current()?.send_local(msg),
+    }
+}
+
+/// Same as [`send`], but returns error if called outside of the process,
+/// if there is no receiver or no link to it.
+pub fn try_send(to: ProcessId, msg: String) -> Result<AckHandle, Error> {
+    match NodeHandle::current() {
+        Some(node) => node.send(to, msg),
+        None => SystemHandle::current()?.send(to, msg),
+    }
+}
+
+/// Same as [`send_unacked`], but returns error if called outside of the process,
+/// if there is no receiver or no link to it.
+pub fn try_send_unacked(to: ProcessId, msg: String) -> Result<(), Error> {
+    match NodeHandle::current() {
+        Some(node) => node.send_unacked(to, msg),
+        None => SystemHandle::current()?.send_unacked(to, msg),
+    }
 }
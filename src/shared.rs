@@ -1,5 +1,6 @@
 /// User must put the value exactly once and
-/// take the value no more than once.
+/// take the value no more than once,
+/// but can try to take it again while it is not put.
 
 #[derive(Default)]
 pub(crate) enum SharedState<T> {
@@ -11,13 +12,13 @@ pub(crate) enum SharedState<T> {
 
 impl<T> SharedState<T> {
     /// If value is already put, then value is returned.
-    /// Else, waker will be stored and called after value will be put.
+    /// Else, waker will be stored, replacing the previous one, and called after value will be put.
     pub(crate) fn take(&mut self, waker: std::task::Waker) -> Option<T> {
         let old = std::mem::replace(self, SharedState::Waiting(waker));
         match old {
             SharedState::Initial => None,
             SharedState::Ready(value) => Some(value),
-            SharedState::Waiting(_) => panic!("taking value twice is contract violation"),
+            SharedState::Waiting(_) => None,
         }
     }
 
@@ -0,0 +1,52 @@
+use std::io::{self, Write};
+
+use crate::{
+    event::{Event, EventKind},
+    json::Value,
+};
+
+/// Regular expression which must be given to ShiViz to parse logs
+/// written by [`write_shiviz`].
+pub const SHIVIZ_REGEX: &str = r"(?<event>.*)\n(?<host>\S*) (?<clock>{.*})";
+
+fn describe(kind: &EventKind) -> String {
+    let text = match kind {
+        EventKind::ProcLocalMessage(_, msg) => format!("local message: {msg}"),
+        EventKind::UserLocalMessage(_, msg) => format!("user message: {msg}"),
+        EventKind::MessageSent(_, to, msg_id, msg) => format!("send m{msg_id} to P{to}: {msg}"),
+        EventKind::MessageDelivered(from, _, msg_id, msg) => {
+            format!("receive m{msg_id} from P{from}: {msg}")
+        }
+        EventKind::AckSent(_, to, msg_id) => format!("send ack m{msg_id} to P{to}"),
+        EventKind::AckDelivered(from, _, msg_id) => format!("receive ack m{msg_id} from P{from}"),
+        EventKind::MessageDropped(from, _, msg_id) => format!("drop m{msg_id} from P{from}"),
+        EventKind::AckDropped(from, _, msg_id) => format!("drop ack m{msg_id} from P{from}"),
+        EventKind::ProcessCrashed(_) => "crash".to_string(),
+        EventKind::TimerSet(_, timer_id) => format!("set timer t{timer_id}"),
+        EventKind::TimerFired(_, timer_id) => format!("timer t{timer_id} fired"),
+        EventKind::ClockJumped(_, jump_id) => format!("clock jump j{jump_id}"),
+        EventKind::MessageRetried(_, to, msg_id, attempt) => {
+            format!("retry m{msg_id} to P{to}, attempt {attempt}")
+        }
+    };
+    text.replace(['\n', '\r'], " ")
+}
+
+/// Writes trace as a log which can be loaded into ShiViz with [`SHIVIZ_REGEX`].
+///
+/// Every event takes two lines: its description,
+/// and then the process name with the vector clock of the event.
+pub fn write_shiviz<W: Write>(trace: &[Event], mut writer: W) -> io::Result<()> {
+    for event in trace {
+        let clock = Value::Object(
+            event
+                .clock
+                .entries()
+                .map(|(proc, value)| (format!("P{proc}"), value.into()))
+                .collect(),
+        );
+        writeln!(writer, "{}", describe(&event.kind))?;
+        writeln!(writer, "P{} {clock}", event.kind.process())?;
+    }
+    writer.flush()
+}
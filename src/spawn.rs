@@ -1,10 +1,21 @@
 use futures::Future;
 
-use crate::{join::JoinHandle, system::SystemHandle};
+use crate::{error::Error, join::JoinHandle, runtime::NodeHandle, system::SystemHandle};
 
 pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
 where
     F: Future + 'static,
 {
-    SystemHandle::current().spawn(future)
+    try_spawn(future).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Same as [`spawn`], but returns error if called outside of the process.
+pub fn try_spawn<F>(future: F) -> Result<JoinHandle<F::Output>, Error>
+where
+    F: Future + 'static,
+{
+    match NodeHandle::current() {
+        Some(node) => Ok(node.spawn(future)),
+        None => SystemHandle::current()?.spawn(future),
+    }
 }
@@ -1,6 +1,7 @@
 use std::{
     cell::RefCell,
-    collections::{HashMap, VecDeque},
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    ops::Range,
     rc::{Rc, Weak},
     sync::Arc,
 };
@@ -8,15 +9,29 @@ use std::{
 use futures::{task::waker, Future};
 
 use crate::{
-    ack::AckHandle,
+    ack::{AckHandle, AckOutcome},
+    clock::Clocks,
+    delivery::Delivery,
+    error::Error,
     event::{Event, EventKind, MessageId},
     join::JoinHandle,
+    latency::Latency,
     process::{Process, ProcessId},
+    random::SeededRng,
+    schedule::{Schedule, ScheduleStep},
     shared::SharedState,
     task::{Task, TaskId},
+    time::{JumpId, LocalClock, Sleep, TimerId},
+    topology::Topology,
     waker::Waker,
 };
 
+type AckWaiter = Weak<RefCell<SharedState<AckOutcome>>>;
+
+/// Mixed into the seed of the system to get the seed of the latency generator,
+/// so latencies do not repeat numbers drawn by processes.
+const LATENCY_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
 /// Represents state of the system,
 /// which handles [`SystemHandle`] shared between wakers [`crate::waker::Waker`]
 /// and can be accessed by user indirectly using [`System`].
@@ -31,19 +46,176 @@ pub(crate) struct SystemState {
     /// is called now.
     current_process: Option<ProcessId>,
     local_messages: HashMap<ProcessId, Vec<String>>,
+    processes: usize,
     trace: Vec<Event>,
+    clocks: Clocks,
     time: f64,
     next_msg_id: MessageId,
     pending_events: Vec<EventKind>,
-    waiting_ack: HashMap<MessageId, Weak<RefCell<SharedState<bool>>>>,
+    /// Times at which pending events are due, in the same order.
+    pending_due: Vec<f64>,
+    /// Time is virtual and advanced by delivered events, not by steps.
+    timing: bool,
+    latency: Option<Latency>,
+    link_latency: HashMap<(ProcessId, ProcessId), Latency>,
+    topology: Option<Topology>,
+    delivery: Delivery,
+    link_delivery: HashMap<(ProcessId, ProcessId), Delivery>,
+    /// FIFO links which have pending message or ack.
+    busy_links: HashSet<(ProcessId, ProcessId)>,
+    /// Messages and acks waiting behind the pending one on FIFO links, with their due times.
+    held: HashMap<(ProcessId, ProcessId), VecDeque<(EventKind, f64)>>,
+    latency_rng: Option<SeededRng>,
+    next_timer_id: TimerId,
+    waiting_timer: HashMap<TimerId, Weak<RefCell<SharedState<()>>>>,
+    /// Local times at which pending timers fire.
+    timer_deadline: HashMap<TimerId, f64>,
+    local_clocks: HashMap<ProcessId, LocalClock>,
+    /// Scheduled jumps of local clocks, by [`JumpId`].
+    clock_jumps: Vec<f64>,
+    /// Senders of messages with ack waiters.
+    waiting_ack: HashMap<MessageId, (ProcessId, AckWaiter)>,
+    /// Messages sent with [`crate::send_unacked`], which are not acknowledged.
+    unacked: HashSet<MessageId>,
+    /// Paths of acknowledged messages in flight, over which their acks travel back.
+    ack_paths: HashMap<MessageId, Vec<ProcessId>>,
+    /// Flags of [`crate::fd::Perfect`] detectors, which are put on the next crash.
+    crash_watchers: Vec<Weak<RefCell<SharedState<()>>>>,
     processed_events: usize,
+    crashed: HashSet<ProcessId>,
+    rng: SeededRng,
+    /// Steps made after the seed was chosen.
+    schedule: Vec<ScheduleStep>,
+}
+
+impl SystemState {
+    fn push_event(&mut self, kind: EventKind) {
+        let clock = self.clocks.observe(&kind);
+        self.trace.push(Event {
+            time: self.time,
+            kind,
+            clock,
+        });
+    }
+
+    fn push_pending(&mut self, kind: EventKind, due: f64) {
+        self.pending_events.push(kind);
+        self.pending_due.push(due);
+    }
+
+    fn remove_pending(&mut self, event: usize) -> (EventKind, f64) {
+        let kind = self.pending_events.remove(event);
+        let due = self.pending_due.remove(event);
+        if let Some(link) = link(&kind).filter(|link| self.busy_links.contains(link)) {
+            match self.held.get_mut(&link).and_then(VecDeque::pop_front) {
+                Some((next, next_due)) => {
+                    let next_due = next_due.max(self.time);
+                    self.push_pending(next, next_due);
+                }
+                None => {
+                    self.busy_links.remove(&link);
+                }
+            }
+        }
+        (kind, due)
+    }
+
+    /// Removes waiter of the ack, returning it if the [`AckHandle`] is still alive.
+    fn take_ack_waiter(
+        &mut self,
+        msg_id: MessageId,
+    ) -> Option<Rc<RefCell<SharedState<AckOutcome>>>> {
+        self.waiting_ack.remove(&msg_id)?.1.upgrade()
+    }
+
+    /// Returns `true` if a copy of the message, or of its ack if `acks` is set,
+    /// is pending or held on the FIFO link.
+    fn in_flight(&self, msg_id: MessageId, acks: bool) -> bool {
+        let copy = |kind: &EventKind| match kind {
+            EventKind::MessageDelivered(_, _, id, _) => *id == msg_id,
+            EventKind::AckDelivered(_, _, id) => acks && *id == msg_id,
+            _ => false,
+        };
+        self.pending_events.iter().any(copy)
+            || self.held.values().flatten().any(|(kind, _)| copy(kind))
+    }
+
+    /// Forgets the message once no copy of it is in flight.
+    fn release(&mut self, msg_id: MessageId) {
+        if !self.in_flight(msg_id, false) {
+            self.ack_paths.remove(&msg_id);
+            self.unacked.remove(&msg_id);
+        }
+    }
+
+    fn link_delivery(&self, link: (ProcessId, ProcessId)) -> Delivery {
+        self.link_delivery
+            .get(&link)
+            .copied()
+            .unwrap_or(self.delivery)
+    }
+
+    /// Makes message or ack pending, unless it waits for the earlier one on the FIFO link.
+    fn push_in_flight(&mut self, kind: EventKind, due: f64) {
+        let link = link(&kind).expect("message or ack travels over the link");
+        if self.link_delivery(link).is_fifo() && !self.busy_links.insert(link) {
+            self.held.entry(link).or_default().push_back((kind, due));
+        } else {
+            self.push_pending(kind, due);
+        }
+    }
+
+    fn local_clock(&self, proc: ProcessId) -> LocalClock {
+        self.local_clocks.get(&proc).copied().unwrap_or_default()
+    }
+
+    /// Returns time when the timer set on the process with local deadline fires.
+    fn timer_due(&self, proc: ProcessId, deadline: f64) -> f64 {
+        self.local_clock(proc).global(deadline).max(self.time)
+    }
+
+    /// Returns processes on the path of the message, which must exist.
+    fn route(&self, from: ProcessId, to: ProcessId) -> Vec<ProcessId> {
+        match &self.topology {
+            Some(topology) => topology.route(from, to).expect("no route"),
+            None => vec![from, to],
+        }
+    }
+
+    /// Returns time when message sent now over the path will be delivered.
+    fn delivery_time(&mut self, path: &[ProcessId]) -> f64 {
+        let mut time = self.time;
+        for link in path.windows(2) {
+            let latency = self
+                .link_latency
+                .get(&(link[0], link[1]))
+                .or(self.latency.as_ref());
+            if let Some(latency) = latency {
+                let seed = self.rng.seed() ^ LATENCY_SEED;
+                let rng = self.latency_rng.get_or_insert_with(|| SeededRng::new(seed));
+                time += latency.sample(rng);
+            }
+        }
+        time
+    }
 }
 
 #[derive(Clone)]
 pub(crate) struct SystemHandle(Weak<RefCell<SystemState>>);
 
 thread_local! {
-    static SYSTEM_HANDLE: RefCell<Option<SystemHandle>> = RefCell::new(None);
+    static SYSTEM_HANDLE: RefCell<Option<SystemHandle>> = const { RefCell::new(None) };
+}
+
+/// Restores system which was current before the step.
+struct HandleGuard {
+    previous: Option<SystemHandle>,
+}
+
+impl Drop for HandleGuard {
+    fn drop(&mut self) {
+        SYSTEM_HANDLE.with(|h| *h.borrow_mut() = self.previous.take());
+    }
 }
 
 impl SystemHandle {
@@ -51,8 +223,8 @@ impl SystemHandle {
         self.upgrade().borrow().processed_events
     }
 
-    pub(crate) fn current() -> Self {
-        SYSTEM_HANDLE.with(|h| h.borrow().as_ref().expect("no system available").clone())
+    pub(crate) fn current() -> Result<Self, Error> {
+        SYSTEM_HANDLE.with(|h| h.borrow().clone().ok_or(Error::NoProcessContext))
     }
 
     fn upgrade(&self) -> Rc<RefCell<SystemState>> {
@@ -60,97 +232,205 @@ impl SystemHandle {
     }
 
     pub(crate) fn add_event_kind(&mut self, event_kind: EventKind) {
+        self.upgrade().borrow_mut().push_event(event_kind);
+    }
+
+    pub(crate) fn random(&self, range: Option<Range<u64>>) -> u64 {
         let this = self.upgrade();
         let mut state = this.borrow_mut();
-        let time = state.time;
-        state.trace.push(Event {
-            time,
-            kind: event_kind,
-        });
+        let value = state.rng.draw(range);
+        state.schedule.push(ScheduleStep::Random(value));
+        value
     }
 
     pub(crate) fn inc_time(&mut self) {
-        self.upgrade().borrow_mut().time += 1.0;
+        let this = self.upgrade();
+        let mut state = this.borrow_mut();
+        if !state.timing {
+            state.time += 1.0;
+        }
+    }
+
+    /// Returns local time of the current process, or time of the system outside of processes.
+    pub(crate) fn now(&self) -> f64 {
+        let this = self.upgrade();
+        let state = this.borrow();
+        match state.current_process {
+            Some(proc) => state.local_clock(proc).local(state.time),
+            None => state.time,
+        }
+    }
+
+    pub(crate) fn sleep(&self, duration: f64) -> Result<Sleep, Error> {
+        let this = self.upgrade();
+        let mut state = this.borrow_mut();
+        let proc = state.current_process.ok_or(Error::NoProcessContext)?;
+
+        let flag = Rc::new(RefCell::new(SharedState::default()));
+        let timer_id = state.next_timer_id;
+        state.next_timer_id += 1;
+        state.waiting_timer.insert(timer_id, Rc::downgrade(&flag));
+
+        state.push_event(EventKind::TimerSet(proc, timer_id));
+        let deadline = state.local_clock(proc).local(state.time) + duration.max(0.0);
+        state.timer_deadline.insert(timer_id, deadline);
+        let due = state.timer_due(proc, deadline);
+        state.push_pending(EventKind::TimerFired(proc, timer_id), due);
+
+        Ok(Sleep {
+            flag,
+            timer: Some((self.clone(), timer_id)),
+        })
+    }
+
+    /// Removes timer which has not fired yet.
+    /// Does nothing if the system is dropped or is in use, e.g. when tasks are dropped with it.
+    pub(crate) fn cancel_timer(&self, timer_id: TimerId) {
+        let Some(this) = self.0.upgrade() else {
+            return;
+        };
+        let Ok(mut state) = this.try_borrow_mut() else {
+            return;
+        };
+        if state.waiting_timer.remove(&timer_id).is_none() {
+            return;
+        }
+        state.timer_deadline.remove(&timer_id);
+        let pending = state
+            .pending_events
+            .iter()
+            .position(|kind| matches!(kind, EventKind::TimerFired(_, id) if *id == timer_id));
+        if let Some(event) = pending {
+            state.remove_pending(event);
+        }
     }
 
     pub(crate) fn get_trace(&self) -> Vec<Event> {
         self.upgrade().borrow().trace.clone()
     }
 
-    pub(crate) fn send_local(&mut self, msg: String) {
+    pub(crate) fn crashed(&self) -> BTreeSet<ProcessId> {
+        self.upgrade().borrow().crashed.iter().copied().collect()
+    }
+
+    /// Returns flag which is put when the next process crashes.
+    pub(crate) fn watch_crashes(&self) -> Rc<RefCell<SharedState<()>>> {
+        let flag = Rc::new(RefCell::new(SharedState::default()));
+        self.upgrade()
+            .borrow_mut()
+            .crash_watchers
+            .push(Rc::downgrade(&flag));
+        flag
+    }
+
+    pub(crate) fn send_local(&mut self, msg: String) -> Result<(), Error> {
         let this = self.upgrade();
         let mut state = this.borrow_mut();
-        let proc = state.current_process.expect(
-            "trying to send local message, 
-            but current process is not set",
-        );
+        let proc = state.current_process.ok_or(Error::NoProcessContext)?;
 
         state
             .local_messages
             .entry(proc)
-            .or_insert(Vec::new())
+            .or_default()
             .push(msg.clone());
 
-        let time = state.time;
-        state.trace.push(Event {
-            time,
-            kind: EventKind::ProcLocalMessage(proc, msg),
-        });
+        state.push_event(EventKind::ProcLocalMessage(proc, msg));
         state.processed_events += 1;
+        Ok(())
     }
 
     pub(crate) fn schedule(&self, task_id: TaskId) {
         self.upgrade().borrow_mut().pending_tasks.push_back(task_id);
     }
 
-    pub(crate) fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    pub(crate) fn spawn<F>(&self, future: F) -> Result<JoinHandle<F::Output>, Error>
     where
         F: Future + 'static,
     {
         let this = self.upgrade();
         let mut state = this.borrow_mut();
-        let cur_proc = state.current_process.expect(
-            "trying to spawn async activity, 
-            but `current_process` is not set",
-        );
+        let cur_proc = state.current_process.ok_or(Error::NoProcessContext)?;
         let (handle, task) = Task::from_future(cur_proc, future);
         let id = state.next_task_id;
         state.next_task_id += 1;
         state.tasks.insert(id, task);
         state.pending_tasks.push_back(id);
-        handle
+        Ok(handle)
+    }
+
+    pub(crate) fn send(&mut self, to: ProcessId, msg: String) -> Result<AckHandle, Error> {
+        let flag = Rc::new(RefCell::new(SharedState::default()));
+        let msg_id = self.send_message(to, msg, Some(Rc::downgrade(&flag)))?;
+        Ok(AckHandle { flag, msg_id })
+    }
+
+    pub(crate) fn send_unacked(&mut self, to: ProcessId, msg: String) -> Result<(), Error> {
+        self.send_message(to, msg, None).map(|_| ())
     }
 
-    pub(crate) fn send(&mut self, to: ProcessId, msg: String) -> AckHandle {
+    /// Traces that the current process sends the message again.
+    pub(crate) fn retry(
+        &mut self,
+        to: ProcessId,
+        msg_id: MessageId,
+        attempt: usize,
+    ) -> Result<(), Error> {
         let this = self.upgrade();
         let mut state = this.borrow_mut();
+        let from = state.current_process.ok_or(Error::NoProcessContext)?;
+        state.push_event(EventKind::MessageRetried(from, to, msg_id, attempt));
+        Ok(())
+    }
 
-        let from = state.current_process.expect(
-            "trying to send message, 
-            but `current_process` is not set",
-        );
+    /// Sends message, which is acknowledged on delivery only if there is the ack waiter.
+    fn send_message(
+        &mut self,
+        to: ProcessId,
+        msg: String,
+        waiter: Option<AckWaiter>,
+    ) -> Result<MessageId, Error> {
+        let this = self.upgrade();
+        let mut state = this.borrow_mut();
 
-        let flag = Rc::new(RefCell::new(SharedState::default()));
-        let flag_ref = Rc::downgrade(&flag);
+        let from = state.current_process.ok_or(Error::NoProcessContext)?;
+        if to >= state.processes {
+            return Err(Error::NoSuchProcess(to));
+        }
+        if let Some(topology) = &state.topology {
+            if topology.route(from, to).is_none() {
+                return Err(Error::NoLink(from, to));
+            }
+        }
 
         let msg_id = state.next_msg_id;
         state.next_msg_id += 1;
-        let old = state.waiting_ack.insert(msg_id, flag_ref);
-        assert!(old.is_none(), "duplicate message id: {msg_id}");
+        state.push_event(EventKind::MessageSent(from, to, msg_id, msg.clone()));
 
-        let time = state.time;
-        state.trace.push(Event {
-            time,
-            kind: EventKind::MessageSent(from, to, msg_id, msg.clone()),
-        });
-
-        state
-            .pending_events
-            .push(EventKind::MessageDelivered(from, to, msg_id, msg));
+        if state.crashed.contains(&to) {
+            state.push_event(EventKind::MessageDropped(from, to, msg_id));
+            if let Some(waiter) = waiter.and_then(|waiter| waiter.upgrade()) {
+                waiter.borrow_mut().put(AckOutcome::ReceiverCrashed);
+            }
+        } else {
+            match waiter {
+                Some(waiter) => {
+                    let old = state.waiting_ack.insert(msg_id, (from, waiter));
+                    assert!(old.is_none(), "duplicate message id: {msg_id}");
+                }
+                None => {
+                    state.unacked.insert(msg_id);
+                }
+            }
+            let path = state.route(from, to);
+            let due = state.delivery_time(&path);
+            if !state.unacked.contains(&msg_id) {
+                state.ack_paths.insert(msg_id, path);
+            }
+            state.push_in_flight(EventKind::MessageDelivered(from, to, msg_id, msg), due);
+        }
 
         state.processed_events += 1;
-
-        AckHandle { flag }
+        Ok(msg_id)
     }
 
     pub(crate) fn get_pending_events(&self) -> Vec<EventKind> {
@@ -165,40 +445,202 @@ impl SystemHandle {
         let this = self.upgrade();
         let mut state = this.borrow_mut();
 
-        let event_kind = state.pending_events.remove(event);
+        let (event_kind, due) = state.remove_pending(event);
+        state
+            .schedule
+            .push(ScheduleStep::ApplyEvent(event, event_kind.clone()));
+        if state.timing {
+            state.time = state.time.max(due);
+        }
 
-        let time = state.time;
-        state.trace.push(Event {
-            time,
-            kind: event_kind.clone(),
-        });
+        state.push_event(event_kind.clone());
 
         match event_kind {
             EventKind::ProcLocalMessage(_, _)
             | EventKind::UserLocalMessage(_, _)
             | EventKind::MessageSent(_, _, _, _)
-            | EventKind::AckSent(_, _, _) => panic!("event can not be pending"),
+            | EventKind::AckSent(_, _, _)
+            | EventKind::MessageDropped(_, _, _)
+            | EventKind::AckDropped(_, _, _)
+            | EventKind::ProcessCrashed(_)
+            | EventKind::TimerSet(_, _)
+            | EventKind::MessageRetried(_, _, _, _) => panic!("event can not be pending"),
+            EventKind::MessageDelivered(_, _, msg_id, _) if state.unacked.contains(&msg_id) => {
+                state.release(msg_id);
+            }
+            EventKind::MessageDelivered(from, to, msg_id, _) if state.crashed.contains(&from) => {
+                state.push_event(EventKind::AckSent(to, from, msg_id));
+                state.push_event(EventKind::AckDropped(to, from, msg_id));
+                state.release(msg_id);
+            }
             EventKind::MessageDelivered(from, to, msg_id, _) => {
-                state.trace.push(Event {
-                    time,
-                    kind: EventKind::AckSent(to, from, msg_id),
-                });
-                state
-                    .pending_events
-                    .push(EventKind::AckDelivered(to, from, msg_id));
+                state.push_event(EventKind::AckSent(to, from, msg_id));
+                // Ack travels back over the path of the message, even if links changed since.
+                let mut path = state.ack_paths[&msg_id].clone();
+                state.release(msg_id);
+                path.reverse();
+                let due = state.delivery_time(&path);
+                state.push_in_flight(EventKind::AckDelivered(to, from, msg_id), due);
+            }
+            EventKind::ClockJumped(proc, jump_id) => {
+                let delta = state.clock_jumps[jump_id];
+                state.local_clocks.entry(proc).or_default().jump(delta);
+                for event in 0..state.pending_events.len() {
+                    if let EventKind::TimerFired(owner, timer_id) = state.pending_events[event] {
+                        if owner == proc {
+                            let deadline = state.timer_deadline[&timer_id];
+                            state.pending_due[event] = state.timer_due(proc, deadline);
+                        }
+                    }
+                }
+            }
+            EventKind::TimerFired(_, timer_id) => {
+                state.timer_deadline.remove(&timer_id);
+                drop(state);
+                let waiter = this.borrow_mut().waiting_timer.remove(&timer_id);
+                if let Some(waiter) = waiter.and_then(|waiter| waiter.upgrade()) {
+                    waiter.borrow_mut().put(());
+                }
             }
             EventKind::AckDelivered(_, _, msg_id) => {
+                let waiter = state.take_ack_waiter(msg_id);
                 drop(state);
-                let waiter_ref = this.borrow_mut().waiting_ack.remove(&msg_id).expect(
-                    format!("ack waiter is not registered for message with id {msg_id}").as_str(),
-                );
-                waiter_ref
-                    .upgrade()
-                    .map(|waiter| waiter.borrow_mut().put(true));
+                if let Some(waiter) = waiter {
+                    waiter.borrow_mut().put(AckOutcome::Delivered);
+                }
             }
         }
 
-        return Some(event_kind);
+        Some(event_kind)
+    }
+
+    pub(crate) fn duplicate_pending_event(&self, event: usize) {
+        let this = self.upgrade();
+        let mut state = this.borrow_mut();
+
+        let kind = state.pending_events[event].clone();
+        let due = state.pending_due[event];
+        state
+            .schedule
+            .push(ScheduleStep::DuplicateEvent(event, kind.clone()));
+        state.push_in_flight(kind, due);
+    }
+
+    pub(crate) fn drop_pending_event(&self, event: usize) {
+        let this = self.upgrade();
+        let mut state = this.borrow_mut();
+
+        let (event_kind, _) = state.remove_pending(event);
+        state
+            .schedule
+            .push(ScheduleStep::DropEvent(event, event_kind.clone()));
+        if let Some(dropped) = dropped(&event_kind) {
+            state.push_event(dropped);
+        }
+        if let EventKind::MessageDelivered(_, _, msg_id, _) = event_kind {
+            state.release(msg_id);
+        }
+        // Message is lost only if no duplicate of it or its ack is left.
+        let waiter = match event_kind {
+            EventKind::MessageDelivered(_, _, msg_id, _)
+            | EventKind::AckDelivered(_, _, msg_id)
+                if !state.in_flight(msg_id, true) =>
+            {
+                state.take_ack_waiter(msg_id)
+            }
+            _ => None,
+        };
+        drop(state);
+        if let Some(waiter) = waiter {
+            waiter.borrow_mut().put(AckOutcome::Lost);
+        }
+    }
+
+    pub(crate) fn crash(&self, proc: ProcessId) {
+        let this = self.upgrade();
+        let mut state = this.borrow_mut();
+
+        state.schedule.push(ScheduleStep::Crash(proc));
+        state.push_event(EventKind::ProcessCrashed(proc));
+        state.crashed.insert(proc);
+
+        let mut resolved = Vec::new();
+        let mut event = 0;
+        while event < state.pending_events.len() {
+            if state.pending_events[event].process() != proc {
+                event += 1;
+                continue;
+            }
+            let (lost, _) = state.remove_pending(event);
+            if let Some(dropped) = dropped(&lost) {
+                state.push_event(dropped);
+            }
+            if let EventKind::MessageDelivered(_, _, msg_id, _) = lost {
+                state.release(msg_id);
+                resolved.push((msg_id, AckOutcome::ReceiverCrashed));
+            }
+        }
+        resolved.extend(
+            state
+                .waiting_ack
+                .iter()
+                .filter(|(_, (from, _))| *from == proc)
+                .map(|(msg_id, _)| (*msg_id, AckOutcome::Cancelled)),
+        );
+        let waiters: Vec<_> = resolved
+            .into_iter()
+            .filter_map(|(msg_id, outcome)| Some((state.take_ack_waiter(msg_id)?, outcome)))
+            .collect();
+        let watchers: Vec<_> = state
+            .crash_watchers
+            .drain(..)
+            .filter_map(|watcher| watcher.upgrade())
+            .collect();
+        drop(state);
+        for (waiter, outcome) in waiters {
+            waiter.borrow_mut().put(outcome);
+        }
+        for watcher in watchers {
+            watcher.borrow_mut().put(());
+        }
+
+        let mut state = this.borrow_mut();
+        let ids: Vec<_> = state
+            .tasks
+            .iter()
+            .filter(|(_, task)| task.owner() == proc)
+            .map(|(id, _)| *id)
+            .collect();
+        let tasks: Vec<_> = ids.iter().filter_map(|id| state.tasks.remove(id)).collect();
+        state.pending_tasks.retain(|id| !ids.contains(id));
+        // Tasks cancel their timers when dropped, which needs the state.
+        drop(state);
+        drop(tasks);
+    }
+}
+
+/// Returns link over which message or ack of the event travels.
+fn link(kind: &EventKind) -> Option<(ProcessId, ProcessId)> {
+    match kind {
+        EventKind::MessageDelivered(from, to, _, _) | EventKind::AckDelivered(from, to, _) => {
+            Some((*from, *to))
+        }
+        _ => None,
+    }
+}
+
+/// Returns event which is traced when pending event is dropped.
+/// Timers which will never fire are not traced.
+fn dropped(pending: &EventKind) -> Option<EventKind> {
+    match pending {
+        EventKind::MessageDelivered(from, to, msg_id, _) => {
+            Some(EventKind::MessageDropped(*from, *to, *msg_id))
+        }
+        EventKind::AckDelivered(from, to, msg_id) => {
+            Some(EventKind::AckDropped(*from, *to, *msg_id))
+        }
+        EventKind::TimerFired(_, _) | EventKind::ClockJumped(_, _) => None,
+        _ => panic!("event can not be pending"),
     }
 }
 
@@ -216,6 +658,7 @@ impl System {
     {
         let id = self.proc.len();
         self.proc.push(Box::new(process));
+        self.state.borrow_mut().processes += 1;
         id
     }
 
@@ -223,29 +666,177 @@ impl System {
         SystemHandle(Rc::downgrade(&self.state))
     }
 
-    fn install_handle(&self) {
-        SYSTEM_HANDLE.with(|h| {
-            *h.borrow_mut() = Some(self.handle());
-        });
+    /// Makes the system current for the step, until the guard is dropped.
+    fn enter(&self) -> HandleGuard {
+        let previous = SYSTEM_HANDLE.with(|h| h.borrow_mut().replace(self.handle()));
+        HandleGuard { previous }
     }
 
     fn set_current_proc(&self, proc: ProcessId) {
         self.state.borrow_mut().current_process = Some(proc);
     }
 
+    /// Creates system with the provided seed of the random number generator,
+    /// which is used by [`crate::random`].
+    pub fn with_seed(seed: u64) -> Self {
+        let system = Self::default();
+        system.state.borrow_mut().rng = SeededRng::new(seed);
+        system
+    }
+
+    /// Turns on the timing mode, where every message and ack
+    /// is delivered after the latency drawn from the distribution,
+    /// and time of the system is virtual time of the last applied event.
+    ///
+    /// Latency is drawn from the generator seeded by the seed of the system.
+    pub fn set_latency(&mut self, latency: Latency) {
+        let mut state = self.state.borrow_mut();
+        state.timing = true;
+        state.latency = Some(latency);
+    }
+
+    /// Sets latency of the link from one process to another and turns on the timing mode.
+    /// Links without own latency use one set by [`System::set_latency`], which is zero by default.
+    pub fn set_link_latency(&mut self, from: ProcessId, to: ProcessId, latency: Latency) {
+        let mut state = self.state.borrow_mut();
+        state.timing = true;
+        state.link_latency.insert((from, to), latency);
+    }
+
+    /// Sets delivery guarantees of all links without own ones.
+    /// Must be set before messages are sent.
+    pub fn set_delivery(&mut self, delivery: Delivery) {
+        self.state.borrow_mut().delivery = delivery;
+    }
+
+    /// Sets delivery guarantees of the link from one process to another.
+    /// Must be set before messages are sent over it.
+    pub fn set_link_delivery(&mut self, from: ProcessId, to: ProcessId, delivery: Delivery) {
+        self.state
+            .borrow_mut()
+            .link_delivery
+            .insert((from, to), delivery);
+    }
+
+    /// Restricts links over which processes can send messages.
+    /// Sending over a missing link returns [`Error::NoLink`].
+    ///
+    /// In the timing mode message sent over several links
+    /// is delivered after the sum of their latencies.
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.state.borrow_mut().topology = Some(topology);
+    }
+
+    /// Returns current time of the system:
+    /// virtual time in the timing mode, otherwise the number of made steps.
+    pub fn now(&self) -> f64 {
+        self.state.borrow().time
+    }
+
+    /// Returns time on the local clock of the process.
+    pub fn local_time(&self, proc: ProcessId) -> f64 {
+        let state = self.state.borrow();
+        state.local_clock(proc).local(state.time)
+    }
+
+    /// Sets offset and drift of the local clock of the process,
+    /// which is returned by [`crate::now`] and used by its timers.
+    pub fn set_clock(&mut self, proc: ProcessId, clock: LocalClock) {
+        self.state.borrow_mut().local_clocks.insert(proc, clock);
+    }
+
+    pub fn jump_clock(&mut self, proc: ProcessId, at: f64, delta: f64) -> JumpId {
+        self.try_jump_clock(proc, at, delta)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Schedules jump of the local clock of the process by `delta`,
+    /// like a step made by NTP, at the time `at` of the system.
+    ///
+    /// Jump is a pending event, so it can be reordered with other events
+    /// or dropped, unless the system is stepped with [`System::step`].
+    /// Pending timers of the process are moved to fire on the new local time.
+    pub fn try_jump_clock(
+        &mut self,
+        proc: ProcessId,
+        at: f64,
+        delta: f64,
+    ) -> Result<JumpId, Error> {
+        self.check_process(proc)?;
+        if self.is_crashed(proc) {
+            return Err(Error::ProcessCrashed(proc));
+        }
+        let mut state = self.state.borrow_mut();
+        state
+            .schedule
+            .push(ScheduleStep::ClockJump(proc, at, delta));
+        let jump_id = state.clock_jumps.len();
+        state.clock_jumps.push(delta);
+        let due = at.max(state.time);
+        state.push_pending(EventKind::ClockJumped(proc, jump_id), due);
+        Ok(jump_id)
+    }
+
+    /// Applies pending event which is due first, the earliest sent one among equal.
+    /// Returns `false` if there are no pending events.
+    pub fn step(&mut self) -> bool {
+        let next = {
+            let state = self.state.borrow();
+            (0..state.pending_due.len())
+                .min_by(|a, b| state.pending_due[*a].total_cmp(&state.pending_due[*b]))
+        };
+        match next {
+            Some(event) => {
+                self.apply_pending_event(event);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn send_local_message(&mut self, to: ProcessId, msg: &str) {
+        self.try_send_local_message(to, msg)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as [`System::send_local_message`],
+    /// but returns error if process does not exist or is crashed.
+    pub fn try_send_local_message(&mut self, to: ProcessId, msg: &str) -> Result<(), Error> {
+        self.check_process(to)?;
+        if self.is_crashed(to) {
+            return Err(Error::ProcessCrashed(to));
+        }
+        self.state
+            .borrow_mut()
+            .schedule
+            .push(ScheduleStep::LocalMessage(to, msg.to_string()));
+
+        let _guard = self.enter();
         self.set_current_proc(to);
-        self.install_handle();
 
         self.handle()
             .add_event_kind(EventKind::UserLocalMessage(to, msg.to_string()));
 
-        self.proc
-            .get_mut(to)
-            .expect("incorrect process id")
-            .on_local_message(msg);
+        self.proc[to].on_local_message(msg);
 
         self.process_pending_tasks();
+        Ok(())
+    }
+
+    fn check_process(&self, proc: ProcessId) -> Result<(), Error> {
+        if proc < self.proc.len() {
+            Ok(())
+        } else {
+            Err(Error::NoSuchProcess(proc))
+        }
+    }
+
+    fn check_event(&self, event: usize) -> Result<(), Error> {
+        if event < self.get_pending_events_count() {
+            Ok(())
+        } else {
+            Err(Error::NoSuchEvent(event))
+        }
     }
 
     fn process_pending_task(&mut self) -> bool {
@@ -255,7 +846,8 @@ impl System {
                 return false;
             };
             let Some(task) = state.tasks.remove(&task_id) else {
-                panic!("missing task: {task_id}");
+                // Task was woken several times and finished after the first wake.
+                return true;
             };
             (task_id, task)
         };
@@ -274,8 +866,6 @@ impl System {
     }
 
     fn process_pending_tasks(&mut self) -> usize {
-        self.install_handle();
-
         let mut cnt = 0;
         loop {
             if !self.process_pending_task() {
@@ -290,19 +880,21 @@ impl System {
     }
 
     pub fn read_local(&mut self, proc: ProcessId) -> Vec<String> {
-        if self.proc.len() <= proc {
-            panic!(
-                "trying to read local message 
-                from process with incorrect id: {proc}"
-            );
-        }
-        self.state
+        self.try_read_local(proc)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as [`System::read_local`], but returns error if process does not exist.
+    pub fn try_read_local(&mut self, proc: ProcessId) -> Result<Vec<String>, Error> {
+        self.check_process(proc)?;
+        Ok(self
+            .state
             .borrow_mut()
             .local_messages
             .entry(proc)
-            .or_insert(Vec::new())
+            .or_default()
             .drain(..)
-            .collect()
+            .collect())
     }
 
     pub fn get_trace(&self) -> Vec<Event> {
@@ -318,21 +910,129 @@ impl System {
     }
 
     pub fn apply_pending_event(&mut self, event: usize) {
-        self.handle()
-            .apply_pending_event(event)
-            .map(|event| match event {
-                EventKind::MessageDelivered(from, to, _, msg) => {
-                    self.set_current_proc(to);
-
-                    self.proc
-                        .get_mut(to)
-                        .expect("invalid process id")
-                        .on_message(from, msg);
-                }
-                _ => {}
-            });
+        self.try_apply_pending_event(event)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as [`System::apply_pending_event`], but returns error if there is no such event.
+    pub fn try_apply_pending_event(&mut self, event: usize) -> Result<(), Error> {
+        self.check_event(event)?;
+        let _guard = self.enter();
+        if let Some(EventKind::MessageDelivered(from, to, _, msg)) =
+            self.handle().apply_pending_event(event)
+        {
+            self.set_current_proc(to);
+
+            self.proc[to].on_message(from, msg);
+        }
 
         self.process_pending_tasks();
+        Ok(())
+    }
+
+    /// Makes a copy of the pending message or ack, so it can be delivered twice.
+    /// The copy is the last pending event, or waits behind the original one on the FIFO link.
+    pub fn duplicate_pending_event(&mut self, event: usize) {
+        self.try_duplicate_pending_event(event)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as [`System::duplicate_pending_event`], but returns error
+    /// if there is no such event or it is not a message or ack.
+    pub fn try_duplicate_pending_event(&mut self, event: usize) -> Result<(), Error> {
+        self.check_event(event)?;
+        if link(&self.state.borrow().pending_events[event]).is_none() {
+            return Err(Error::NotMessage(event));
+        }
+        self.handle().duplicate_pending_event(event);
+        Ok(())
+    }
+
+    /// Drops pending message or ack, so it will never be applied.
+    /// Dropped messages and acks are traced as [`EventKind::MessageDropped`]
+    /// and [`EventKind::AckDropped`].
+    pub fn drop_pending_event(&mut self, event: usize) {
+        self.try_drop_pending_event(event)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as [`System::drop_pending_event`], but returns error if there is no such event,
+    /// it is not a message or ack, or it travels over the [`Delivery::FifoReliable`] link.
+    pub fn try_drop_pending_event(&mut self, event: usize) -> Result<(), Error> {
+        self.check_event(event)?;
+        let link = link(&self.state.borrow().pending_events[event]);
+        let (from, to) = link.ok_or(Error::NotMessage(event))?;
+        if self.state.borrow().link_delivery((from, to)).is_reliable() {
+            return Err(Error::ReliableLink(from, to));
+        }
+        let _guard = self.enter();
+        self.handle().drop_pending_event(event);
+        self.process_pending_tasks();
+        Ok(())
+    }
+
+    /// Crashes process.
+    /// Its tasks are cancelled and its methods are never called again.
+    /// Messages and acks pending for the process are dropped,
+    /// and messages sent to it later and acks of its messages are dropped right after sending.
+    /// Its senders get [`AckOutcome::ReceiverCrashed`], its own acks [`AckOutcome::Cancelled`].
+    pub fn crash_process(&mut self, proc: ProcessId) {
+        self.try_crash_process(proc)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as [`System::crash_process`], but returns error if process does not exist.
+    pub fn try_crash_process(&mut self, proc: ProcessId) -> Result<(), Error> {
+        self.check_process(proc)?;
+        if self.is_crashed(proc) {
+            return Ok(());
+        }
+        let _guard = self.enter();
+        self.handle().crash(proc);
+        self.process_pending_tasks();
+        Ok(())
+    }
+
+    pub fn is_crashed(&self, proc: ProcessId) -> bool {
+        self.state.borrow().crashed.contains(&proc)
+    }
+
+    /// Returns steps made by the system so far,
+    /// which can be replayed with [`crate::replay`].
+    pub fn get_schedule(&self) -> Schedule {
+        Schedule {
+            steps: self.recorded_steps(0),
+        }
+    }
+
+    /// Returns recorded steps starting from index `from`,
+    /// where the seed is the step with index 0.
+    pub(crate) fn recorded_steps(&self, from: usize) -> Vec<ScheduleStep> {
+        let state = self.state.borrow();
+        let mut steps = Vec::new();
+        if from == 0 {
+            steps.push(ScheduleStep::Seed(state.rng.seed()));
+        }
+        let from = from.saturating_sub(1).min(state.schedule.len());
+        steps.extend_from_slice(&state.schedule[from..]);
+        steps
+    }
+
+    /// Returns due times of pending events and local clocks of processes
+    /// in the timing mode, `None` otherwise.
+    pub(crate) fn get_timing(&self) -> Option<(Vec<f64>, Vec<LocalClock>)> {
+        let state = self.state.borrow();
+        if !state.timing {
+            return None;
+        }
+        let clocks = (0..self.proc.len())
+            .map(|proc| state.local_clock(proc))
+            .collect();
+        Some((state.pending_due.clone(), clocks))
+    }
+
+    pub fn get_processes_count(&self) -> usize {
+        self.proc.len()
     }
 
     pub fn get_processed_tasks(&self) -> usize {
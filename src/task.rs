@@ -22,9 +22,9 @@ impl Task {
 
         let future = async move {
             let value = future.await;
-            result_ref
-                .upgrade()
-                .map(|result| result.borrow_mut().put(value));
+            if let Some(result) = result_ref.upgrade() {
+                result.borrow_mut().put(value);
+            }
         };
 
         (join_handle, Task(proc, Box::pin(future)))
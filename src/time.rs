@@ -0,0 +1,122 @@
+use std::{
+    cell::RefCell,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use futures::Future;
+
+use crate::{error::Error, runtime::NodeHandle, shared::SharedState, system::SystemHandle};
+
+pub type TimerId = usize;
+
+pub type JumpId = usize;
+
+/// Local clock of the process in the [`crate::System`],
+/// which is derived from the time of the system.
+///
+/// Local time is `offset + (1 + drift) * time` plus all jumps made so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LocalClock {
+    offset: f64,
+    /// Rate at which the clock gains time, e.g. `0.01` runs one percent fast.
+    drift: f64,
+}
+
+impl LocalClock {
+    pub fn new(offset: f64, drift: f64) -> Self {
+        assert!(
+            drift > -1.0,
+            "clock must go forward, drift must be greater than -1"
+        );
+        Self { offset, drift }
+    }
+
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// Returns rate at which the clock gains time, e.g. `0.01` runs one percent fast.
+    pub fn drift(&self) -> f64 {
+        self.drift
+    }
+
+    /// Moves the clock by the delta, like the jump scheduled with [`crate::System::jump_clock`].
+    pub(crate) fn jump(&mut self, delta: f64) {
+        self.offset += delta;
+    }
+
+    /// Returns local time at the time of the system.
+    pub fn local(&self, time: f64) -> f64 {
+        self.offset + (1.0 + self.drift) * time
+    }
+
+    /// Returns time of the system at which the clock shows the local time.
+    pub fn global(&self, local: f64) -> f64 {
+        (local - self.offset) / (1.0 + self.drift)
+    }
+}
+
+/// Future which is resolved when the timer fires.
+///
+/// In the [`crate::System`] dropping it before the timer fires cancels the timer.
+pub struct Sleep {
+    pub(crate) flag: Rc<RefCell<SharedState<()>>>,
+    pub(crate) timer: Option<(SystemHandle, TimerId)>,
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some((system, timer_id)) = self.timer.take() {
+            system.cancel_timer(timer_id);
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.flag.borrow_mut().take(cx.waker().clone()).is_some() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Returns current time on the local clock of the process.
+///
+/// In the [`crate::System`] local clock is derived by [`LocalClock`]
+/// from the virtual time in the timing mode, otherwise from the number of made steps.
+/// In the [`crate::runtime`] it is the number of seconds since the start of the process.
+pub fn now() -> f64 {
+    try_now().unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Sets timer which fires after the duration.
+///
+/// In the [`crate::System`] timer is a pending event.
+/// In the timing mode it is due when the local clock shows `now() + duration`,
+/// otherwise it can fire at any step like a message.
+/// In the [`crate::runtime`] duration is the number of seconds.
+pub fn sleep(duration: f64) -> Sleep {
+    try_sleep(duration).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Same as [`now`], but returns error if called outside of the process.
+pub fn try_now() -> Result<f64, Error> {
+    match NodeHandle::current() {
+        Some(node) => Ok(node.now()),
+        None => Ok(SystemHandle::current()?.now()),
+    }
+}
+
+/// Same as [`sleep`], but returns error if called outside of the process.
+pub fn try_sleep(duration: f64) -> Result<Sleep, Error> {
+    match NodeHandle::current() {
+        Some(node) => Ok(node.sleep(duration)),
+        None => SystemHandle::current()?.sleep(duration),
+    }
+}
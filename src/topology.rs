@@ -0,0 +1,155 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    json::Value,
+    process::ProcessId,
+    workload::{node_id, parse_node_id},
+};
+
+/// Links between processes of the [`crate::System`], set with [`crate::System::set_topology`].
+///
+/// Process can send messages only over its links, unless routing is enabled.
+/// Acks travel back over the same path as the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topology {
+    neighbours: Vec<Vec<ProcessId>>,
+    routing: bool,
+}
+
+impl Topology {
+    /// Creates topology from the neighbours of every process.
+    /// Links are directed, so process `i` can send to `neighbours[i]`.
+    pub fn custom(neighbours: Vec<Vec<ProcessId>>) -> Self {
+        Self {
+            neighbours,
+            routing: false,
+        }
+    }
+
+    pub fn full_mesh(processes: usize) -> Self {
+        Self::custom(
+            (0..processes)
+                .map(|proc| (0..processes).filter(|other| *other != proc).collect())
+                .collect(),
+        )
+    }
+
+    /// Every process is linked with the previous and the next one.
+    pub fn ring(processes: usize) -> Self {
+        Self::custom(
+            (0..processes)
+                .map(|proc| {
+                    let mut neighbours =
+                        vec![(proc + processes - 1) % processes, (proc + 1) % processes];
+                    neighbours.sort_unstable();
+                    neighbours.dedup();
+                    neighbours.retain(|other| *other != proc);
+                    neighbours
+                })
+                .collect(),
+        )
+    }
+
+    /// Process `0` is linked with every other process.
+    pub fn star(processes: usize) -> Self {
+        Self::custom(
+            (0..processes)
+                .map(|proc| match proc {
+                    0 => (1..processes).collect(),
+                    _ => vec![0],
+                })
+                .collect(),
+        )
+    }
+
+    /// Parses topology in the format of the Maelstrom `topology` message,
+    /// e.g. `{"n0": ["n1"], "n1": ["n0"]}`.
+    pub fn from_maelstrom(topology: &Value) -> Result<Self, String> {
+        let Value::Object(nodes) = topology else {
+            return Err("topology must be an object".to_string());
+        };
+        let parse = |node: &str| parse_node_id(node).ok_or(format!("invalid node id '{node}'"));
+        let mut neighbours: Vec<Vec<ProcessId>> = Vec::new();
+        for (node, links) in nodes {
+            let proc = parse(node)?;
+            let links = links
+                .as_array()
+                .ok_or(format!("neighbours of {node} must be an array"))?
+                .iter()
+                .map(|link| parse(link.as_str().ok_or("node id must be a string")?))
+                .collect::<Result<Vec<_>, String>>()?;
+            if neighbours.len() <= proc {
+                neighbours.resize(proc + 1, Vec::new());
+            }
+            neighbours[proc] = links;
+        }
+        Ok(Self::custom(neighbours))
+    }
+
+    /// Returns topology in the format of the Maelstrom `topology` message.
+    pub fn to_maelstrom(&self) -> Value {
+        Value::Object(
+            self.neighbours
+                .iter()
+                .enumerate()
+                .map(|(proc, links)| {
+                    let links: Vec<Value> = links.iter().map(|to| node_id(*to).into()).collect();
+                    (node_id(proc), links.into())
+                })
+                .collect(),
+        )
+    }
+
+    /// Lets messages travel to processes without direct link
+    /// over the shortest path of links.
+    pub fn with_routing(mut self) -> Self {
+        self.routing = true;
+        self
+    }
+
+    pub fn neighbours(&self, proc: ProcessId) -> &[ProcessId] {
+        self.neighbours.get(proc).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns neighbours of every process,
+    /// e.g. for [`crate::workload::Broadcast::with_topology`].
+    pub fn adjacency(&self) -> Vec<Vec<ProcessId>> {
+        self.neighbours.clone()
+    }
+
+    pub fn has_link(&self, from: ProcessId, to: ProcessId) -> bool {
+        self.neighbours(from).contains(&to)
+    }
+
+    /// Returns processes on the path of the message, from the sender to the receiver,
+    /// or `None` if the message can not be sent.
+    /// Process can always send to itself.
+    pub fn route(&self, from: ProcessId, to: ProcessId) -> Option<Vec<ProcessId>> {
+        if from == to || self.has_link(from, to) {
+            return Some(vec![from, to]);
+        }
+        if !self.routing {
+            return None;
+        }
+
+        let mut previous = HashMap::from([(from, from)]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(proc) = queue.pop_front() {
+            for next in self.neighbours(proc) {
+                if previous.contains_key(next) {
+                    continue;
+                }
+                previous.insert(*next, proc);
+                queue.push_back(*next);
+            }
+        }
+
+        previous.get(&to)?;
+        let mut path = vec![to];
+        while *path.last().unwrap() != from {
+            path.push(previous[path.last().unwrap()]);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeSet;
+
+use crate::{json::Value, process::ProcessId};
+
+use super::{node_id, Workload};
+
+/// Sends `topology` to every node, then `broadcast` requests,
+/// and after all messages are delivered checks with `read` requests
+/// that every node has every acknowledged message and nothing else.
+pub struct Broadcast {
+    nodes: usize,
+    messages: u64,
+    topology: Vec<Vec<ProcessId>>,
+    concurrency: u64,
+    topology_sent: bool,
+    next_message: u64,
+    read_sent: bool,
+    acked: BTreeSet<u64>,
+}
+
+impl Broadcast {
+    /// Creates workload which broadcasts `messages` messages in the fully connected topology.
+    /// Message `m` is sent to the node `m % nodes`.
+    pub fn new(nodes: usize, messages: u64) -> Self {
+        let topology = (0..nodes)
+            .map(|proc| (0..nodes).filter(|other| *other != proc).collect())
+            .collect();
+        Self {
+            nodes,
+            messages,
+            topology,
+            concurrency: messages.max(1),
+            topology_sent: false,
+            next_message: 0,
+            read_sent: false,
+            acked: BTreeSet::new(),
+        }
+    }
+
+    /// Replaces topology sent to nodes, where `topology[i]` are neighbours of the node `i`.
+    pub fn with_topology(mut self, topology: Vec<Vec<ProcessId>>) -> Self {
+        assert_eq!(topology.len(), self.nodes, "topology must list every node");
+        self.topology = topology;
+        self
+    }
+
+    /// Sends broadcasts in phases of `concurrency` messages instead of all at once,
+    /// waiting for delivery of the previous phase.
+    pub fn with_concurrency(mut self, concurrency: u64) -> Self {
+        assert!(concurrency > 0, "concurrency must be positive");
+        self.concurrency = concurrency;
+        self
+    }
+}
+
+impl Workload for Broadcast {
+    fn next_requests(&mut self) -> Vec<(ProcessId, Value)> {
+        if !self.topology_sent {
+            self.topology_sent = true;
+            let topology = Value::Object(
+                self.topology
+                    .iter()
+                    .enumerate()
+                    .map(|(proc, neighbours)| {
+                        let neighbours = neighbours
+                            .iter()
+                            .map(|n| Value::from(node_id(*n)))
+                            .collect::<Vec<_>>();
+                        (node_id(proc), neighbours.into())
+                    })
+                    .collect(),
+            );
+            let body = Value::object([("type", "topology".into()), ("topology", topology)]);
+            return (0..self.nodes).map(|proc| (proc, body.clone())).collect();
+        }
+        if self.next_message < self.messages {
+            let from = self.next_message;
+            self.next_message = (from + self.concurrency).min(self.messages);
+            return (from..self.next_message)
+                .map(|m| {
+                    let body = Value::object([("type", "broadcast".into()), ("message", m.into())]);
+                    (m as usize % self.nodes, body)
+                })
+                .collect();
+        }
+        if !self.read_sent {
+            self.read_sent = true;
+            return (0..self.nodes)
+                .map(|proc| (proc, Value::object([("type", "read".into())])))
+                .collect();
+        }
+        Vec::new()
+    }
+
+    fn on_reply(&mut self, proc: ProcessId, request: &Value, reply: &Value) -> Result<(), String> {
+        match request.get("type").and_then(Value::as_str) {
+            Some("broadcast") => {
+                self.acked
+                    .insert(request.get("message").and_then(Value::as_u64).unwrap());
+            }
+            Some("read") => {
+                let invalid = || format!("{} replied {reply} to read", node_id(proc));
+                let messages = reply
+                    .get("messages")
+                    .and_then(Value::as_array)
+                    .ok_or_else(invalid)?
+                    .iter()
+                    .map(|m| m.as_u64().ok_or_else(invalid))
+                    .collect::<Result<BTreeSet<_>, _>>()?;
+                if let Some(m) = self.acked.difference(&messages).next() {
+                    return Err(format!("{} lost message {m}", node_id(proc)));
+                }
+                if let Some(m) = messages.iter().find(|m| **m >= self.messages) {
+                    return Err(format!("{} read unknown message {m}", node_id(proc)));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
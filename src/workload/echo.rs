@@ -0,0 +1,45 @@
+use crate::{json::Value, process::ProcessId};
+
+use super::{node_id, Workload};
+
+/// Sends `echo` requests and checks that every reply echoes the request.
+pub struct Echo {
+    nodes: usize,
+    requests: usize,
+    sent: bool,
+}
+
+impl Echo {
+    /// Creates workload which sends `requests` requests to each of `nodes` nodes.
+    pub fn new(nodes: usize, requests: usize) -> Self {
+        Self {
+            nodes,
+            requests,
+            sent: false,
+        }
+    }
+}
+
+impl Workload for Echo {
+    fn next_requests(&mut self) -> Vec<(ProcessId, Value)> {
+        if self.sent {
+            return Vec::new();
+        }
+        self.sent = true;
+        (0..self.nodes)
+            .flat_map(|proc| (0..self.requests).map(move |i| (proc, i)))
+            .map(|(proc, i)| {
+                let echo = format!("echo {i} to {}", node_id(proc));
+                let body = Value::object([("type", "echo".into()), ("echo", echo.into())]);
+                (proc, body)
+            })
+            .collect()
+    }
+
+    fn on_reply(&mut self, proc: ProcessId, request: &Value, reply: &Value) -> Result<(), String> {
+        if reply.get("echo") != request.get("echo") {
+            return Err(format!("{} replied {reply} to {request}", node_id(proc)));
+        }
+        Ok(())
+    }
+}
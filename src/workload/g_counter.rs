@@ -0,0 +1,68 @@
+use crate::{json::Value, process::ProcessId};
+
+use super::{node_id, Workload};
+
+/// Sends `add` requests and after all messages are delivered
+/// checks with `read` requests that every node has the sum of acknowledged deltas.
+/// Unacknowledged deltas may be counted or not.
+pub struct GCounter {
+    nodes: usize,
+    adds: u64,
+    phase: usize,
+    acked: u64,
+}
+
+impl GCounter {
+    /// Creates workload which sends `adds` requests, where request `m`
+    /// adds `m + 1` on the node `m % nodes`.
+    pub fn new(nodes: usize, adds: u64) -> Self {
+        Self {
+            nodes,
+            adds,
+            phase: 0,
+            acked: 0,
+        }
+    }
+}
+
+impl Workload for GCounter {
+    fn next_requests(&mut self) -> Vec<(ProcessId, Value)> {
+        self.phase += 1;
+        match self.phase {
+            1 => (0..self.adds)
+                .map(|m| {
+                    let body = Value::object([("type", "add".into()), ("delta", (m + 1).into())]);
+                    (m as usize % self.nodes, body)
+                })
+                .collect(),
+            2 => (0..self.nodes)
+                .map(|proc| (proc, Value::object([("type", "read".into())])))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn on_reply(&mut self, proc: ProcessId, request: &Value, reply: &Value) -> Result<(), String> {
+        match request.get("type").and_then(Value::as_str) {
+            Some("add") => {
+                self.acked += request.get("delta").and_then(Value::as_u64).unwrap();
+            }
+            Some("read") => {
+                let value = reply
+                    .get("value")
+                    .and_then(Value::as_u64)
+                    .ok_or(format!("{} replied {reply} to read", node_id(proc)))?;
+                let total = self.adds * (self.adds + 1) / 2;
+                if value < self.acked || value > total {
+                    return Err(format!(
+                        "{} read {value}, expected at least {} and at most {total}",
+                        node_id(proc),
+                        self.acked
+                    ));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
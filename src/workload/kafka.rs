@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+
+use crate::{json::Value, process::ProcessId};
+
+use super::{node_id, Workload};
+
+/// Kafka-style replicated logs.
+///
+/// Sends `send` requests appending messages to logs by keys,
+/// then after all messages are delivered `poll`s every node from offset 0,
+/// commits the last offsets with `commit_offsets` on the node `n0`
+/// and reads them back with `list_committed_offsets` on every node.
+///
+/// Checks that acknowledged offsets of a key are unique,
+/// that every poll returns every acknowledged message at its offset in increasing order of offsets,
+/// and that committed offsets are not lost.
+pub struct Kafka {
+    nodes: usize,
+    keys: usize,
+    sends: u64,
+    phase: usize,
+    acked: BTreeMap<String, BTreeMap<u64, u64>>,
+}
+
+impl Kafka {
+    /// Creates workload which sends `sends` messages, where message `m`
+    /// is appended to the log with key `k{m % keys}` on the node `m % nodes`.
+    pub fn new(nodes: usize, keys: usize, sends: u64) -> Self {
+        Self {
+            nodes,
+            keys,
+            sends,
+            phase: 0,
+            acked: BTreeMap::new(),
+        }
+    }
+
+    fn keys(&self) -> impl Iterator<Item = String> {
+        (0..self.keys).map(|key| format!("k{key}"))
+    }
+
+    fn offsets(&self, offset: impl Fn(&BTreeMap<u64, u64>) -> u64) -> Value {
+        Value::Object(
+            self.keys()
+                .map(|key| {
+                    let offset = self.acked.get(&key).map(&offset).unwrap_or(0);
+                    (key, offset.into())
+                })
+                .collect(),
+        )
+    }
+
+    fn check_poll(&self, proc: ProcessId, reply: &Value) -> Result<(), String> {
+        let node = node_id(proc);
+        let invalid = || format!("{node} replied {reply} to poll");
+        let msgs = reply.get("msgs").ok_or_else(invalid)?;
+        for key in self.keys() {
+            let mut polled = BTreeMap::new();
+            let mut last = None;
+            for entry in msgs.get(&key).and_then(Value::as_array).unwrap_or(&[]) {
+                let pair = entry.as_array().ok_or_else(invalid)?;
+                let (Some(offset), Some(msg)) = (
+                    pair.first().and_then(Value::as_u64),
+                    pair.get(1).and_then(Value::as_u64),
+                ) else {
+                    return Err(invalid());
+                };
+                if last.is_some_and(|last| offset <= last) {
+                    return Err(format!("{node} polled {key} out of order: {reply}"));
+                }
+                last = Some(offset);
+                polled.insert(offset, msg);
+            }
+            for (offset, msg) in self.acked.get(&key).into_iter().flatten() {
+                match polled.get(offset) {
+                    Some(polled) if polled == msg => {}
+                    Some(polled) => {
+                        return Err(format!(
+                            "{node} polled {polled} at offset {offset} of {key}, but {msg} was sent there"
+                        ))
+                    }
+                    None => return Err(format!("{node} lost offset {offset} of {key}")),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Workload for Kafka {
+    fn next_requests(&mut self) -> Vec<(ProcessId, Value)> {
+        self.phase += 1;
+        let everyone = |body: Value| (0..self.nodes).map(|proc| (proc, body.clone())).collect();
+        match self.phase {
+            1 => (0..self.sends)
+                .map(|m| {
+                    let body = Value::object([
+                        ("type", "send".into()),
+                        ("key", format!("k{}", m as usize % self.keys).into()),
+                        ("msg", m.into()),
+                    ]);
+                    (m as usize % self.nodes, body)
+                })
+                .collect(),
+            2 => everyone(Value::object([
+                ("type", "poll".into()),
+                ("offsets", self.offsets(|_| 0)),
+            ])),
+            3 => {
+                let offsets = self.offsets(|log| log.keys().last().copied().unwrap_or(0));
+                let body = Value::object([("type", "commit_offsets".into()), ("offsets", offsets)]);
+                vec![(0, body)]
+            }
+            4 => {
+                let keys = self.keys().map(Value::from).collect::<Vec<_>>();
+                everyone(Value::object([
+                    ("type", "list_committed_offsets".into()),
+                    ("keys", keys.into()),
+                ]))
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn on_reply(&mut self, proc: ProcessId, request: &Value, reply: &Value) -> Result<(), String> {
+        let node = node_id(proc);
+        match request.get("type").and_then(Value::as_str) {
+            Some("send") => {
+                let key = request.get("key").and_then(Value::as_str).unwrap();
+                let msg = request.get("msg").and_then(Value::as_u64).unwrap();
+                let offset = reply
+                    .get("offset")
+                    .and_then(Value::as_u64)
+                    .ok_or(format!("{node} replied {reply} to send"))?;
+                let log = self.acked.entry(key.to_string()).or_default();
+                if let Some(other) = log.insert(offset, msg) {
+                    return Err(format!(
+                        "messages {other} and {msg} got the same offset {offset} of {key}"
+                    ));
+                }
+            }
+            Some("poll") => self.check_poll(proc, reply)?,
+            Some("list_committed_offsets") => {
+                let offsets = reply
+                    .get("offsets")
+                    .ok_or(format!("{node} replied {reply} to list_committed_offsets"))?;
+                for (key, log) in self.acked.iter() {
+                    let committed = *log.keys().last().unwrap();
+                    let listed = offsets.get(key).and_then(Value::as_u64);
+                    if listed.is_none_or(|listed| listed < committed) {
+                        return Err(format!(
+                            "{node} lost committed offset {committed} of {key}: {reply}"
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
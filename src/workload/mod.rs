@@ -0,0 +1,160 @@
+//! Maelstrom-style workloads which run inside the [`System`],
+//! so processes written for [`crate::maelstrom`] can be checked without Maelstrom.
+//!
+//! [`Workload`] produces client requests in phases and validates replies.
+//! [`Driver`] sends requests to processes as local messages,
+//! giving every request unique `msg_id`, and matches replies by `in_reply_to`.
+//! Node `n{i}` of the workload is the process with [`ProcessId`] `i`.
+
+mod broadcast;
+mod echo;
+mod g_counter;
+mod kafka;
+mod unique_ids;
+
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{json::Value, process::ProcessId, system::System};
+
+pub use broadcast::Broadcast;
+pub use echo::Echo;
+pub use g_counter::GCounter;
+pub use kafka::Kafka;
+pub use unique_ids::UniqueIds;
+
+/// Client side of the workload.
+pub trait Workload {
+    /// Returns requests of the next phase, as processes and bodies without `msg_id`.
+    ///
+    /// Called when the system has no pending events and every request is answered.
+    /// Empty result finishes the workload.
+    fn next_requests(&mut self) -> Vec<(ProcessId, Value)>;
+
+    /// Validates reply of the process to the request.
+    /// Type of the reply is already checked to be type of the request with `_ok` suffix.
+    fn on_reply(&mut self, proc: ProcessId, request: &Value, reply: &Value) -> Result<(), String>;
+
+    /// Validates workload after the last phase.
+    fn check(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Returns Maelstrom name of the node.
+pub fn node_id(proc: ProcessId) -> String {
+    format!("n{proc}")
+}
+
+/// Returns process of the node with Maelstrom name, `None` if name is not `n{i}`.
+pub fn parse_node_id(node: &str) -> Option<ProcessId> {
+    node.strip_prefix('n')?.parse().ok()
+}
+
+/// Runs workload against processes of the system.
+pub struct Driver<W> {
+    workload: W,
+    next_msg_id: u64,
+    waiting: HashMap<u64, (ProcessId, Value)>,
+    finished: bool,
+}
+
+impl<W: Workload> Driver<W> {
+    pub fn new(workload: W) -> Self {
+        Self {
+            workload,
+            next_msg_id: 0,
+            waiting: HashMap::new(),
+            finished: false,
+        }
+    }
+
+    pub fn workload(&self) -> &W {
+        &self.workload
+    }
+
+    /// Handles replies of processes and, if the system has no pending events,
+    /// sends requests of the next phase.
+    ///
+    /// Returns `true` when the workload is finished and checked.
+    /// Requests to crashed processes are skipped, and requests which they did not answer are forgotten.
+    /// Outputs of processes which are not replies are ignored.
+    pub fn step(&mut self, system: &mut System) -> Result<bool, String> {
+        for proc in 0..system.get_processes_count() {
+            for msg in system.read_local(proc) {
+                self.on_output(proc, &msg)?;
+            }
+        }
+        if self.finished {
+            return Ok(true);
+        }
+        if system.get_pending_events_count() > 0 {
+            return Ok(false);
+        }
+
+        self.waiting
+            .retain(|_, (proc, _)| !system.is_crashed(*proc));
+        if let Some((proc, request)) = self.waiting.values().next() {
+            return Err(format!(
+                "{} did not answer request {request}",
+                node_id(*proc)
+            ));
+        }
+
+        let requests = self.workload.next_requests();
+        if requests.is_empty() {
+            self.workload.check()?;
+            self.finished = true;
+            return Ok(true);
+        }
+        for (proc, mut request) in requests {
+            if system.is_crashed(proc) {
+                continue;
+            }
+            let msg_id = self.next_msg_id;
+            self.next_msg_id += 1;
+            request.set("msg_id", msg_id.into());
+            system.send_local_message(proc, &request.to_string());
+            self.waiting.insert(msg_id, (proc, request));
+        }
+        Ok(false)
+    }
+
+    fn on_output(&mut self, proc: ProcessId, msg: &str) -> Result<(), String> {
+        let Ok(reply) = Value::parse(msg) else {
+            return Ok(());
+        };
+        let Some(msg_id) = reply.get("in_reply_to").and_then(Value::as_u64) else {
+            return Ok(());
+        };
+        if !matches!(self.waiting.get(&msg_id), Some((to, _)) if *to == proc) {
+            return Ok(());
+        }
+        let (_, request) = self.waiting.remove(&msg_id).unwrap();
+
+        let request_type = request.get("type").and_then(Value::as_str).unwrap_or("");
+        let reply_type = reply.get("type").and_then(Value::as_str).unwrap_or("");
+        if reply_type != format!("{request_type}_ok") {
+            return Err(format!(
+                "{} replied {reply} to request {request}",
+                node_id(proc)
+            ));
+        }
+        self.workload.on_reply(proc, &request, &reply)
+    }
+}
+
+/// Runs workload to the end, applying pending events of the system in random order.
+pub fn run<W: Workload>(system: &mut System, workload: W, seed: u64) -> Result<W, String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut driver = Driver::new(workload);
+    loop {
+        let pending = system.get_pending_events_count();
+        if pending > 0 {
+            system.apply_pending_event(rng.gen_range(0..pending));
+        } else if driver.step(system)? {
+            return Ok(driver.workload);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use crate::{json::Value, process::ProcessId};
+
+use super::{node_id, Workload};
+
+/// Sends `generate` requests and checks that all generated ids are unique.
+pub struct UniqueIds {
+    nodes: usize,
+    requests: usize,
+    sent: bool,
+    ids: HashMap<String, ProcessId>,
+}
+
+impl UniqueIds {
+    /// Creates workload which sends `requests` requests to each of `nodes` nodes.
+    pub fn new(nodes: usize, requests: usize) -> Self {
+        Self {
+            nodes,
+            requests,
+            sent: false,
+            ids: HashMap::new(),
+        }
+    }
+
+    /// Returns number of generated ids.
+    pub fn generated(&self) -> usize {
+        self.ids.len()
+    }
+}
+
+impl Workload for UniqueIds {
+    fn next_requests(&mut self) -> Vec<(ProcessId, Value)> {
+        if self.sent {
+            return Vec::new();
+        }
+        self.sent = true;
+        (0..self.nodes)
+            .flat_map(|proc| (0..self.requests).map(move |_| proc))
+            .map(|proc| (proc, Value::object([("type", "generate".into())])))
+            .collect()
+    }
+
+    fn on_reply(&mut self, proc: ProcessId, _request: &Value, reply: &Value) -> Result<(), String> {
+        let id = reply
+            .get("id")
+            .ok_or(format!("{} replied {reply} without id", node_id(proc)))?;
+        if let Some(other) = self.ids.insert(id.to_string(), proc) {
+            return Err(format!(
+                "id {id} was generated by {} and {}",
+                node_id(other),
+                node_id(proc)
+            ));
+        }
+        Ok(())
+    }
+}
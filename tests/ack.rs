@@ -0,0 +1,80 @@
+use flurry::{EventKind, Latency};
+
+/// Sends message to the process given in local message,
+/// waiting for the ack no longer than the timeout, and reports the outcome.
+struct Sender {
+    timeout: Option<f64>,
+}
+
+impl flurry::Process for Sender {
+    fn on_message(&mut self, _from: flurry::ProcessId, _msg: String) {}
+
+    fn on_local_message(&mut self, msg: &str) {
+        let ack = flurry::send(msg.parse().unwrap(), "hi".to_string());
+        let timeout = self.timeout;
+        flurry::spawn(async move {
+            let outcome = match timeout {
+                Some(timeout) => ack.timeout(timeout).await,
+                None => ack.await,
+            };
+            flurry::send_local(format!("{outcome:?}"));
+        });
+    }
+}
+
+fn senders(timeout: Option<f64>) -> flurry::System {
+    let mut system = flurry::System::default();
+    for _ in 0..3 {
+        system.add_process(Sender { timeout });
+    }
+    system
+}
+
+#[test]
+fn outcomes() {
+    let mut system = senders(None);
+    system.send_local_message(0, "1");
+    system.send_local_message(0, "2");
+    system.apply_pending_event(0);
+    system.apply_pending_event(1);
+    system.drop_pending_event(0);
+    assert_eq!(system.read_local(0), vec!["Delivered", "Lost"]);
+
+    let mut system = senders(None);
+    system.send_local_message(0, "1");
+    system.send_local_message(1, "2");
+    system.send_local_message(2, "0");
+    system.crash_process(1);
+    system.send_local_message(0, "1");
+    assert_eq!(
+        system.read_local(0),
+        vec!["ReceiverCrashed", "ReceiverCrashed"]
+    );
+    system.apply_pending_event(0);
+    assert_eq!(
+        system.get_trace().last().unwrap().kind,
+        EventKind::AckDropped(2, 1, 1)
+    );
+    system.apply_pending_event(0);
+    system.crash_process(2);
+    assert_eq!(system.get_pending_events_count(), 0);
+}
+
+#[test]
+fn timeout() {
+    let mut system = senders(Some(5.0));
+    system.set_latency(Latency::Constant(2.0));
+    system.send_local_message(0, "1");
+    system.set_link_latency(0, 2, Latency::Constant(10.0));
+    system.send_local_message(0, "2");
+    while system.step() {}
+    assert_eq!(system.read_local(0), vec!["Delivered", "TimedOut"]);
+
+    // Timer is cancelled once the ack arrives.
+    let mut system = senders(Some(5.0));
+    system.set_latency(Latency::Constant(2.0));
+    system.send_local_message(0, "1");
+    while system.step() {}
+    assert_eq!(system.read_local(0), vec!["Delivered"]);
+    assert_eq!(system.now(), 4.0);
+}
@@ -0,0 +1,126 @@
+use flurry::{EventKind, TraceAnalysis};
+
+/// Sends every message to all others.
+/// Process 1 answers to the first message with its own one.
+struct BroadcastProcess {
+    me: flurry::ProcessId,
+    others: Vec<flurry::ProcessId>,
+}
+
+impl BroadcastProcess {
+    fn broadcast(&self, msg: String) {
+        for to in self.others.iter().copied() {
+            let msg = msg.clone();
+            flurry::spawn(async move {
+                flurry::send(to, msg).await;
+            });
+        }
+    }
+}
+
+impl flurry::Process for BroadcastProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        flurry::send_local(msg.clone());
+        if self.me == 1 && msg == "question" {
+            self.broadcast("answer".to_string());
+        }
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        self.broadcast(msg.to_string());
+    }
+}
+
+fn make_system() -> flurry::System {
+    let mut system = flurry::System::default();
+    for me in 0..3 {
+        let others = (0..3).filter(|p| *p != me).collect();
+        system.add_process(BroadcastProcess { me, others });
+    }
+    system.send_local_message(0, "question");
+    system
+}
+
+/// Checks that if broadcast of one message happens before broadcast of another,
+/// then every process delivers them in the same order.
+/// Broadcast of the message is the first sending of it.
+fn delivery_respects_causality(trace: &[flurry::Event]) -> bool {
+    let analysis = TraceAnalysis::new(trace);
+    let broadcast_of = |msg: &str| {
+        trace
+            .iter()
+            .position(|e| matches!(&e.kind, EventKind::MessageSent(_, _, _, m) if m == msg))
+            .unwrap()
+    };
+    let mut delivered = vec![Vec::new(); 3];
+    for event in trace {
+        if let EventKind::MessageDelivered(_, to, _, msg) = &event.kind {
+            let broadcast = broadcast_of(msg);
+            if delivered[*to]
+                .iter()
+                .any(|earlier| analysis.happens_before(broadcast, *earlier))
+            {
+                return false;
+            }
+            delivered[*to].push(broadcast);
+        }
+    }
+    true
+}
+
+#[test]
+fn causal_past() {
+    let mut system = make_system();
+    while system.get_pending_events_count() > 0 {
+        system.apply_pending_event(0);
+    }
+    let trace = system.get_trace();
+    let analysis = TraceAnalysis::new(&trace);
+    assert_eq!(analysis.len(), trace.len());
+
+    let answer_sent = trace
+        .iter()
+        .position(|e| matches!(&e.kind, EventKind::MessageSent(1, _, _, msg) if msg == "answer"))
+        .unwrap();
+    let past = analysis.causal_past(answer_sent);
+    assert!(past.contains(&0));
+    assert!(past.iter().any(|i| matches!(
+        &trace[*i].kind,
+        EventKind::MessageDelivered(0, 1, _, msg) if msg == "question"
+    )));
+    assert!(!past
+        .iter()
+        .any(|i| matches!(trace[*i].kind, EventKind::MessageDelivered(_, 2, _, _))));
+
+    for i in 0..trace.len() {
+        for j in 0..trace.len() {
+            if analysis.happens_before(i, j) {
+                assert!(analysis.lamport(i) < analysis.lamport(j));
+                assert!(!analysis.concurrent(i, j));
+            }
+        }
+    }
+    assert!(delivery_respects_causality(&trace));
+}
+
+#[test]
+fn detects_causality_violation() {
+    let mut system = make_system();
+    // deliver "question" only to process 1
+    system.apply_pending_event(0);
+    // now deliver everything except pending "question" to process 2 first
+    loop {
+        let pending = system.get_pending_events();
+        let Some(next) = pending.iter().position(
+            |e| !matches!(e, EventKind::MessageDelivered(0, 2, _, msg) if msg == "question"),
+        ) else {
+            break;
+        };
+        system.apply_pending_event(next);
+    }
+    while system.get_pending_events_count() > 0 {
+        system.apply_pending_event(0);
+    }
+    assert_eq!(system.read_local(2), vec!["answer", "question"]);
+    assert!(!delivery_respects_causality(&system.get_trace()));
+}
@@ -0,0 +1,114 @@
+use flurry::protocols::broadcast::{
+    self, BestEffort, Broadcast, BroadcastProcess, Causal, Fifo, Reliable, Uniform,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const PROCESSES: usize = 4;
+
+fn processes<B, F>(protocol: F) -> flurry::System
+where
+    B: Broadcast + 'static,
+    F: Fn(flurry::ProcessId) -> B,
+{
+    let mut system = flurry::System::default();
+    for proc in 0..PROCESSES {
+        system.add_process(BroadcastProcess::new(protocol(proc)));
+    }
+    system
+}
+
+/// Broadcasts two messages from every process, applying pending events in random order.
+fn random_run(system: &mut flurry::System, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for round in 0..2 {
+        for proc in 0..PROCESSES {
+            system.send_local_message(proc, &format!("m{round} from {proc}"));
+            for _ in 0..rng.gen_range(0..4) {
+                let pending = system.get_pending_events_count();
+                if pending > 0 {
+                    system.apply_pending_event(rng.gen_range(0..pending));
+                }
+            }
+        }
+    }
+    while system.get_pending_events_count() > 0 {
+        let pending = system.get_pending_events_count();
+        system.apply_pending_event(rng.gen_range(0..pending));
+    }
+}
+
+#[test]
+fn properties_hold() {
+    for seed in 0..20 {
+        let mut system = processes(|me| Reliable::new(me, PROCESSES));
+        random_run(&mut system, seed);
+        let trace = system.get_trace();
+        broadcast::check_validity(&trace, PROCESSES).unwrap();
+        broadcast::check_no_duplication(&trace, PROCESSES).unwrap();
+        broadcast::check_agreement(&trace, PROCESSES).unwrap();
+
+        let mut system = processes(|me| Fifo::new(Reliable::new(me, PROCESSES)));
+        random_run(&mut system, seed);
+        broadcast::check_fifo_order(&system.get_trace(), PROCESSES).unwrap();
+
+        let mut system = processes(|me| Causal::new(me, Uniform::new(me, PROCESSES)));
+        random_run(&mut system, seed);
+        let trace = system.get_trace();
+        broadcast::check_validity(&trace, PROCESSES).unwrap();
+        broadcast::check_uniform_agreement(&trace, PROCESSES).unwrap();
+        broadcast::check_fifo_order(&trace, PROCESSES).unwrap();
+        broadcast::check_causal_order(&trace, PROCESSES).unwrap();
+    }
+}
+
+#[test]
+fn violations_found() {
+    let mut system = processes(|me| BestEffort::new(me, PROCESSES));
+    system.send_local_message(0, "hello");
+    system.apply_pending_event(0);
+    system.crash_process(0);
+    while system.get_pending_events_count() > 0 {
+        system.drop_pending_event(0);
+    }
+    assert_eq!(
+        broadcast::check_agreement(&system.get_trace(), PROCESSES),
+        Err("process 2 did not deliver message 0:0 delivered by process 1".to_string())
+    );
+
+    let mut system = processes(|me| Reliable::new(me, PROCESSES));
+    system.send_local_message(0, "first");
+    system.send_local_message(0, "second");
+    system.apply_pending_event(3);
+    assert_eq!(
+        broadcast::check_fifo_order(&system.get_trace(), PROCESSES),
+        Err("process 1 delivered message 0:1 before 0:0".to_string())
+    );
+
+    let mut system = processes(|me| Fifo::new(Reliable::new(me, PROCESSES)));
+    system.send_local_message(0, "question");
+    system.apply_pending_event(0);
+    system.send_local_message(1, "answer");
+    let answer = system.get_pending_events_count() - 2;
+    system.apply_pending_event(answer);
+    assert!(broadcast::check_fifo_order(&system.get_trace(), PROCESSES).is_ok());
+    assert_eq!(
+        broadcast::check_causal_order(&system.get_trace(), PROCESSES),
+        Err("process 2 delivered message 1:0 before 0:0".to_string())
+    );
+}
+
+#[test]
+fn silent_process_checked() {
+    let mut system = flurry::System::default();
+    for proc in 0..PROCESSES {
+        system.add_process(BroadcastProcess::new(Reliable::new(proc, PROCESSES - 1)));
+    }
+    system.send_local_message(0, "hello");
+    while system.step() {}
+    let trace = system.get_trace();
+    assert!(broadcast::check_agreement(&trace, PROCESSES - 1).is_ok());
+    assert_eq!(
+        broadcast::check_agreement(&trace, PROCESSES),
+        Err("process 3 did not deliver message 0:0 delivered by process 0".to_string())
+    );
+}
@@ -0,0 +1,76 @@
+use flurry::{Delivery, Error, EventKind, Explorer};
+
+/// Sends numbered messages to the process 1 and reports received messages.
+struct Counter;
+
+impl flurry::Process for Counter {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        flurry::send_local(msg);
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        for i in 0..msg.parse().unwrap() {
+            flurry::send(1, i.to_string());
+        }
+    }
+}
+
+fn counters(messages: usize, delivery: Delivery) -> flurry::System {
+    let mut system = flurry::System::default();
+    system.add_process(Counter);
+    system.add_process(Counter);
+    system.set_delivery(delivery);
+    system.send_local_message(0, &messages.to_string());
+    system
+}
+
+#[test]
+fn fifo_links() {
+    let mut system = counters(3, Delivery::FifoReliable);
+    assert_eq!(system.get_pending_events_count(), 1);
+    assert_eq!(
+        system.try_drop_pending_event(0),
+        Err(Error::ReliableLink(0, 1))
+    );
+    system.apply_pending_event(0);
+    assert!(matches!(
+        system.get_pending_events()[..],
+        [
+            EventKind::MessageDelivered(0, 1, _, _),
+            EventKind::AckDelivered(1, 0, _)
+        ]
+    ));
+    while system.get_pending_events_count() > 0 {
+        let last = system.get_pending_events_count() - 1;
+        system.apply_pending_event(last);
+    }
+    assert_eq!(system.read_local(1), vec!["0", "1", "2"]);
+
+    let mut system = counters(3, Delivery::Unordered);
+    system.set_link_delivery(0, 1, Delivery::FifoLossy);
+    system.drop_pending_event(0);
+    system.apply_pending_event(0);
+    system.crash_process(1);
+    assert!(matches!(
+        system.get_pending_events()[..],
+        [EventKind::AckDelivered(1, 0, _)]
+    ));
+    assert_eq!(system.read_local(1), vec!["1"]);
+    let dropped = system
+        .get_trace()
+        .iter()
+        .filter(|event| matches!(event.kind, EventKind::MessageDropped(..)))
+        .count();
+    assert_eq!(dropped, 2);
+}
+
+#[test]
+fn fifo_shrinks_state_space() {
+    let states = |delivery| {
+        Explorer::new(move || counters(4, delivery))
+            .with_threads(1)
+            .explore()
+            .states
+    };
+    assert!(states(Delivery::FifoReliable) < states(Delivery::Unordered));
+}
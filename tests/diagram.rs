@@ -0,0 +1,111 @@
+struct PingProcess {}
+
+impl flurry::Process for PingProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        flurry::send_local(format!("got {msg}"));
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        let msg = msg.to_string();
+        flurry::spawn(async move {
+            flurry::send(1, msg.clone()).await;
+            flurry::send(2, msg).await;
+        });
+    }
+}
+
+fn run(msg: &str) -> Vec<flurry::Event> {
+    let mut system = flurry::System::default();
+    for _ in 0..3 {
+        system.add_process(PingProcess {});
+    }
+    system.send_local_message(0, msg);
+    system.apply_pending_event(0); // deliver to 1
+    system.apply_pending_event(0); // deliver ack to 0
+    system.get_trace()
+}
+
+#[test]
+fn mermaid() {
+    let trace = run("ping");
+
+    let diagram = flurry::render_mermaid(&trace, &flurry::DiagramOptions::default());
+    assert_eq!(
+        diagram,
+        "sequenceDiagram
+    participant P0
+    participant P1
+    participant P2
+    P0->>P1: m0: ping
+    P0-xP2: m1: ping (in flight)
+"
+    );
+
+    let diagram = flurry::render_mermaid(
+        &trace,
+        &flurry::DiagramOptions {
+            acks: true,
+            local_messages: true,
+        },
+    );
+    assert_eq!(
+        diagram,
+        "sequenceDiagram
+    participant P0
+    participant P1
+    participant P2
+    Note over P0: user: ping
+    P0->>P1: m0: ping
+    Note over P1: local: got ping
+    P1-->>P0: ack m0
+    P0-xP2: m1: ping (in flight)
+"
+    );
+
+    let diagram = flurry::render_mermaid(&run("#1; ok"), &flurry::DiagramOptions::default());
+    assert!(diagram.contains("P0->>P1: m0: #35;1#59; ok\n"));
+}
+
+#[test]
+fn plantuml() {
+    let diagram = flurry::render_plantuml(
+        &run("ping"),
+        &flurry::DiagramOptions {
+            acks: true,
+            local_messages: false,
+        },
+    );
+    assert_eq!(
+        diagram,
+        "@startuml
+participant P0
+participant P1
+participant P2
+P0 -> P1 : m0: ping
+P1 --> P0 : ack m0
+P0 ->x P2 : m1: ping (in flight)
+@enduml
+"
+    );
+}
+
+#[test]
+fn ascii() {
+    let diagram = flurry::render_ascii(
+        &run("ping"),
+        &flurry::DiagramOptions {
+            acks: true,
+            local_messages: true,
+        },
+    );
+    assert_eq!(
+        diagram,
+        "  time P0  P1  P2
+     0 *   |   |  user: ping
+     1 o-->+   |  m0: ping
+     1 |   *   |  local: got ping
+     2 +<--o   |  ack m0
+     2 o------>x  m1: ping (in flight)
+"
+    );
+}
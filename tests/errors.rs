@@ -0,0 +1,76 @@
+use flurry::Error;
+
+/// Reports results of the fallible functions as local messages.
+struct MisusingProcess;
+
+impl flurry::Process for MisusingProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, _msg: String) {}
+
+    fn on_local_message(&mut self, msg: &str) {
+        let to = msg.parse().unwrap();
+        let sent = flurry::try_send(to, "hi".to_string()).map(|_| ());
+        let spawned = flurry::try_spawn(async {}).map(|_| ());
+        flurry::try_send_local(format!("{sent:?} {spawned:?}")).unwrap();
+    }
+}
+
+#[test]
+fn system_errors() {
+    let mut system = flurry::System::default();
+    let proc = system.add_process(MisusingProcess);
+
+    assert_eq!(
+        system.try_send_local_message(1, "0"),
+        Err(Error::NoSuchProcess(1))
+    );
+    assert_eq!(system.try_read_local(1), Err(Error::NoSuchProcess(1)));
+    assert_eq!(system.try_crash_process(1), Err(Error::NoSuchProcess(1)));
+    assert_eq!(
+        system.try_apply_pending_event(0),
+        Err(Error::NoSuchEvent(0))
+    );
+    assert_eq!(system.try_drop_pending_event(0), Err(Error::NoSuchEvent(0)));
+
+    system.try_send_local_message(proc, "3").unwrap();
+    system.try_send_local_message(proc, "0").unwrap();
+    assert_eq!(
+        system.try_read_local(proc),
+        Ok(vec![
+            "Err(NoSuchProcess(3)) Ok(())".to_string(),
+            "Ok(()) Ok(())".to_string()
+        ])
+    );
+    assert_eq!(system.get_pending_events_count(), 1);
+
+    system.try_crash_process(proc).unwrap();
+    assert_eq!(
+        system.try_send_local_message(proc, "0"),
+        Err(Error::ProcessCrashed(proc))
+    );
+}
+
+#[test]
+fn outside_of_process() {
+    assert_eq!(
+        flurry::try_send(0, "hi".to_string()).err(),
+        Some(Error::NoProcessContext)
+    );
+    assert_eq!(
+        flurry::try_spawn(async {}).err(),
+        Some(Error::NoProcessContext)
+    );
+    assert_eq!(
+        flurry::try_send_local("hi".to_string()),
+        Err(Error::NoProcessContext)
+    );
+    assert_eq!(flurry::try_random(), Err(Error::NoProcessContext));
+    assert_eq!(flurry::try_random_range(0..2), Err(Error::NoProcessContext));
+    assert_eq!(flurry::try_now(), Err(Error::NoProcessContext));
+    assert_eq!(flurry::try_sleep(1.0).err(), Some(Error::NoProcessContext));
+    let policy = flurry::reliable::RetryPolicy::new(1.0).with_jitter(0.5);
+    let send = flurry::reliable::try_send(0, "hi".to_string(), policy);
+    assert_eq!(
+        futures::executor::block_on(send).err(),
+        Some(Error::NoProcessContext)
+    );
+}
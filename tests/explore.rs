@@ -0,0 +1,171 @@
+use std::collections::BTreeSet;
+
+use flurry::{
+    json::Value,
+    workload::{self, Broadcast, Driver},
+    Explorer,
+};
+
+/// Forwards every new message to its neighbours except the sender, unless it is `lazy`.
+struct Gossip {
+    me: flurry::ProcessId,
+    lazy: bool,
+    neighbours: Vec<flurry::ProcessId>,
+    seen: BTreeSet<u64>,
+}
+
+impl Gossip {
+    fn add(&mut self, msg: u64, from: Option<flurry::ProcessId>) {
+        if !self.seen.insert(msg) || self.lazy {
+            return;
+        }
+        for to in self.neighbours.clone() {
+            if Some(to) == from {
+                continue;
+            }
+            flurry::spawn(async move { flurry::send(to, msg.to_string()).await });
+        }
+    }
+}
+
+impl flurry::Process for Gossip {
+    fn on_message(&mut self, from: flurry::ProcessId, msg: String) {
+        self.add(msg.parse().unwrap(), Some(from));
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        let body = Value::parse(msg).unwrap();
+        let kind = body.get("type").and_then(Value::as_str).unwrap();
+        let mut reply = Value::object([
+            ("type", format!("{kind}_ok").into()),
+            ("in_reply_to", body.get("msg_id").unwrap().clone()),
+        ]);
+        match kind {
+            "topology" => {
+                self.neighbours = body
+                    .get("topology")
+                    .and_then(|t| t.get(&workload::node_id(self.me)))
+                    .and_then(Value::as_array)
+                    .unwrap()
+                    .iter()
+                    .map(|node| workload::parse_node_id(node.as_str().unwrap()).unwrap())
+                    .collect();
+            }
+            "broadcast" => self.add(body.get("message").and_then(Value::as_u64).unwrap(), None),
+            "read" => {
+                let messages: Vec<Value> = self.seen.iter().map(|m| (*m).into()).collect();
+                reply.set("messages", messages.into());
+            }
+            other => panic!("unexpected request {other}"),
+        }
+        flurry::send_local(reply.to_string());
+    }
+}
+
+fn gossip_system(nodes: usize, lazy: bool) -> flurry::System {
+    let mut system = flurry::System::default();
+    for me in 0..nodes {
+        system.add_process(Gossip {
+            me,
+            lazy,
+            neighbours: Vec::new(),
+            seen: BTreeSet::new(),
+        });
+    }
+    system
+}
+
+#[test]
+fn parallel_exploration() {
+    let factory = || (gossip_system(3, false), Driver::new(Broadcast::new(3, 1)));
+    let single = Explorer::new(factory).with_threads(1).explore();
+    assert!(single.violation.is_none());
+    let parallel = Explorer::new(factory).with_threads(4).explore();
+    assert!(parallel.violation.is_none());
+    assert_eq!(single.states, parallel.states);
+
+    let shallow = Explorer::new(factory).with_max_depth(3).explore();
+    assert!(shallow.states < single.states);
+}
+
+#[test]
+fn violation_replays() {
+    let factory = || (gossip_system(3, true), Driver::new(Broadcast::new(3, 1)));
+    let report = Explorer::new(factory).with_threads(4).explore();
+    let violation = report.violation.unwrap();
+    assert_eq!(violation.error, "n1 lost message 0");
+
+    let system = flurry::replay(&violation.schedule, || gossip_system(3, true)).unwrap();
+    let applied = violation
+        .schedule
+        .steps
+        .iter()
+        .filter(|step| matches!(step, flurry::ScheduleStep::ApplyEvent(..)))
+        .count();
+    assert_eq!(applied, violation.path.len());
+    assert_eq!(system.get_pending_events_count(), 0);
+}
+
+/// Process 0 sends `a` to 1 and `b` to 2 on local message, and 2 sends `c` to 1 on `b`.
+struct Relay;
+
+impl flurry::Process for Relay {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        if msg == "b" {
+            flurry::send_unacked(1, "c".to_string());
+        }
+    }
+
+    fn on_local_message(&mut self, _msg: &str) {
+        flurry::send_unacked(1, "a".to_string());
+        flurry::send_unacked(2, "b".to_string());
+    }
+}
+
+/// Delivers `a` by itself once `b` is delivered, so the same state
+/// is reached both by the path `[a, b]` and by the shorter path `[b]`.
+struct RelayModel(flurry::System);
+
+impl flurry::Model for RelayModel {
+    fn system(&mut self) -> &mut flurry::System {
+        &mut self.0
+    }
+
+    fn visit(&mut self) -> Result<bool, String> {
+        let pending = self.0.get_pending_events();
+        let delivered = |msg: &str| {
+            self.0.get_trace().iter().any(|event| {
+                matches!(&event.kind, flurry::EventKind::MessageDelivered(_, _, _, m) if m == msg)
+            })
+        };
+        if delivered("c") {
+            return Err("c delivered".to_string());
+        }
+        if delivered("b") {
+            let a = pending.iter().position(
+                |kind| matches!(kind, flurry::EventKind::MessageDelivered(_, _, _, m) if m == "a"),
+            );
+            if let Some(a) = a {
+                self.0.apply_pending_event(a);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[test]
+fn shallower_path() {
+    let factory = || {
+        let mut system = flurry::System::default();
+        for _ in 0..3 {
+            system.add_process(Relay);
+        }
+        system.send_local_message(0, "go");
+        RelayModel(system)
+    };
+    let report = Explorer::new(factory)
+        .with_threads(1)
+        .with_max_depth(2)
+        .explore();
+    assert_eq!(report.violation.unwrap().path, vec![1, 0]);
+}
@@ -0,0 +1,140 @@
+use flurry::{
+    fd::{self, EventuallyPerfect, FailureDetector, Omega, Perfect},
+    Latency,
+};
+
+const PROCESSES: usize = 3;
+
+/// Starts detectors on the first local message and reports their suspicions and leaders.
+struct FdProcess {
+    me: flurry::ProcessId,
+    perfect: bool,
+    detector: Option<EventuallyPerfect>,
+}
+
+impl FdProcess {
+    fn report<D: FailureDetector + Clone + 'static>(detector: D) {
+        fd::report_suspicions(detector.clone());
+        fd::report_leader(Omega::new(0..PROCESSES, detector));
+    }
+}
+
+impl flurry::Process for FdProcess {
+    fn on_message(&mut self, from: flurry::ProcessId, msg: String) {
+        if let Some(detector) = &self.detector {
+            assert!(detector.on_message(from, &msg));
+        }
+    }
+
+    fn on_local_message(&mut self, _msg: &str) {
+        if self.perfect {
+            Self::report(Perfect::new());
+            return;
+        }
+        let me = self.me;
+        let detector = EventuallyPerfect::start((0..PROCESSES).filter(|p| *p != me), 1.0, 2.0);
+        Self::report(detector.clone());
+        self.detector = Some(detector);
+    }
+}
+
+fn processes(perfect: bool) -> flurry::System {
+    let mut system = flurry::System::with_seed(3);
+    for me in 0..PROCESSES {
+        system.add_process(FdProcess {
+            me,
+            perfect,
+            detector: None,
+        });
+    }
+    system
+}
+
+fn start(system: &mut flurry::System) {
+    for proc in 0..PROCESSES {
+        system.send_local_message(proc, "start");
+    }
+}
+
+fn run_until(system: &mut flurry::System, time: f64) {
+    while system.now() < time && system.step() {}
+}
+
+fn check_all(system: &flurry::System) -> Vec<Result<(), String>> {
+    let trace = system.get_trace();
+    vec![
+        fd::check_strong_completeness(&trace, PROCESSES),
+        fd::check_strong_accuracy(&trace, PROCESSES),
+        fd::check_eventual_strong_accuracy(&trace, PROCESSES),
+        fd::check_eventual_leadership(&trace, PROCESSES),
+    ]
+}
+
+#[test]
+fn perfect() {
+    let mut system = processes(true);
+    start(&mut system);
+    assert_eq!(system.read_local(1), vec!["leader 0"]);
+    assert_eq!(system.read_local(2), vec!["leader 0"]);
+    assert_eq!(check_all(&system)[0], Ok(()));
+
+    system.crash_process(0);
+    assert_eq!(system.read_local(1), vec!["suspect 0", "leader 1"]);
+    assert_eq!(system.read_local(2), vec!["suspect 0", "leader 1"]);
+    assert!(check_all(&system).into_iter().all(|result| result.is_ok()));
+}
+
+#[test]
+fn eventually_perfect() {
+    let mut system = processes(false);
+    system.set_latency(Latency::Constant(0.5));
+    start(&mut system);
+    run_until(&mut system, 10.0);
+    assert_eq!(system.read_local(2), vec!["leader 0"]);
+
+    system.crash_process(0);
+    assert_eq!(
+        check_all(&system)[0],
+        Err("crashed process 0 is not suspected by correct process 1".to_string())
+    );
+    run_until(&mut system, 20.0);
+    assert_eq!(system.read_local(2), vec!["suspect 0", "leader 1"]);
+    assert!(check_all(&system).into_iter().all(|result| result.is_ok()));
+}
+
+#[test]
+fn false_suspicions() {
+    let mut system = processes(false);
+    system.set_latency(Latency::Constant(0.5));
+    system.set_link_latency(2, 1, Latency::Constant(3.0));
+    start(&mut system);
+    run_until(&mut system, 30.0);
+    assert_eq!(
+        system.read_local(1),
+        vec!["leader 0", "suspect 2", "restore 2"]
+    );
+    let results = check_all(&system);
+    assert_eq!(
+        results[1],
+        Err("process 1 suspected process 2 before it crashed".to_string())
+    );
+    assert_eq!(results[2], Ok(()));
+    assert_eq!(results[3], Ok(()));
+}
+
+#[test]
+fn silent_process_checked() {
+    let mut system = processes(true);
+    system.send_local_message(0, "start");
+    system.send_local_message(1, "start");
+    system.crash_process(0);
+    let results = check_all(&system);
+    assert_eq!(
+        results[0],
+        Err("crashed process 0 is not suspected by correct process 2".to_string())
+    );
+    assert_eq!(
+        results[3],
+        Err("correct process 2 reported no leader".to_string())
+    );
+}
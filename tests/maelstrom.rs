@@ -0,0 +1,106 @@
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+use flurry::json::Value;
+
+/// Answers `echo` requests and relays `relay` requests to the other node,
+/// replying when the other node acknowledges the message.
+struct RelayProcess {
+    other: flurry::ProcessId,
+}
+
+impl flurry::Process for RelayProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        flurry::send_local(format!("got {msg}"));
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        let body = Value::parse(msg).unwrap();
+        let msg_id = body.get("msg_id").unwrap().clone();
+        match body.get("type").and_then(Value::as_str) {
+            Some("echo") => {
+                let reply = Value::object([
+                    ("type", "echo_ok".into()),
+                    ("in_reply_to", msg_id),
+                    ("echo", body.get("echo").unwrap().clone()),
+                ]);
+                flurry::send_local(reply.to_string());
+            }
+            Some("relay") => {
+                let to = self.other;
+                let msg = body.get("msg").and_then(Value::as_str).unwrap().to_string();
+                flurry::spawn(async move {
+                    assert_eq!(flurry::send(to, msg).await, flurry::AckOutcome::Delivered);
+                    let reply =
+                        Value::object([("type", "relay_ok".into()), ("in_reply_to", msg_id)]);
+                    flurry::send_local(reply.to_string());
+                });
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn adapter() {
+    let input = r#"{"src":"c0","dest":"n1","body":{"type":"init","msg_id":1,"node_id":"n1","node_ids":["n0","n1"]}}
+{"src":"c1","dest":"n1","body":{"type":"relay","msg_id":5,"msg":"hi"}}
+{"src":"n0","dest":"n1","body":{"type":"flurry_ok","in_reply_to":0}}
+{"src":"n0","dest":"n1","body":{"type":"flurry","msg_id":7,"msg":"ping"}}
+{"src":"c2","dest":"n1","body":{"type":"echo","msg_id":5,"echo":"hello"}}
+"#;
+    let (output, log) = (Output::default(), Output::default());
+    flurry::maelstrom::run_with(
+        input.as_bytes(),
+        output.clone(),
+        log.clone(),
+        |me, nodes| {
+            assert_eq!(me, 1);
+            assert_eq!(nodes, 2);
+            RelayProcess { other: 0 }
+        },
+    )
+    .unwrap();
+
+    let output = String::from_utf8(output.0.borrow().clone()).unwrap();
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            r#"{"src":"n1","dest":"c0","body":{"type":"init_ok","in_reply_to":1}}"#,
+            r#"{"src":"n1","dest":"n0","body":{"type":"flurry","msg_id":0,"msg":"hi"}}"#,
+            r#"{"src":"n1","dest":"c1","body":{"type":"relay_ok","in_reply_to":5}}"#,
+            r#"{"src":"n1","dest":"n0","body":{"type":"flurry_ok","in_reply_to":7}}"#,
+            r#"{"src":"n1","dest":"c2","body":{"type":"echo_ok","in_reply_to":5,"echo":"hello"}}"#,
+        ]
+    );
+    assert_eq!(log.0.borrow().as_slice(), b"got ping\n");
+}
+
+#[test]
+fn same_process_in_simulation() {
+    let mut system = flurry::System::default();
+    system.add_process(RelayProcess { other: 1 });
+    system.add_process(RelayProcess { other: 0 });
+    system.send_local_message(0, r#"{"type":"relay","msg_id":0,"msg":"hi"}"#);
+    while system.get_pending_events_count() > 0 {
+        system.apply_pending_event(0);
+    }
+    assert_eq!(system.read_local(1), vec!["got hi"]);
+    assert_eq!(
+        system.read_local(0),
+        vec![r#"{"type":"relay_ok","in_reply_to":0}"#]
+    );
+}
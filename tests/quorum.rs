@@ -0,0 +1,155 @@
+use flurry::quorum::{self, Replies};
+
+/// Process 0 asks others for votes in a new round on every local message,
+/// others vote in the round.
+struct Voter {
+    replies: Replies<String>,
+    round: u64,
+    peers: Vec<flurry::ProcessId>,
+}
+
+impl flurry::Process for Voter {
+    fn on_message(&mut self, from: flurry::ProcessId, msg: String) {
+        match msg.split_once(' ') {
+            Some(("vote?", round)) => {
+                flurry::send(from, format!("yes {round}"));
+            }
+            Some((_, round)) => self.replies.put(from, round.parse().unwrap(), msg),
+            None => {}
+        }
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        self.round += 1;
+        let (peers, round) = (self.peers.clone(), self.round);
+        let vote = format!("vote? {round}");
+        let collect = match msg.strip_prefix("unacked ") {
+            Some(k) => quorum::broadcast_unacked(peers, vote).collect(
+                &self.replies,
+                round,
+                k.parse().unwrap(),
+            ),
+            None => {
+                quorum::broadcast(peers, vote).collect(&self.replies, round, msg.parse().unwrap())
+            }
+        };
+        flurry::spawn(async move {
+            let result = collect.await;
+            flurry::send_local(format!("{result:?}"));
+        });
+    }
+}
+
+fn voters() -> flurry::System {
+    let mut system = flurry::System::default();
+    for _ in 0..4 {
+        system.add_process(Voter {
+            replies: Replies::new(),
+            round: 0,
+            peers: vec![1, 2, 3],
+        });
+    }
+    system
+}
+
+/// Applies the last pending event until there are none.
+fn run(system: &mut flurry::System) {
+    while system.get_pending_events_count() > 0 {
+        let last = system.get_pending_events_count() - 1;
+        system.apply_pending_event(last);
+    }
+}
+
+#[test]
+fn collect_replies() {
+    let mut system = voters();
+    system.send_local_message(0, "2");
+    run(&mut system);
+    assert_eq!(
+        system.read_local(0),
+        vec![r#"Ok([(3, "yes 1"), (2, "yes 1")])"#]
+    );
+
+    let mut system = voters();
+    system.send_local_message(0, "2");
+    system.drop_pending_event(0);
+    assert!(system.read_local(0).is_empty());
+    system.crash_process(2);
+    assert_eq!(system.read_local(0), vec!["Err([])"]);
+}
+
+#[test]
+fn late_reply_ignored() {
+    let mut system = voters();
+    system.send_local_message(0, "unacked 2");
+    system.apply_pending_event(0);
+    system.send_local_message(0, "unacked 3");
+    // Reply of process 1 in the first round arrives during the second one.
+    system.apply_pending_event(2);
+    run(&mut system);
+    assert_eq!(
+        system.read_local(0),
+        vec![
+            "Err([])",
+            r#"Ok([(3, "yes 2"), (2, "yes 2"), (1, "yes 2")])"#
+        ]
+    );
+}
+
+#[test]
+fn collect_unacked() {
+    let mut system = voters();
+    system.send_local_message(0, "unacked 2");
+    assert!(system
+        .get_pending_events()
+        .iter()
+        .all(|event| matches!(event, flurry::EventKind::MessageDelivered(..))));
+    system.drop_pending_event(0);
+    run(&mut system);
+    assert_eq!(
+        system.read_local(0),
+        vec![r#"Ok([(3, "yes 1"), (2, "yes 1")])"#]
+    );
+}
+
+/// Reports which processes acknowledged the message.
+struct Pinger;
+
+impl flurry::Process for Pinger {
+    fn on_message(&mut self, _from: flurry::ProcessId, _msg: String) {}
+
+    fn on_local_message(&mut self, msg: &str) {
+        let acks = quorum::broadcast([1, 2, 3], "ping".to_string()).await_k(msg.parse().unwrap());
+        let handles = (1..4).map(|proc| (proc, flurry::spawn(async move { proc * 2 })));
+        let join = quorum::join(handles.collect::<Vec<_>>()).await_k(2);
+        flurry::spawn(async move {
+            flurry::send_local(format!("{:?}", acks.await));
+            flurry::send_local(format!("{:?}", join.await));
+        });
+    }
+}
+
+#[test]
+fn await_acks() {
+    let mut system = flurry::System::default();
+    for _ in 0..4 {
+        system.add_process(Pinger);
+    }
+    system.send_local_message(0, "2");
+    system.apply_pending_event(2);
+    system.drop_pending_event(0);
+    assert!(system.read_local(0).is_empty());
+    run(&mut system);
+    assert_eq!(
+        system.read_local(0),
+        vec!["Ok([3, 2])", "[(1, 2), (2, 4), (3, 6)]"]
+    );
+
+    let mut system = flurry::System::default();
+    for _ in 0..4 {
+        system.add_process(Pinger);
+    }
+    system.send_local_message(0, "3");
+    system.drop_pending_event(1);
+    assert_eq!(system.read_local(0)[0], "Err([])");
+}
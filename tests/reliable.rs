@@ -0,0 +1,98 @@
+use std::collections::BTreeSet;
+
+use flurry::{
+    reliable::{self, RetryPolicy},
+    EventKind, Latency,
+};
+
+/// Reliably sends local messages to the process 1 and reports the outcome,
+/// and counts distinct received messages.
+struct Node {
+    policy: RetryPolicy,
+    received: BTreeSet<String>,
+}
+
+impl flurry::Process for Node {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        self.received.insert(msg);
+        flurry::send_local(format!("received {}", self.received.len()));
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        let (msg, policy) = (msg.to_string(), self.policy.clone());
+        flurry::spawn(async move {
+            let outcome = reliable::send(1, msg, policy).await;
+            flurry::send_local(format!("{outcome:?} at {}", flurry::now()));
+        });
+    }
+}
+
+fn pair(policy: RetryPolicy) -> flurry::System {
+    let mut system = flurry::System::with_seed(3);
+    for _ in 0..2 {
+        system.add_process(Node {
+            policy: policy.clone(),
+            received: BTreeSet::new(),
+        });
+    }
+    system.set_latency(Latency::Constant(1.0));
+    system
+}
+
+#[test]
+fn retries() {
+    let mut system = pair(RetryPolicy::new(3.0));
+    system.send_local_message(0, "hello");
+    system.drop_pending_event(0);
+    while system.step() {}
+    assert_eq!(system.read_local(0), vec!["Delivered at 5"]);
+    assert!(system
+        .get_trace()
+        .iter()
+        .any(|event| event.kind == EventKind::MessageRetried(0, 1, 0, 2)));
+
+    let mut system = pair(RetryPolicy::new(3.0).with_max_attempts(2));
+    system.send_local_message(0, "hello");
+    loop {
+        let pending = system.get_pending_events();
+        match pending
+            .iter()
+            .position(|kind| matches!(kind, EventKind::MessageDelivered(..)))
+        {
+            Some(event) => system.drop_pending_event(event),
+            None if system.step() => {}
+            None => break,
+        }
+    }
+    assert_eq!(system.read_local(0), vec!["TimedOut at 9"]);
+
+    let mut system = pair(RetryPolicy::new(3.0).with_jitter(0.5));
+    system.send_local_message(0, "hello");
+    system.drop_pending_event(0);
+    while system.step() {}
+    let time: f64 = system.read_local(0)[0]
+        .strip_prefix("Delivered at ")
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(time > 5.0 && time < 6.5);
+}
+
+#[test]
+fn duplicate_delivery() {
+    let mut system = pair(RetryPolicy::new(5.0));
+    system.send_local_message(0, "hello");
+    system.duplicate_pending_event(0);
+    while system.step() {}
+    assert_eq!(system.read_local(1), vec!["received 1", "received 1"]);
+    assert_eq!(system.read_local(0), vec!["Delivered at 2"]);
+    assert!(system.try_duplicate_pending_event(0).is_err());
+
+    // Message is not lost while its duplicate is pending.
+    let mut system = pair(RetryPolicy::new(5.0));
+    system.send_local_message(0, "hello");
+    system.duplicate_pending_event(0);
+    system.drop_pending_event(0);
+    while system.step() {}
+    assert_eq!(system.read_local(0), vec!["Delivered at 2"]);
+}
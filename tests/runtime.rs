@@ -0,0 +1,120 @@
+use std::{collections::HashSet, time::Duration};
+
+use flurry::runtime::Runtime;
+
+/// Eager reliable broadcast, which reports every delivered message
+/// and every acknowledged sending.
+struct BroadcastProcess {
+    others: Vec<flurry::ProcessId>,
+    delivered: HashSet<String>,
+}
+
+impl BroadcastProcess {
+    fn broadcast(&mut self, msg: String) {
+        if !self.delivered.insert(msg.clone()) {
+            return;
+        }
+        flurry::send_local(format!("delivered {msg}"));
+        for to in self.others.iter().copied() {
+            let msg = msg.clone();
+            flurry::spawn(async move {
+                if flurry::send(to, msg.clone()).await == flurry::AckOutcome::Delivered {
+                    flurry::send_local(format!("acked {msg} by {to}"));
+                }
+            });
+        }
+    }
+}
+
+impl flurry::Process for BroadcastProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        self.broadcast(msg);
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        self.broadcast(msg.to_string());
+    }
+}
+
+fn make_process(me: flurry::ProcessId, procs: usize) -> BroadcastProcess {
+    BroadcastProcess {
+        others: (0..procs).filter(|p| *p != me).collect(),
+        delivered: HashSet::new(),
+    }
+}
+
+fn sorted(mut msgs: Vec<String>) -> Vec<String> {
+    msgs.sort();
+    msgs
+}
+
+#[test]
+fn broadcast_over_udp() {
+    let mut runtime = Runtime::new();
+    for me in 0..3 {
+        let proc = runtime
+            .add_process("127.0.0.1:0".parse().unwrap(), move || make_process(me, 3))
+            .unwrap();
+        assert_eq!(proc, me);
+    }
+    let runtime = runtime.start().unwrap();
+    assert_ne!(runtime.addr(0), runtime.addr(1));
+
+    runtime.send_local_message(0, "hello");
+    let msgs = runtime.wait_local(0, 3, Duration::from_secs(10));
+    assert_eq!(
+        sorted(msgs),
+        vec!["acked hello by 1", "acked hello by 2", "delivered hello"]
+    );
+    for proc in 1..3 {
+        let msgs = runtime.wait_local(proc, 3, Duration::from_secs(10));
+        assert_eq!(msgs[0], "delivered hello");
+        assert_eq!(msgs.len(), 3);
+    }
+
+    runtime.shutdown();
+}
+
+#[test]
+fn same_process_in_simulation() {
+    let mut system = flurry::System::default();
+    for me in 0..3 {
+        system.add_process(make_process(me, 3));
+    }
+    system.send_local_message(0, "hello");
+    while system.get_pending_events_count() > 0 {
+        system.apply_pending_event(0);
+    }
+    assert_eq!(
+        sorted(system.read_local(0)),
+        vec!["acked hello by 1", "acked hello by 2", "delivered hello"]
+    );
+}
+
+#[test]
+fn remote_processes() {
+    let mut first = Runtime::new();
+    let mut second = Runtime::new();
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let second_addr = socket.local_addr().unwrap();
+    drop(socket);
+
+    first
+        .add_process("127.0.0.1:0".parse().unwrap(), || make_process(0, 2))
+        .unwrap();
+    first.add_remote(second_addr);
+    let first = first.start().unwrap();
+
+    second.add_remote(first.addr(0));
+    second
+        .add_process(second_addr, || make_process(1, 2))
+        .unwrap();
+    let second = second.start().unwrap();
+
+    second.send_local_message(1, "from second");
+    let msgs = first.wait_local(0, 2, Duration::from_secs(10));
+    assert_eq!(
+        sorted(msgs),
+        vec!["acked from second by 1", "delivered from second"]
+    );
+}
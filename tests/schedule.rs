@@ -0,0 +1,157 @@
+use flurry::{EventKind, ScheduleStep};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Forwards every message to the random other process.
+struct RandomForwardProcess {
+    me: flurry::ProcessId,
+    procs: u64,
+}
+
+impl RandomForwardProcess {
+    fn forward(&self, msg: String) {
+        let to = loop {
+            let to = flurry::random_range(0..self.procs) as usize;
+            if to != self.me {
+                break to;
+            }
+        };
+        flurry::spawn(async move {
+            flurry::send(to, msg).await;
+        });
+    }
+}
+
+impl flurry::Process for RandomForwardProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        flurry::send_local(msg.clone());
+        if msg.len() < 6 {
+            self.forward(format!("{msg}+"));
+        }
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        self.forward(msg.to_string());
+    }
+}
+
+fn make_system(seed: u64) -> flurry::System {
+    let mut system = flurry::System::with_seed(seed);
+    for me in 0..4 {
+        system.add_process(RandomForwardProcess { me, procs: 4 });
+    }
+    system.send_local_message(0, "a");
+    system
+}
+
+fn random_run(seed: u64) -> flurry::System {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut system = make_system(seed);
+    system.send_local_message(1, "b");
+    while system.get_pending_events_count() > 0 {
+        let event = rng.gen_range(0..system.get_pending_events_count());
+        match rng.gen_range(0..10) {
+            0 => system.drop_pending_event(event),
+            1 if !system.is_crashed(3) => system.crash_process(3),
+            _ => system.apply_pending_event(event),
+        }
+    }
+    system
+}
+
+#[test]
+fn record_and_replay() {
+    for seed in 0..20 {
+        let system = random_run(seed);
+        let schedule = system.get_schedule();
+        assert_eq!(schedule.steps[0], ScheduleStep::Seed(seed));
+        assert!(schedule
+            .steps
+            .iter()
+            .any(|step| matches!(step, ScheduleStep::Random(_))));
+
+        let mut buf = Vec::new();
+        schedule.write(&mut buf).unwrap();
+        let read = flurry::Schedule::read(buf.as_slice()).unwrap();
+        assert_eq!(read, schedule);
+
+        let replayed = flurry::replay(&read, || make_system(seed)).unwrap();
+        assert_eq!(replayed.get_trace(), system.get_trace());
+    }
+}
+
+#[test]
+fn report_divergence() {
+    let schedule = random_run(1).get_schedule();
+
+    let divergence = flurry::replay(&schedule, || make_system(2)).err().unwrap();
+    assert_eq!(divergence.step, 0);
+    assert_eq!(divergence.expected, Some(ScheduleStep::Seed(1)));
+    assert_eq!(divergence.actual, Some(ScheduleStep::Seed(2)));
+
+    let divergence = flurry::replay(&schedule, || {
+        let mut system = make_system(1);
+        system.send_local_message(2, "c");
+        system
+    })
+    .err()
+    .unwrap();
+    assert_eq!(divergence.step, 3);
+    assert_eq!(
+        divergence.actual,
+        Some(ScheduleStep::LocalMessage(2, "c".to_string()))
+    );
+
+    let mut truncated = schedule.clone();
+    truncated.steps.truncate(5);
+    let divergence = flurry::replay(&truncated, || random_run(1)).err().unwrap();
+    assert_eq!(divergence.step, 5);
+    assert_eq!(divergence.expected, None);
+
+    for step in [
+        ScheduleStep::LocalMessage(9, "c".to_string()),
+        ScheduleStep::Crash(9),
+    ] {
+        let mut schedule = make_system(1).get_schedule();
+        schedule.steps.push(step.clone());
+        let divergence = flurry::replay(&schedule, || make_system(1)).err().unwrap();
+        assert_eq!(divergence.step, schedule.steps.len() - 1);
+        assert_eq!(divergence.expected, Some(step));
+        assert_eq!(divergence.actual, None);
+    }
+}
+
+#[test]
+fn faults() {
+    let mut system = flurry::System::default();
+    for me in 0..2 {
+        system.add_process(RandomForwardProcess { me, procs: 2 });
+    }
+    system.send_local_message(0, "a");
+    system.send_local_message(0, "b");
+    assert_eq!(system.get_pending_events_count(), 2);
+
+    system.drop_pending_event(0);
+    system.apply_pending_event(0);
+    assert_eq!(system.read_local(1), vec!["b"]);
+    // ack for "b" and message "b+" are pending
+    assert_eq!(system.get_pending_events_count(), 2);
+
+    system.crash_process(0);
+    assert!(system.is_crashed(0));
+    assert_eq!(system.get_pending_events_count(), 0);
+
+    let trace = system.get_trace();
+    assert!(matches!(
+        trace[trace.len() - 3].kind,
+        EventKind::ProcessCrashed(0)
+    ));
+    assert!(trace
+        .iter()
+        .any(|e| matches!(e.kind, EventKind::MessageDropped(0, 1, 0))));
+    assert!(trace
+        .iter()
+        .any(|e| matches!(e.kind, EventKind::AckDropped(1, 0, 1))));
+    assert!(trace
+        .iter()
+        .any(|e| matches!(e.kind, EventKind::MessageDropped(1, 0, 2))));
+}
@@ -101,3 +101,42 @@ fn send() {
         assert_eq!(sender_proc_local[0], format!("sent: {}", 1 + i / 2));
     }
 }
+
+/// Passes every message to the next process without acks, until it made the round.
+struct Relay {
+    next: flurry::ProcessId,
+}
+
+impl flurry::Process for Relay {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        flurry::send_local(msg.clone());
+        if self.next != 0 {
+            flurry::send_unacked(self.next, msg);
+        }
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        flurry::send_unacked(self.next, msg.to_string());
+    }
+}
+
+#[test]
+fn send_unacked() {
+    let mut system = flurry::System::default();
+    for proc in 0..3 {
+        system.add_process(Relay {
+            next: (proc + 1) % 3,
+        });
+    }
+    system.send_local_message(0, "token");
+    while system.get_pending_events_count() > 0 {
+        assert_eq!(system.get_pending_events_count(), 1);
+        system.apply_pending_event(0);
+    }
+    assert_eq!(system.read_local(2), vec!["token"]);
+    assert_eq!(system.read_local(1), vec!["token"]);
+    assert!(system.get_trace().iter().all(|event| !matches!(
+        event.kind,
+        EventKind::AckSent(..) | EventKind::AckDelivered(..)
+    )));
+}
@@ -7,13 +7,13 @@ impl flurry::Process for SpawnProcess {
 
     fn on_local_message(&mut self, _: &str) {
         flurry::spawn(async move {
-            flurry::send_local(format!("spawn1"));
+            flurry::send_local("spawn1".to_string());
             let res2 = flurry::spawn(async move {
-                flurry::send_local(format!("spawn2"));
+                flurry::send_local("spawn2".to_string());
                 2
             });
             let res3 = flurry::spawn(async move {
-                flurry::send_local(format!("spawn3"));
+                flurry::send_local("spawn3".to_string());
                 res2.await + 3
             });
             let total_result = res3.await + 1; // must be 2+3+1
@@ -0,0 +1,79 @@
+/// Sends local message to the other process and reports every received message locally.
+struct EchoProcess {
+    other: flurry::ProcessId,
+}
+
+impl flurry::Process for EchoProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        flurry::send_local(msg);
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        let to = self.other;
+        let msg = msg.to_string();
+        flurry::spawn(async move {
+            flurry::send(to, msg).await;
+        });
+    }
+}
+
+fn pair() -> flurry::System {
+    let mut system = flurry::System::default();
+    system.add_process(EchoProcess { other: 1 });
+    system.add_process(EchoProcess { other: 0 });
+    system
+}
+
+#[test]
+fn interleaved_systems() {
+    let mut first = pair();
+    let mut second = pair();
+    first.send_local_message(0, "first");
+    second.send_local_message(0, "second");
+    while first.get_pending_events_count() > 0 || second.get_pending_events_count() > 0 {
+        if first.get_pending_events_count() > 0 {
+            first.apply_pending_event(0);
+        }
+        if second.get_pending_events_count() > 0 {
+            second.apply_pending_event(0);
+        }
+    }
+    assert_eq!(first.read_local(1), vec!["first"]);
+    assert_eq!(second.read_local(1), vec!["second"]);
+    assert_eq!(first.get_trace().len(), second.get_trace().len());
+}
+
+/// Runs inner system on every local message and reports what it delivered.
+struct NestedProcess;
+
+impl flurry::Process for NestedProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, _msg: String) {
+        unreachable!()
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        let mut inner = pair();
+        inner.send_local_message(1, msg);
+        while inner.get_pending_events_count() > 0 {
+            inner.apply_pending_event(0);
+        }
+        let delivered = inner.read_local(0);
+        flurry::send_local(format!("inner delivered {delivered:?}"));
+    }
+}
+
+#[test]
+fn nested_system() {
+    let mut outer = flurry::System::default();
+    outer.add_process(NestedProcess);
+    outer.send_local_message(0, "hi");
+    assert_eq!(outer.read_local(0), vec![r#"inner delivered ["hi"]"#]);
+}
+
+#[test]
+#[should_panic(expected = "no system available")]
+fn no_system_outside_of_step() {
+    let mut system = pair();
+    system.send_local_message(0, "msg");
+    flurry::send_local("outside".to_string());
+}
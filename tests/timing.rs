@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+use flurry::{runtime::Runtime, EventKind, Latency, LocalClock, Schedule};
+
+/// Pings the other process on local message and reports the time of the ack,
+/// and reports the time of every received ping.
+struct PingProcess {
+    other: flurry::ProcessId,
+}
+
+impl flurry::Process for PingProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, _msg: String) {
+        flurry::send_local(format!("ping at {}", flurry::now()));
+    }
+
+    fn on_local_message(&mut self, _msg: &str) {
+        let to = self.other;
+        flurry::spawn(async move {
+            flurry::send(to, "ping".to_string()).await;
+            flurry::send_local(format!("ack at {}", flurry::now()));
+        });
+    }
+}
+
+fn ping_pair() -> flurry::System {
+    let mut system = flurry::System::with_seed(7);
+    system.add_process(PingProcess { other: 1 });
+    system.add_process(PingProcess { other: 0 });
+    system
+}
+
+fn round_trip(system: &mut flurry::System) -> Vec<f64> {
+    system.send_local_message(0, "go");
+    while system.step() {}
+    system
+        .get_trace()
+        .iter()
+        .filter(|event| {
+            matches!(
+                event.kind,
+                EventKind::MessageDelivered(..) | EventKind::AckDelivered(..)
+            )
+        })
+        .map(|event| event.time)
+        .collect()
+}
+
+#[test]
+fn latency() {
+    let mut system = ping_pair();
+    system.set_latency(Latency::Constant(1.5));
+    assert_eq!(round_trip(&mut system), vec![1.5, 3.0]);
+    assert_eq!(system.read_local(0), vec!["ack at 3"]);
+    assert_eq!(system.read_local(1), vec!["ping at 1.5"]);
+    assert_eq!(system.now(), 3.0);
+
+    let mut system = ping_pair();
+    system.set_latency(Latency::Constant(1.0));
+    system.set_link_latency(1, 0, Latency::custom(|u| 10.0 + u));
+    let times = round_trip(&mut system);
+    assert_eq!(times[0], 1.0);
+    assert!(times[1] >= 11.0 && times[1] < 12.0);
+
+    let exponential = || {
+        let mut system = ping_pair();
+        system.set_latency(Latency::Exponential(2.0));
+        round_trip(&mut system)
+    };
+    let times = exponential();
+    assert!(times[0] > 0.0 && times[1] > times[0]);
+    assert_eq!(times, exponential());
+}
+
+/// Reports local message after the delay given in it.
+struct SleepyProcess;
+
+impl flurry::Process for SleepyProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, _msg: String) {}
+
+    fn on_local_message(&mut self, msg: &str) {
+        let delay: f64 = msg.parse().unwrap();
+        flurry::spawn(async move {
+            flurry::sleep(delay).await;
+            flurry::send_local(format!("woke up after {delay}"));
+        });
+    }
+}
+
+#[test]
+fn timers() {
+    let mut system = flurry::System::default();
+    system.add_process(SleepyProcess);
+    system.set_latency(Latency::Constant(0.0));
+    system.send_local_message(0, "10");
+    system.send_local_message(0, "2.5");
+    assert_eq!(system.get_pending_events_count(), 2);
+    assert!(system.step());
+    assert_eq!(system.now(), 2.5);
+    assert!(system.step());
+    assert_eq!(system.now(), 10.0);
+    assert!(!system.step());
+    assert_eq!(
+        system.read_local(0),
+        vec!["woke up after 2.5", "woke up after 10"]
+    );
+
+    let mut system = flurry::System::default();
+    system.add_process(SleepyProcess);
+    system.send_local_message(0, "10");
+    system.send_local_message(0, "2.5");
+    system.apply_pending_event(0);
+    assert_eq!(system.read_local(0), vec!["woke up after 10"]);
+    assert!(matches!(
+        system.get_pending_events()[..],
+        [EventKind::TimerFired(0, 1)]
+    ));
+    assert_eq!(
+        system.try_drop_pending_event(0),
+        Err(flurry::Error::NotMessage(0))
+    );
+}
+
+#[test]
+fn timers_in_runtime() {
+    let mut runtime = Runtime::new();
+    runtime
+        .add_process("127.0.0.1:0".parse().unwrap(), || SleepyProcess)
+        .unwrap();
+    let runtime = runtime.start().unwrap();
+    runtime.send_local_message(0, "0.2");
+    runtime.send_local_message(0, "0.05");
+    let msgs = runtime.wait_local(0, 2, Duration::from_secs(10));
+    assert_eq!(msgs, vec!["woke up after 0.05", "woke up after 0.2"]);
+    runtime.shutdown();
+}
+
+/// Reports local time after the delay given in local message.
+struct ClockProcess;
+
+impl flurry::Process for ClockProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, _msg: String) {}
+
+    fn on_local_message(&mut self, msg: &str) {
+        let delay: f64 = msg.parse().unwrap();
+        flurry::spawn(async move {
+            flurry::sleep(delay).await;
+            flurry::send_local(format!("woke up at {}", flurry::now()));
+        });
+    }
+}
+
+fn skewed_system() -> flurry::System {
+    let mut system = flurry::System::default();
+    system.add_process(ClockProcess);
+    system.add_process(ClockProcess);
+    system.set_latency(Latency::Constant(0.0));
+    system.set_clock(0, LocalClock::new(100.0, 1.0));
+    system
+}
+
+#[test]
+fn local_clocks() {
+    let clock = LocalClock::new(100.0, 1.0);
+    assert_eq!((clock.offset(), clock.drift()), (100.0, 1.0));
+
+    let mut system = skewed_system();
+    system.send_local_message(0, "4");
+    system.send_local_message(1, "4");
+    system.jump_clock(1, 3.0, 0.5);
+
+    assert!(system.step());
+    assert_eq!(system.now(), 2.0);
+    assert_eq!(system.read_local(0), vec!["woke up at 104"]);
+    assert!(system.step());
+    assert_eq!(system.local_time(1), 3.5);
+    assert!(system.step());
+    assert_eq!(system.now(), 3.5);
+    assert_eq!(system.read_local(1), vec!["woke up at 4"]);
+    assert!(!system.step());
+    assert!(system
+        .get_trace()
+        .iter()
+        .any(|event| event.kind == EventKind::ClockJumped(1, 0)));
+
+    let mut written = Vec::new();
+    system.get_schedule().write(&mut written).unwrap();
+    let schedule = Schedule::read(written.as_slice()).unwrap();
+    assert_eq!(schedule, system.get_schedule());
+    let replayed = flurry::replay(&schedule, skewed_system).unwrap();
+    assert_eq!(replayed.get_trace(), system.get_trace());
+}
@@ -0,0 +1,88 @@
+use flurry::{json::Value, Latency, Topology};
+
+#[test]
+fn shapes() {
+    assert_eq!(Topology::ring(4).neighbours(0), [1, 3]);
+    assert_eq!(Topology::ring(2).neighbours(1), [0]);
+    assert_eq!(
+        Topology::star(3).adjacency(),
+        vec![vec![1, 2], vec![0], vec![0]]
+    );
+    assert_eq!(Topology::full_mesh(3).neighbours(1), [0, 2]);
+
+    let value = Value::parse(r#"{"n0":["n1"],"n1":["n0","n2"],"n2":["n1"]}"#).unwrap();
+    let line = Topology::from_maelstrom(&value).unwrap();
+    assert_eq!(line, Topology::custom(vec![vec![1], vec![0, 2], vec![1]]));
+    assert_eq!(line.to_maelstrom(), value);
+    assert!(Topology::from_maelstrom(&Value::parse(r#"{"a":[]}"#).unwrap()).is_err());
+
+    assert_eq!(line.route(0, 2), None);
+    assert_eq!(line.with_routing().route(0, 2), Some(vec![0, 1, 2]));
+    assert_eq!(
+        Topology::ring(6).with_routing().route(0, 4),
+        Some(vec![0, 5, 4])
+    );
+}
+
+/// Sends message to the process given in local message and reports the ack time or error.
+struct Sender;
+
+impl flurry::Process for Sender {
+    fn on_message(&mut self, _from: flurry::ProcessId, _msg: String) {}
+
+    fn on_local_message(&mut self, msg: &str) {
+        match flurry::try_send(msg.parse().unwrap(), "hi".to_string()) {
+            Ok(ack) => {
+                flurry::spawn(async move {
+                    ack.await;
+                    flurry::send_local(format!("acked at {}", flurry::now()));
+                });
+            }
+            Err(err) => flurry::send_local(err.to_string()),
+        }
+    }
+}
+
+fn line(topology: Topology) -> flurry::System {
+    let mut system = flurry::System::default();
+    for _ in 0..3 {
+        system.add_process(Sender);
+    }
+    system.set_topology(topology);
+    system.set_latency(Latency::Constant(1.0));
+    system
+}
+
+#[test]
+fn missing_links() {
+    let topology = Topology::custom(vec![vec![1], vec![0, 2], vec![1]]);
+
+    let mut system = line(topology.clone());
+    system.send_local_message(0, "1");
+    system.send_local_message(0, "2");
+    while system.step() {}
+    assert_eq!(
+        system.read_local(0),
+        vec!["no link from process 0 to process 2", "acked at 2"]
+    );
+
+    let mut system = line(topology.with_routing());
+    system.send_local_message(0, "2");
+    while system.step() {}
+    assert_eq!(system.read_local(0), vec!["acked at 4"]);
+    let delivered = &system.get_trace()[2];
+    assert!(matches!(
+        delivered.kind,
+        flurry::EventKind::MessageDelivered(0, 2, _, _)
+    ));
+    assert_eq!(delivered.time, 2.0);
+}
+
+#[test]
+fn links_cut_in_flight() {
+    let mut system = line(Topology::full_mesh(3));
+    system.send_local_message(0, "1");
+    system.set_topology(Topology::custom(vec![vec![], vec![], vec![]]));
+    while system.step() {}
+    assert_eq!(system.read_local(0), vec!["acked at 2"]);
+}
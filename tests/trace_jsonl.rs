@@ -0,0 +1,89 @@
+use flurry::EventKind;
+
+struct EchoProcess {}
+
+impl flurry::Process for EchoProcess {
+    fn on_message(&mut self, from: flurry::ProcessId, msg: String) {
+        flurry::send_local(format!("got \"{msg}\"\n"));
+        if from == 0 {
+            flurry::spawn(async move {
+                flurry::send(from, msg).await;
+            });
+        }
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        let msg = msg.to_string();
+        flurry::spawn(async move {
+            flurry::send(1, msg).await;
+        });
+    }
+}
+
+fn run() -> flurry::System {
+    let mut system = flurry::System::default();
+    system.add_process(EchoProcess {});
+    system.add_process(EchoProcess {});
+    system.send_local_message(0, "hello, \"world\" \\ привет");
+    while system.get_pending_events_count() > 0 {
+        system.apply_pending_event(0);
+    }
+    system
+}
+
+#[test]
+fn write_and_read_trace() {
+    let system = run();
+    let trace = system.get_trace();
+
+    let mut buf = Vec::new();
+    flurry::write_trace(&trace, &mut buf).unwrap();
+
+    let text = String::from_utf8(buf.clone()).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        format!(
+            "{{\"format\":\"flurry-trace\",\"version\":{}}}",
+            flurry::TRACE_FORMAT_VERSION
+        )
+    );
+    assert_eq!(
+        lines.next().unwrap(),
+        "{\"time\":0,\"kind\":\"UserLocalMessage\",\"proc\":0,\
+        \"msg\":\"hello, \\\"world\\\" \\\\ привет\"}"
+    );
+    assert_eq!(lines.count(), trace.len() - 1);
+
+    let read = flurry::read_trace(buf.as_slice()).unwrap();
+    assert_eq!(read, trace);
+    assert!(read
+        .iter()
+        .any(|e| matches!(&e.kind, EventKind::ProcLocalMessage(1, msg) if msg.ends_with('\n'))));
+}
+
+#[test]
+fn read_invalid_trace() {
+    let err = flurry::read_trace("".as_bytes()).unwrap_err();
+    assert!(matches!(err, flurry::TraceReadError::Header(_)));
+
+    let err = flurry::read_trace("{\"format\":\"flurry-trace\",\"version\":999}\n".as_bytes())
+        .unwrap_err();
+    assert!(matches!(err, flurry::TraceReadError::Version(999)));
+
+    let err = flurry::read_trace(
+        "{\"format\":\"flurry-trace\",\"version\":1}\n\
+        {\"time\":0,\"kind\":\"AckSent\",\"from\":0}\n"
+            .as_bytes(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, flurry::TraceReadError::Parse { line: 2, .. }));
+
+    let old = flurry::read_trace(
+        "{\"format\":\"flurry-trace\",\"version\":1}\n\
+        {\"time\":0,\"kind\":\"UserLocalMessage\",\"proc\":0,\"msg\":\"hi\"}\n"
+            .as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(old.len(), 1);
+}
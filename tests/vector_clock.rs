@@ -0,0 +1,83 @@
+use flurry::EventKind;
+
+struct ForwardProcess {
+    next: Option<flurry::ProcessId>,
+}
+
+impl flurry::Process for ForwardProcess {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        if let Some(next) = self.next {
+            flurry::spawn(async move {
+                flurry::send(next, msg).await;
+            });
+        }
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        flurry::send_local(msg.to_string());
+        if let Some(next) = self.next {
+            let msg = msg.to_string();
+            flurry::spawn(async move {
+                flurry::send(next, msg).await;
+            });
+        }
+    }
+}
+
+fn run() -> flurry::System {
+    let mut system = flurry::System::default();
+    system.add_process(ForwardProcess { next: Some(1) });
+    system.add_process(ForwardProcess { next: Some(2) });
+    system.add_process(ForwardProcess { next: None });
+    system.send_local_message(2, "independent");
+    system.send_local_message(0, "chain");
+    while system.get_pending_events_count() > 0 {
+        system.apply_pending_event(0);
+    }
+    system
+}
+
+fn find(trace: &[flurry::Event], pred: impl Fn(&EventKind) -> bool) -> &flurry::Event {
+    trace.iter().find(|e| pred(&e.kind)).unwrap()
+}
+
+#[test]
+fn happens_before() {
+    let trace = run().get_trace();
+
+    let first_send = find(&trace, |k| matches!(k, EventKind::MessageSent(0, 1, _, _)));
+    let last_delivery = find(&trace, |k| {
+        matches!(k, EventKind::MessageDelivered(1, 2, _, _))
+    });
+    let independent = find(&trace, |k| matches!(k, EventKind::ProcLocalMessage(2, _)));
+
+    assert_eq!(first_send.clock.get(0), 3);
+    assert!(first_send.clock.happens_before(&last_delivery.clock));
+    assert!(!last_delivery.clock.happens_before(&first_send.clock));
+    assert!(independent.clock.happens_before(&last_delivery.clock));
+    assert!(independent.clock.concurrent(&first_send.clock));
+
+    for (i, event) in trace.iter().enumerate() {
+        for later in trace[i + 1..].iter() {
+            assert!(!later.clock.happens_before(&event.clock));
+        }
+    }
+}
+
+#[test]
+fn shiviz() {
+    let trace = run().get_trace();
+    let mut buf = Vec::new();
+    flurry::write_shiviz(&trace, &mut buf).unwrap();
+    let log = String::from_utf8(buf).unwrap();
+    let lines: Vec<_> = log.lines().collect();
+    assert_eq!(lines.len(), trace.len() * 2);
+    assert_eq!(lines[0], "user message: independent");
+    assert_eq!(lines[1], "P2 {\"P2\":1}");
+    assert_eq!(lines[4], "user message: chain");
+    assert_eq!(lines[5], "P0 {\"P0\":1}");
+    assert_eq!(lines[8], "send m0 to P1: chain");
+    assert_eq!(lines[9], "P0 {\"P0\":3}");
+    assert_eq!(lines[10], "receive m0 from P0: chain");
+    assert_eq!(lines[11], "P1 {\"P0\":3,\"P1\":1}");
+}
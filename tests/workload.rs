@@ -0,0 +1,202 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use flurry::{
+    json::Value,
+    workload::{self, Broadcast, Echo, GCounter, Kafka, UniqueIds},
+};
+
+/// Serves every workload on its own, without talking to other nodes.
+#[derive(Default)]
+struct SingleNode {
+    prefix: String,
+    generated: u64,
+    counter: u64,
+    logs: BTreeMap<String, Vec<u64>>,
+    committed: Vec<(String, Value)>,
+}
+
+impl flurry::Process for SingleNode {
+    fn on_message(&mut self, _from: flurry::ProcessId, _msg: String) {}
+
+    fn on_local_message(&mut self, msg: &str) {
+        let body = Value::parse(msg).unwrap();
+        let kind = body.get("type").and_then(Value::as_str).unwrap();
+        let mut reply = Value::object([
+            ("type", format!("{kind}_ok").into()),
+            ("in_reply_to", body.get("msg_id").unwrap().clone()),
+        ]);
+        match kind {
+            "echo" => reply.set("echo", body.get("echo").unwrap().clone()),
+            "generate" => {
+                self.generated += 1;
+                reply.set("id", format!("{}{}", self.prefix, self.generated).into());
+            }
+            "add" => self.counter += body.get("delta").and_then(Value::as_u64).unwrap(),
+            "read" => reply.set("value", self.counter.into()),
+            "send" => {
+                let key = body.get("key").and_then(Value::as_str).unwrap();
+                let log = self.logs.entry(key.to_string()).or_default();
+                log.push(body.get("msg").and_then(Value::as_u64).unwrap());
+                reply.set("offset", (log.len() - 1).into());
+            }
+            "poll" => {
+                let msgs = self
+                    .logs
+                    .iter()
+                    .map(|(key, log)| {
+                        let entries = log
+                            .iter()
+                            .enumerate()
+                            .map(|(offset, msg)| vec![offset.into(), (*msg).into()].into())
+                            .collect::<Vec<Value>>();
+                        (key.clone(), entries.into())
+                    })
+                    .collect();
+                reply.set("msgs", Value::Object(msgs));
+            }
+            "commit_offsets" => {
+                let Some(Value::Object(offsets)) = body.get("offsets") else {
+                    panic!("offsets must be an object");
+                };
+                self.committed = offsets.clone();
+            }
+            "list_committed_offsets" => reply.set("offsets", Value::Object(self.committed.clone())),
+            other => panic!("unexpected request {other}"),
+        }
+        flurry::send_local(reply.to_string());
+    }
+}
+
+fn single_nodes(count: usize, prefix: impl Fn(usize) -> String) -> flurry::System {
+    let mut system = flurry::System::default();
+    for proc in 0..count {
+        system.add_process(SingleNode {
+            prefix: prefix(proc),
+            ..Default::default()
+        });
+    }
+    system
+}
+
+#[test]
+fn single_node_workloads() {
+    let prefix = |proc| format!("{proc}-");
+    workload::run(&mut single_nodes(3, prefix), Echo::new(3, 5), 0).unwrap();
+    let ids = workload::run(&mut single_nodes(3, prefix), UniqueIds::new(3, 5), 0).unwrap();
+    assert_eq!(ids.generated(), 15);
+    workload::run(&mut single_nodes(1, prefix), GCounter::new(1, 10), 0).unwrap();
+    workload::run(&mut single_nodes(1, prefix), Kafka::new(1, 3, 10), 0).unwrap();
+
+    let err = workload::run(
+        &mut single_nodes(2, |_| String::new()),
+        UniqueIds::new(2, 1),
+        0,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err, r#"id "1" was generated by n0 and n1"#);
+}
+
+/// Forwards every new message to its neighbours, unless it is `lazy`.
+struct Gossip {
+    me: flurry::ProcessId,
+    lazy: bool,
+    neighbours: Vec<flurry::ProcessId>,
+    seen: BTreeSet<u64>,
+}
+
+impl Gossip {
+    fn add(&mut self, msg: u64) {
+        if !self.seen.insert(msg) || self.lazy {
+            return;
+        }
+        for to in self.neighbours.clone() {
+            flurry::spawn(async move { flurry::send(to, msg.to_string()).await });
+        }
+    }
+}
+
+impl flurry::Process for Gossip {
+    fn on_message(&mut self, _from: flurry::ProcessId, msg: String) {
+        self.add(msg.parse().unwrap());
+    }
+
+    fn on_local_message(&mut self, msg: &str) {
+        let body = Value::parse(msg).unwrap();
+        let kind = body.get("type").and_then(Value::as_str).unwrap();
+        let mut reply = Value::object([
+            ("type", format!("{kind}_ok").into()),
+            ("in_reply_to", body.get("msg_id").unwrap().clone()),
+        ]);
+        match kind {
+            "topology" => {
+                let topology = body.get("topology").unwrap();
+                self.neighbours = topology
+                    .get(&workload::node_id(self.me))
+                    .and_then(Value::as_array)
+                    .unwrap()
+                    .iter()
+                    .map(|node| workload::parse_node_id(node.as_str().unwrap()).unwrap())
+                    .collect();
+            }
+            "broadcast" => self.add(body.get("message").and_then(Value::as_u64).unwrap()),
+            "read" => {
+                let messages: Vec<Value> = self.seen.iter().map(|m| (*m).into()).collect();
+                reply.set("messages", messages.into());
+            }
+            other => panic!("unexpected request {other}"),
+        }
+        flurry::send_local(reply.to_string());
+    }
+}
+
+fn gossip_system(nodes: usize, lazy: bool) -> flurry::System {
+    let mut system = flurry::System::default();
+    for me in 0..nodes {
+        system.add_process(Gossip {
+            me,
+            lazy,
+            neighbours: Vec::new(),
+            seen: BTreeSet::new(),
+        });
+    }
+    system
+}
+
+#[test]
+fn broadcast_in_ring() {
+    let ring = (0..5).map(|proc| vec![(proc + 1) % 5]).collect::<Vec<_>>();
+    for seed in 0..10 {
+        let workload = Broadcast::new(5, 8).with_topology(ring.clone());
+        workload::run(&mut gossip_system(5, false), workload, seed).unwrap();
+    }
+
+    let workload = Broadcast::new(5, 8).with_topology(ring).with_concurrency(3);
+    let err = workload::run(&mut gossip_system(5, true), workload, 0)
+        .err()
+        .unwrap();
+    assert_eq!(err, "n0 lost message 1");
+}
+
+struct Silent;
+
+impl flurry::Process for Silent {
+    fn on_message(&mut self, _from: flurry::ProcessId, _msg: String) {}
+
+    fn on_local_message(&mut self, _msg: &str) {}
+}
+
+#[test]
+fn unanswered_request() {
+    let mut system = flurry::System::default();
+    system.add_process(Silent);
+    let mut driver = workload::Driver::new(Echo::new(1, 1));
+    assert_eq!(driver.step(&mut system), Ok(false));
+    assert_eq!(
+        driver.step(&mut system),
+        Err(
+            r#"n0 did not answer request {"type":"echo","echo":"echo 0 to n0","msg_id":0}"#
+                .to_string()
+        )
+    );
+}